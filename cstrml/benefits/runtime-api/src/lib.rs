@@ -0,0 +1,20 @@
+// Copyright (C) 2019-2021 Crust Network Technologies Ltd.
+// This file is part of Crust.
+
+//! Runtime API definition for the benefits module.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Codec, HasCompact};
+pub use benefits::BenefitQuota;
+
+sp_api::decl_runtime_apis! {
+    pub trait BenefitsApi<AccountId, Balance> where
+        AccountId: Codec,
+        Balance: Codec + HasCompact,
+    {
+        /// Get the fee reduction quota and the free report works count of the account
+        /// in the active era, including the remaining part.
+        fn benefit_quota(who: AccountId) -> BenefitQuota<Balance>;
+    }
+}
@@ -56,6 +56,14 @@ benchmarks! {
         assert_eq!(Benefits::<T>::market_benefits(&user).unlocking_funds.len(), 0);
     }
 
+    set_auto_compounding {
+        let user = create_funded_user::<T>("user", 100);
+        Benefits::<T>::set_auto_compounding(RawOrigin::Signed(user.clone()).into(), Perbill::from_percent(40), FundsType::SWORK).expect("Something wrong during set auto compounding");
+    }: _(RawOrigin::Signed(user.clone()), Perbill::from_percent(60), FundsType::MARKET)
+    verify {
+        assert_eq!(Benefits::<T>::auto_compounding_ratios(&user, FundsType::MARKET), Perbill::from_percent(60));
+    }
+
 }
 
 
@@ -92,4 +100,11 @@ mod tests {
             assert_ok!(test_benchmark_withdraw_benefit_funds::<Test>());
         });
     }
+
+    #[test]
+    fn set_auto_compounding() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_set_auto_compounding::<Test>());
+        });
+    }
 }
\ No newline at end of file
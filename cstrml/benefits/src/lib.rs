@@ -21,10 +21,10 @@ use sp_runtime::DispatchError;
 
 use sp_runtime::{
     DispatchResult, Perbill, RuntimeDebug,
-    traits::{Zero, Saturating, AtLeast32BitUnsigned}, SaturatedConversion
+    traits::{Zero, Saturating, AtLeast32BitUnsigned, Convert}, SaturatedConversion
 };
 
-use primitives::{EraIndex, traits::BenefitInterface};
+use primitives::{EraIndex, traits::{BenefitInterface, CsmInterface}};
 
 #[cfg(test)]
 mod mock;
@@ -48,6 +48,7 @@ pub trait WeightInfo {
     fn cut_benefit_funds() -> Weight;
     fn rebond_benefit_funds() -> Weight;
     fn withdraw_benefit_funds() -> Weight;
+    fn set_auto_compounding() -> Weight;
 }
 
 pub trait Config: frame_system::Config {
@@ -61,6 +62,11 @@ pub trait Config: frame_system::Config {
     type BenefitMarketCostRatio: Get<Perbill>;
     /// Number of eras that staked funds must remain bonded for.
     type BondingDuration: Get<EraIndex>;
+    // The locking and guaranteed CSM
+    type CsmInterface: CsmInterface<Self::AccountId, BalanceOf<Self>>;
+    // Convert the locking and guaranteed CSM into the extra fee reduction quota,
+    // from the smallest unit of CSM to the smallest unit of the currency
+    type CsmToFeeReductionQuota: Convert<BalanceOf<Self>, BalanceOf<Self>>;
     /// Weight information for extrinsics in this pallet.
     type WeightInfo: WeightInfo;
 }
@@ -86,6 +92,20 @@ decl_event!(
         /// The first item is the account.
         /// The second item is the withdrawed benefit amount.
         WithdrawBenefitFundsSuccess(AccountId, Balance),
+        /// Set auto compounding success
+        /// The first item is the account.
+        /// The second item is the ratio of rewards added into the funds type.
+        SetAutoCompoundingSuccess(AccountId, Perbill, FundsType),
+        /// The benefits of the new era have been updated
+        /// The first item is the new active era.
+        /// The second item is the total fee reduction quota.
+        /// The third item is the number of accounts with active market funds.
+        /// The fourth item is the number of accounts with active swork funds.
+        EraBenefitsUpdated(EraIndex, Balance, u32, u32),
+        /// Compound reward failed
+        /// The first item is the account.
+        /// The second item is the reward amount which cannot be added into the funds type.
+        CompoundRewardFailed(AccountId, Balance, FundsType),
     }
 );
 
@@ -100,7 +120,9 @@ decl_error! {
         /// Can not rebond without unlocking chunks.
         NoUnlockChunk,
         /// Can not bond with value less than minimum balance.
-        InsufficientValue
+        InsufficientValue,
+        /// The total auto compounding ratio of all funds types exceeds 100%
+        InvalidCompoundingRatio
     }
 }
 
@@ -130,6 +152,34 @@ pub struct EraBenefits<Balance: HasCompact> {
     pub used_fee_reduction_quota: Balance,
     /// The latest active era index
     #[codec(compact)]
+    pub active_era: EraIndex,
+    /// The number of accounts with active market funds
+    pub market_participants: u32,
+    /// The number of accounts with active swork funds
+    pub swork_participants: u32
+}
+
+/// The benefit quota of an account in the active era
+#[derive(Debug, PartialEq, Eq, Clone, Encode, Decode, Default)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct BenefitQuota<Balance: HasCompact> {
+    /// The fee reduction quota according to the market active funds and the locking CSM
+    #[codec(compact)]
+    pub total_fee_reduction_quota: Balance,
+    /// The used fee reduction quota
+    #[codec(compact)]
+    pub used_fee_reduction_quota: Balance,
+    /// The remaining fee reduction quota, which is also limited by the total quota of the era
+    #[codec(compact)]
+    pub remaining_fee_reduction_quota: Balance,
+    /// The free report works count according to the swork active funds
+    pub total_fee_reduction_count: u32,
+    /// The used free report works count
+    pub used_fee_reduction_count: u32,
+    /// The remaining free report works count
+    pub remaining_fee_reduction_count: u32,
+    /// The active era index
+    #[codec(compact)]
     pub active_era: EraIndex
 }
 
@@ -293,6 +343,10 @@ impl<T: Config> BenefitInterface<<T as frame_system::Config>::AccountId, Balance
         Self::maybe_do_free_count(who)
     }
 
+    fn maybe_compound_reward(who: &<T as frame_system::Config>::AccountId, reward: BalanceOf<T>) -> BalanceOf<T> {
+        Self::maybe_do_compound_reward(who, reward)
+    }
+
     fn get_collateral_and_reward(who: &<T as frame_system::Config>::AccountId) -> (BalanceOf<T>, BalanceOf<T>) {
         let market_benefits = Self::market_benefits(who);
         (market_benefits.active_funds, market_benefits.file_reward)
@@ -307,13 +361,28 @@ impl<T: Config> BenefitInterface<<T as frame_system::Config>::AccountId, Balance
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Encode, Decode)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Encode, Decode)]
 pub enum FundsType {
     SWORK = 0,
     MARKET = 1,
 }
 
 
+// A value placed in storage that represents the current version of the Benefits storage.
+// This value is used by the `on_runtime_upgrade` logic to determine whether we run
+// storage migration logic.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+enum Releases {
+    V1_0_0,
+    V2_0_0,
+}
+
+impl Default for Releases {
+    fn default() -> Self {
+        Releases::V1_0_0
+    }
+}
+
 decl_storage! {
     trait Store for Module<T: Config> as Benefits {
         /// The global benefits information
@@ -322,6 +391,12 @@ decl_storage! {
         MarketBenefits get(fn market_benefits): map hasher(blake2_128_concat) T::AccountId => MarketBenefit<BalanceOf<T>>;
         /// The sworker benefit
         SworkBenefits get(fn swork_benefits): map hasher(blake2_128_concat) T::AccountId => SworkBenefit<BalanceOf<T>>;
+        /// The ratio of rewards automatically added into each type of benefit funds
+        AutoCompoundingRatios get(fn auto_compounding_ratios): double_map hasher(blake2_128_concat) T::AccountId, hasher(twox_64_concat) FundsType => Perbill;
+        /// Storage version of the pallet.
+        ///
+        /// This is set to v2.0.0 after the participants counters are migrated.
+        StorageVersion: Releases;
     }
 }
 
@@ -331,36 +406,19 @@ decl_module! {
 
         fn deposit_event() = default;
 
+        fn on_runtime_upgrade() -> Weight {
+            if StorageVersion::get() == Releases::V1_0_0 {
+                Self::migrate_participants()
+            } else {
+                0
+            }
+        }
+
         /// Add benefit funds
         #[weight = T::WeightInfo::add_benefit_funds()]
         pub fn add_benefit_funds(origin, #[compact] value: BalanceOf<T>, funds_type: FundsType) -> DispatchResult {
             let who = ensure_signed(origin)?;
-
-            // 1. Reserve the currency
-            T::Currency::reserve(&who, value.clone()).map_err(|_| Error::<T>::InsuffientBalance)?;
-
-            // 2. Change the benefits status
-            match funds_type {
-                FundsType::SWORK => {
-                    <SworkBenefits<T>>::mutate(&who, |swork_benefit| {
-                        swork_benefit.active_funds += value.clone();
-                        swork_benefit.total_funds += value.clone();
-                        swork_benefit.total_fee_reduction_count = Self::calculate_total_fee_reduction_count(&swork_benefit.active_funds);
-                    });
-                },
-                FundsType::MARKET => {
-                    <MarketBenefits<T>>::mutate(&who, |market_benefit| {
-                        market_benefit.active_funds += value.clone();
-                        market_benefit.total_funds += value.clone();
-                    });
-                    <CurrentBenefits<T>>::mutate(|benefits| { benefits.total_market_active_funds += value.clone();});
-                }
-            }
-
-            // 3. Emit success
-            Self::deposit_event(RawEvent::AddBenefitFundsSuccess(who.clone(), value, funds_type));
-
-            Ok(())
+            Self::do_add_benefit_funds(&who, value, funds_type)
         }
 
         /// Cut benefit funds
@@ -384,6 +442,7 @@ decl_module! {
                     value = value.min(benefit.active_funds);
 
                     if !value.is_zero() {
+                        let old_active_funds = benefit.active_funds;
                         benefit.active_funds -= value;
 
                         // 4. Avoid there being a dust balance left in the benefit system.
@@ -396,6 +455,7 @@ decl_module! {
                         let era = Self::current_benefits().active_era + T::BondingDuration::get();
                         benefit.unlocking_funds.push(FundsUnlockChunk { value, era });
                         benefit.total_fee_reduction_count = Self::calculate_total_fee_reduction_count(&benefit.active_funds);
+                        Self::update_participants(FundsType::SWORK, old_active_funds, benefit.active_funds);
                         <SworkBenefits<T>>::insert(&who, benefit);
                     }
                 },
@@ -414,6 +474,7 @@ decl_module! {
                     value = value.min(benefit.active_funds);
 
                     if !value.is_zero() {
+                        let old_active_funds = benefit.active_funds;
                         benefit.active_funds -= value;
 
                         // 4. Avoid there being a dust balance left in the benefit system.
@@ -425,6 +486,7 @@ decl_module! {
                         // 5. Update benefit
                         let era = Self::current_benefits().active_era + T::BondingDuration::get();
                         benefit.unlocking_funds.push(FundsUnlockChunk { value, era });
+                        Self::update_participants(FundsType::MARKET, old_active_funds, benefit.active_funds);
                         <MarketBenefits<T>>::insert(&who, benefit);
                         <CurrentBenefits<T>>::mutate(|benefits| { benefits.total_market_active_funds = benefits.total_market_active_funds.saturating_sub(value.clone());});
                     }
@@ -492,6 +554,35 @@ decl_module! {
             Ok(())
         }
 
+        /// Set the ratio of rewards automatically added into the benefit funds
+        /// The total ratio of all funds types can't exceed 100%
+        #[weight = T::WeightInfo::set_auto_compounding()]
+        pub fn set_auto_compounding(origin, ratio: Perbill, funds_type: FundsType) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            // 1. Ensure the total ratio doesn't exceed 100%
+            let other_type = match funds_type {
+                FundsType::SWORK => FundsType::MARKET,
+                FundsType::MARKET => FundsType::SWORK
+            };
+            let other_ratio = Self::auto_compounding_ratios(&who, other_type);
+            ensure!(
+                ratio.deconstruct() as u64 + other_ratio.deconstruct() as u64 <= Perbill::one().deconstruct() as u64,
+                Error::<T>::InvalidCompoundingRatio
+            );
+
+            // 2. Set or remove the ratio
+            if ratio.is_zero() {
+                <AutoCompoundingRatios<T>>::remove(&who, funds_type);
+            } else {
+                <AutoCompoundingRatios<T>>::insert(&who, funds_type, ratio);
+            }
+
+            // 3. Emit success
+            Self::deposit_event(RawEvent::SetAutoCompoundingSuccess(who, ratio, funds_type));
+            Ok(())
+        }
+
         /// Withdraw benefit funds
         #[weight = T::WeightInfo::rebond_benefit_funds()]
         pub fn rebond_benefit_funds(origin, #[compact] value: BalanceOf<T>, funds_type: FundsType) -> DispatchResult {
@@ -502,6 +593,7 @@ decl_module! {
                     // 1. Get benefit
                     ensure!(<SworkBenefits<T>>::contains_key(&who), Error::<T>::InvalidTarget);
                     let mut benefit = Self::swork_benefits(&who);
+                    let old_active_funds = benefit.active_funds;
                     ensure!(!benefit.unlocking_funds.is_empty(), Error::<T>::NoUnlockChunk);
 
                     // 2. Rebond benefit
@@ -510,6 +602,7 @@ decl_module! {
                     ensure!(benefit.active_funds >= T::Currency::minimum_balance(), Error::<T>::InsufficientValue);
                     // 3. Update total fee reduction count according to active funds
                     benefit.total_fee_reduction_count = Self::calculate_total_fee_reduction_count(&benefit.active_funds);
+                    Self::update_participants(FundsType::SWORK, old_active_funds, benefit.active_funds);
                     <SworkBenefits<T>>::insert(&who, benefit);
                 },
                 FundsType::MARKET => {
@@ -527,6 +620,7 @@ decl_module! {
                     <MarketBenefits<T>>::insert(&who, benefit);
                     // 3. Update current benefits
                     <CurrentBenefits<T>>::mutate(|benefits| { benefits.total_market_active_funds = benefits.total_market_active_funds.saturating_add(new_active_funds).saturating_sub(old_active_funds);});
+                    Self::update_participants(FundsType::MARKET, old_active_funds, new_active_funds);
                 }
             };
             // 4. Send event
@@ -538,6 +632,57 @@ decl_module! {
 
 
 impl<T: Config> Module<T> {
+    pub fn do_add_benefit_funds(who: &T::AccountId, value: BalanceOf<T>, funds_type: FundsType) -> DispatchResult {
+        // 1. Reserve the currency
+        T::Currency::reserve(who, value.clone()).map_err(|_| Error::<T>::InsuffientBalance)?;
+
+        // 2. Change the benefits status
+        match funds_type {
+            FundsType::SWORK => {
+                let old_active_funds = Self::swork_benefits(who).active_funds;
+                Self::update_participants(FundsType::SWORK, old_active_funds, old_active_funds.saturating_add(value));
+                <SworkBenefits<T>>::mutate(who, |swork_benefit| {
+                    swork_benefit.active_funds += value.clone();
+                    swork_benefit.total_funds += value.clone();
+                    swork_benefit.total_fee_reduction_count = Self::calculate_total_fee_reduction_count(&swork_benefit.active_funds);
+                });
+            },
+            FundsType::MARKET => {
+                let old_active_funds = Self::market_benefits(who).active_funds;
+                Self::update_participants(FundsType::MARKET, old_active_funds, old_active_funds.saturating_add(value));
+                <MarketBenefits<T>>::mutate(who, |market_benefit| {
+                    market_benefit.active_funds += value.clone();
+                    market_benefit.total_funds += value.clone();
+                });
+                <CurrentBenefits<T>>::mutate(|benefits| { benefits.total_market_active_funds += value.clone();});
+            }
+        }
+
+        // 3. Emit success
+        Self::deposit_event(RawEvent::AddBenefitFundsSuccess(who.clone(), value, funds_type));
+
+        Ok(())
+    }
+
+    /// Add the configured ratios of the reward into the benefit funds
+    /// The return value is the total compounded amount
+    pub fn maybe_do_compound_reward(who: &T::AccountId, reward: BalanceOf<T>) -> BalanceOf<T> {
+        let mut compounded: BalanceOf<T> = Zero::zero();
+        for funds_type in vec![FundsType::SWORK, FundsType::MARKET] {
+            let value = Self::auto_compounding_ratios(who, funds_type) * reward;
+            if value.is_zero() {
+                continue;
+            }
+            // The reward might have been spent or locked, just skip it
+            if Self::do_add_benefit_funds(who, value, funds_type).is_ok() {
+                compounded = compounded.saturating_add(value);
+            } else {
+                Self::deposit_event(RawEvent::CompoundRewardFailed(who.clone(), value, funds_type));
+            }
+        }
+        compounded
+    }
+
     /// The return value is the used fee quota in the last era
     pub fn do_update_era_benefit(next_era: EraIndex, total_fee_reduction_quota: BalanceOf<T>) -> BalanceOf<T> {
         // Fetch overall benefits information
@@ -550,7 +695,9 @@ impl<T: Config> Module<T> {
         current_benefits.total_fee_reduction_quota = total_fee_reduction_quota;
         // Reset used benefits to zero
         current_benefits.used_fee_reduction_quota = Zero::zero();
+        let (market_participants, swork_participants) = (current_benefits.market_participants, current_benefits.swork_participants);
         <CurrentBenefits<T>>::put(current_benefits);
+        Self::deposit_event(RawEvent::EraBenefitsUpdated(next_era, total_fee_reduction_quota, market_participants, swork_participants));
         // Return the used benefits in the last era
         used_fee_reduction_quota
     }
@@ -577,7 +724,8 @@ impl<T: Config> Module<T> {
         // Calculate the own reduction limit
         let fee_reduction_benefits_quota = Self::calculate_fee_reduction_quota(market_benefit.active_funds,
                                                                                current_benefits.total_market_active_funds,
-                                                                               current_benefits.total_fee_reduction_quota);
+                                                                               current_benefits.total_fee_reduction_quota)
+            .saturating_add(Self::calculate_csm_fee_reduction_quota(who));
         // Try to free fee reduction
         // Check the person has his own fee reduction quota and the total benefits
         let fee_reduction_benefit_cost = T::BenefitMarketCostRatio::get() * fee;
@@ -612,6 +760,32 @@ impl<T: Config> Module<T> {
         result
     }
 
+    /// The benefit quota of the account after refreshing to the active era, used by the runtime api
+    pub fn benefit_quota(who: &T::AccountId) -> BenefitQuota<BalanceOf<T>> {
+        let current_benefits = Self::current_benefits();
+        let mut market_benefit = Self::market_benefits(who);
+        Self::maybe_refresh_market_benefit(current_benefits.active_era, &mut market_benefit);
+        let mut swork_benefit = Self::swork_benefits(who);
+        Self::maybe_refresh_swork_benefits(current_benefits.active_era, &mut swork_benefit);
+
+        let total_fee_reduction_quota = Self::calculate_fee_reduction_quota(market_benefit.active_funds,
+                                                                            current_benefits.total_market_active_funds,
+                                                                            current_benefits.total_fee_reduction_quota)
+            .saturating_add(Self::calculate_csm_fee_reduction_quota(who));
+        let remaining_fee_reduction_quota = total_fee_reduction_quota
+            .saturating_sub(market_benefit.used_fee_reduction_quota)
+            .min(current_benefits.total_fee_reduction_quota.saturating_sub(current_benefits.used_fee_reduction_quota));
+        BenefitQuota {
+            total_fee_reduction_quota,
+            used_fee_reduction_quota: market_benefit.used_fee_reduction_quota,
+            remaining_fee_reduction_quota,
+            total_fee_reduction_count: swork_benefit.total_fee_reduction_count,
+            used_fee_reduction_count: swork_benefit.used_fee_reduction_count,
+            remaining_fee_reduction_count: swork_benefit.total_fee_reduction_count.saturating_sub(swork_benefit.used_fee_reduction_count),
+            active_era: current_benefits.active_era
+        }
+    }
+
     fn check_and_update_swork_funds(who: &T::AccountId) {
         let mut swork_benefit = Self::swork_benefits(&who);
         let reserved_value = T::Currency::reserved_balance(who);
@@ -620,9 +794,11 @@ impl<T: Config> Module<T> {
         }
         // Something wrong, fix it
         let old_total_funds = swork_benefit.total_funds;
+        let old_active_funds = swork_benefit.active_funds;
         swork_benefit.total_funds = reserved_value;
         swork_benefit.active_funds = swork_benefit.active_funds.saturating_add(swork_benefit.total_funds).saturating_sub(old_total_funds);
         swork_benefit.total_fee_reduction_count = Self::calculate_total_fee_reduction_count(&swork_benefit.active_funds);
+        Self::update_participants(FundsType::SWORK, old_active_funds, swork_benefit.active_funds);
         <SworkBenefits<T>>::insert(&who, swork_benefit);
     }
 
@@ -638,13 +814,98 @@ impl<T: Config> Module<T> {
         market_benefit.total_funds = reserved_value;
         market_benefit.active_funds = market_benefit.active_funds.saturating_add(market_benefit.total_funds).saturating_sub(old_total_funds);
         <CurrentBenefits<T>>::mutate(|benefits| { benefits.total_market_active_funds = benefits.total_market_active_funds.saturating_add(market_benefit.active_funds).saturating_sub(old_active_funds);});
+        Self::update_participants(FundsType::MARKET, old_active_funds, market_benefit.active_funds);
         <MarketBenefits<T>>::insert(&who, market_benefit);
     }
 
+    /// Update the participants count when the active funds goes between zero and non-zero
+    fn update_participants(funds_type: FundsType, old_active_funds: BalanceOf<T>, new_active_funds: BalanceOf<T>) {
+        if old_active_funds.is_zero() == new_active_funds.is_zero() {
+            return;
+        }
+        <CurrentBenefits<T>>::mutate(|benefits| {
+            let participants = match funds_type {
+                FundsType::SWORK => &mut benefits.swork_participants,
+                FundsType::MARKET => &mut benefits.market_participants
+            };
+            if new_active_funds.is_zero() {
+                *participants = participants.saturating_sub(1);
+            } else {
+                *participants = participants.saturating_add(1);
+            }
+        });
+    }
+
+    /// Add the participants counters into the current benefits, and count them for one time
+    fn migrate_participants() -> Weight {
+        #[derive(Encode, Decode)]
+        struct OldEraBenefits<Balance: HasCompact> {
+            #[codec(compact)]
+            total_fee_reduction_quota: Balance,
+            #[codec(compact)]
+            total_market_active_funds: Balance,
+            #[codec(compact)]
+            used_fee_reduction_quota: Balance,
+            #[codec(compact)]
+            active_era: EraIndex
+        }
+
+        // Every benefit entry is read to count the participants
+        let mut scanned: u64 = 0;
+        let mut market_participants: u32 = 0;
+        for (_, benefit) in <MarketBenefits<T>>::iter() {
+            scanned += 1;
+            if !benefit.active_funds.is_zero() {
+                market_participants += 1;
+            }
+        }
+        let mut swork_participants: u32 = 0;
+        for (_, benefit) in <SworkBenefits<T>>::iter() {
+            scanned += 1;
+            if !benefit.active_funds.is_zero() {
+                swork_participants += 1;
+            }
+        }
+        let mut translated: u64 = 0;
+        let _ = <CurrentBenefits<T>>::translate::<OldEraBenefits<BalanceOf<T>>, _>(|maybe_old| {
+            if maybe_old.is_some() {
+                translated += 1;
+            }
+            maybe_old.map(|old| EraBenefits {
+                total_fee_reduction_quota: old.total_fee_reduction_quota,
+                total_market_active_funds: old.total_market_active_funds,
+                used_fee_reduction_quota: old.used_fee_reduction_quota,
+                active_era: old.active_era,
+                market_participants,
+                swork_participants
+            })
+        });
+
+        StorageVersion::put(Releases::V2_0_0);
+        // Reads: the scanned benefits, `CurrentBenefits` and `StorageVersion`
+        // Writes: the translated `CurrentBenefits` and `StorageVersion`
+        T::DbWeight::get().reads_writes(scanned + 2, translated + 1)
+    }
+
     pub fn calculate_fee_reduction_quota(market_active_funds: BalanceOf<T>, total_market_active_funds: BalanceOf<T>, total_fee_reduction_quota: BalanceOf<T>) -> BalanceOf<T> {
         Perbill::from_rational_approximation(market_active_funds, total_market_active_funds) * total_fee_reduction_quota
     }
 
+    /// The extra fee reduction quota given by the locking CSM of the account and the CSM guaranteed to it.
+    /// The locking CSM guaranteed to a target which still accepts guarantees is counted by the target only.
+    pub fn calculate_csm_fee_reduction_quota(who: &T::AccountId) -> BalanceOf<T> {
+        let own_csm = match T::CsmInterface::guarantee_of(who) {
+            Some((target, _)) if T::CsmInterface::guarantee_pref(&target).is_some() => Zero::zero(),
+            _ => T::CsmInterface::locked_csm(who)
+        };
+        let guaranteed_csm = if T::CsmInterface::guarantee_pref(who).is_some() {
+            T::CsmInterface::guaranteed_csm(who)
+        } else {
+            Zero::zero()
+        };
+        T::CsmToFeeReductionQuota::convert(own_csm.saturating_add(guaranteed_csm))
+    }
+
     pub fn calculate_total_fee_reduction_count(active_funds: &BalanceOf<T>) -> u32 {
         (*active_funds / T::BenefitReportWorkCost::get()).saturated_into()
     }
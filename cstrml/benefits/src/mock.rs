@@ -9,12 +9,20 @@ use sp_runtime::{
     traits::{BlakeTwo256, IdentityLookup}, testing::Header,
 };
 pub use sp_core::{crypto::{AccountId32, Ss58Codec}, H256};
+use std::{cell::RefCell, collections::HashMap};
 
 pub type AccountId = AccountId32;
 pub type Balance = u64;
 
 pub const ALICE: AccountId32 = AccountId32::new([1u8; 32]);
 pub const BOB: AccountId32 = AccountId32::new([2u8; 32]);
+pub const CHARLIE: AccountId32 = AccountId32::new([3u8; 32]);
+
+thread_local! {
+    static LOCKED_CSM: RefCell<HashMap<AccountId, Balance>> = RefCell::new(Default::default());
+    static CSM_GUARANTEES: RefCell<HashMap<AccountId, AccountId>> = RefCell::new(Default::default());
+    static CSM_GUARANTEE_PREFS: RefCell<HashMap<AccountId, u32>> = RefCell::new(Default::default());
+}
 
 parameter_types! {
     pub const BlockHashCount: u32 = 250;
@@ -65,6 +73,57 @@ parameter_types! {
     pub const BondingDuration: EraIndex = 2;
 }
 
+/// Each CSM gives 0.1 extra fee reduction quota
+pub struct TestCsmToFeeReductionQuota;
+
+impl Convert<Balance, Balance> for TestCsmToFeeReductionQuota {
+    fn convert(csm: Balance) -> Balance {
+        Perbill::from_percent(10) * csm
+    }
+}
+
+pub struct TestCsmInterface;
+
+impl CsmInterface<AccountId, Balance> for TestCsmInterface {
+    fn locked_csm(who: &AccountId) -> Balance {
+        LOCKED_CSM.with(|locked| locked.borrow().get(who).cloned().unwrap_or_default())
+    }
+
+    fn guaranteed_csm(target: &AccountId) -> Balance {
+        CSM_GUARANTEES.with(|guarantees| guarantees.borrow().iter()
+            .filter(|(_, guaranteed)| *guaranteed == target)
+            .map(|(who, _)| Self::locked_csm(who))
+            .sum())
+    }
+
+    fn guarantee_of(who: &AccountId) -> Option<(AccountId, Balance)> {
+        CSM_GUARANTEES.with(|guarantees| guarantees.borrow().get(who).cloned())
+            .map(|target| (target, Self::locked_csm(who)))
+    }
+
+    fn guarantee_pref(target: &AccountId) -> Option<u32> {
+        CSM_GUARANTEE_PREFS.with(|prefs| prefs.borrow().get(target).cloned())
+    }
+}
+
+pub fn set_locked_csm(who: &AccountId, value: Balance) {
+    LOCKED_CSM.with(|locked| locked.borrow_mut().insert(who.clone(), value));
+}
+
+pub fn set_csm_guarantee(who: &AccountId, target: Option<AccountId>) {
+    CSM_GUARANTEES.with(|guarantees| match target {
+        Some(target) => guarantees.borrow_mut().insert(who.clone(), target),
+        None => guarantees.borrow_mut().remove(who)
+    });
+}
+
+pub fn set_csm_guarantee_pref(target: &AccountId, pref: Option<u32>) {
+    CSM_GUARANTEE_PREFS.with(|prefs| match pref {
+        Some(pref) => prefs.borrow_mut().insert(target.clone(), pref),
+        None => prefs.borrow_mut().remove(target)
+    });
+}
+
 impl Config for Test {
     type Event = ();
     type Currency = Balances;
@@ -72,6 +131,8 @@ impl Config for Test {
     type BenefitsLimitRatio = BenefitsLimitRatio;
     type BenefitMarketCostRatio = BenefitMarketCostRatio;
     type BondingDuration = BondingDuration;
+    type CsmInterface = TestCsmInterface;
+    type CsmToFeeReductionQuota = TestCsmToFeeReductionQuota;
     type WeightInfo = weight::WeightInfo<Test>;
 }
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
@@ -14,7 +14,9 @@ fn update_overall_info_should_work() {
             total_fee_reduction_quota: 1,
             total_market_active_funds: 0,
             used_fee_reduction_quota: 0,
-            active_era: 10
+            active_era: 10,
+            market_participants: 0,
+            swork_participants: 0
         });
     });
 }
@@ -27,7 +29,9 @@ fn add_benefit_funds_should_work() {
             total_fee_reduction_quota: 1,
             total_market_active_funds: 0,
             used_fee_reduction_quota: 0,
-            active_era: 10
+            active_era: 10,
+            market_participants: 0,
+            swork_participants: 0
         });
         let _ = Balances::make_free_balance_be(&ALICE, 200_000);
         // add swork benefit
@@ -52,7 +56,9 @@ fn add_benefit_funds_should_work() {
             total_fee_reduction_quota: 1,
             total_market_active_funds: 0,
             used_fee_reduction_quota: 0,
-            active_era: 10
+            active_era: 10,
+            market_participants: 0,
+            swork_participants: 1
         });
 
         // add market benefit
@@ -77,7 +83,9 @@ fn add_benefit_funds_should_work() {
             total_fee_reduction_quota: 1,
             total_market_active_funds: 100,
             used_fee_reduction_quota: 0,
-            active_era: 10
+            active_era: 10,
+            market_participants: 1,
+            swork_participants: 1
         });
     });
 }
@@ -110,7 +118,9 @@ fn cut_benefit_funds_should_work() {
             total_fee_reduction_quota: 1,
             total_market_active_funds: 0,
             used_fee_reduction_quota: 0,
-            active_era: 10
+            active_era: 10,
+            market_participants: 0,
+            swork_participants: 1
         });
 
         assert_ok!(Benefits::cut_benefit_funds(Origin::signed(ALICE.clone()), 50, FundsType::SWORK));
@@ -126,7 +136,9 @@ fn cut_benefit_funds_should_work() {
             total_fee_reduction_quota: 1,
             total_market_active_funds: 0,
             used_fee_reduction_quota: 0,
-            active_era: 10
+            active_era: 10,
+            market_participants: 0,
+            swork_participants: 0
         });
 
         assert_ok!(Benefits::add_benefit_funds(Origin::signed(ALICE.clone()), 100, FundsType::MARKET));
@@ -151,7 +163,9 @@ fn cut_benefit_funds_should_work() {
             total_fee_reduction_quota: 1,
             total_market_active_funds: 50,
             used_fee_reduction_quota: 0,
-            active_era: 10
+            active_era: 10,
+            market_participants: 1,
+            swork_participants: 0
         });
 
         assert_ok!(Benefits::cut_benefit_funds(Origin::signed(ALICE.clone()), 50, FundsType::MARKET));
@@ -167,7 +181,9 @@ fn cut_benefit_funds_should_work() {
             total_fee_reduction_quota: 1,
             total_market_active_funds: 0,
             used_fee_reduction_quota: 0,
-            active_era: 10
+            active_era: 10,
+            market_participants: 0,
+            swork_participants: 0
         });
     });
 }
@@ -190,7 +206,9 @@ fn maybe_free_count_should_work() {
             total_fee_reduction_quota: 1,
             total_market_active_funds: 0,
             used_fee_reduction_quota: 0,
-            active_era: 10
+            active_era: 10,
+            market_participants: 0,
+            swork_participants: 0
         });
         let _ = Balances::make_free_balance_be(&ALICE, 200_000);
         assert_ok!(Benefits::add_benefit_funds(Origin::signed(ALICE.clone()), 105, FundsType::SWORK));
@@ -266,7 +284,9 @@ fn maybe_reduce_fee_should_work() {
             total_fee_reduction_quota: 100,
             total_market_active_funds: 0,
             used_fee_reduction_quota: 0,
-            active_era: 10
+            active_era: 10,
+            market_participants: 0,
+            swork_participants: 0
         });
         assert_eq!(Benefits::maybe_reduce_fee(&ALICE, 20, WithdrawReasons::TRANSACTION_PAYMENT).unwrap(), target_fee);
         // won't update reduction detail since it has not staking
@@ -294,7 +314,9 @@ fn maybe_reduce_fee_should_work() {
             total_fee_reduction_quota: 100,
             total_market_active_funds: 105,
             used_fee_reduction_quota: 19,
-            active_era: 10
+            active_era: 10,
+            market_participants: 1,
+            swork_participants: 0
         });
         assert_eq!(Balances::total_issuance(), 179); // 180 - 20 + 19
         assert_eq!(Balances::total_balance(&ALICE), 179);
@@ -321,14 +343,18 @@ fn update_overall_info_should_return_used_fee() {
             total_fee_reduction_quota: 100,
             total_market_active_funds: 105,
             used_fee_reduction_quota: 19,
-            active_era: 10
+            active_era: 10,
+            market_participants: 1,
+            swork_participants: 0
         });
         assert_eq!(Benefits::update_era_benefit(11u32.into(), 10_000), 19);
         assert_eq!(Benefits::current_benefits(), EraBenefits {
             total_fee_reduction_quota: 100,
             total_market_active_funds: 105,
             used_fee_reduction_quota: 0,
-            active_era: 11
+            active_era: 11,
+            market_participants: 1,
+            swork_participants: 0
         });
     });
 }
@@ -345,7 +371,9 @@ fn free_fee_limit_should_work() {
             total_fee_reduction_quota: 100,
             total_market_active_funds: 0,
             used_fee_reduction_quota: 0,
-            active_era: 10
+            active_era: 10,
+            market_participants: 0,
+            swork_participants: 0
         });
         assert_ok!(Benefits::add_benefit_funds(Origin::signed(ALICE.clone()), 100, FundsType::MARKET));
         assert_ok!(Benefits::add_benefit_funds(Origin::signed(BOB.clone()), 100, FundsType::MARKET));
@@ -362,7 +390,9 @@ fn free_fee_limit_should_work() {
             total_fee_reduction_quota: 100,
             total_market_active_funds: 200,
             used_fee_reduction_quota: 38,
-            active_era: 10
+            active_era: 10,
+            market_participants: 2,
+            swork_participants: 0
         });
         assert_eq!(Balances::total_issuance(), 498); // 500 - 40 + 38
         assert_eq!(Balances::total_balance(&ALICE), 198);
@@ -381,7 +411,9 @@ fn free_fee_limit_should_work() {
             total_fee_reduction_quota: 100,
             total_market_active_funds: 200,
             used_fee_reduction_quota: 38,
-            active_era: 10
+            active_era: 10,
+            market_participants: 2,
+            swork_participants: 0
         });
         assert_eq!(Balances::total_issuance(), 458); // 498 - 40
         assert_eq!(Balances::total_balance(&ALICE), 158);
@@ -390,7 +422,9 @@ fn free_fee_limit_should_work() {
             total_fee_reduction_quota: 100,
             total_market_active_funds: 100,
             used_fee_reduction_quota: 38,
-            active_era: 10
+            active_era: 10,
+            market_participants: 1,
+            swork_participants: 0
         });
 
         // Since Bob cut his collateral, Alice has more limitation, it's free again
@@ -407,7 +441,9 @@ fn free_fee_limit_should_work() {
             total_fee_reduction_quota: 100,
             total_market_active_funds: 100,
             used_fee_reduction_quota: 76,
-            active_era: 10
+            active_era: 10,
+            market_participants: 1,
+            swork_participants: 0
         });
         assert_eq!(Balances::total_issuance(), 456); // 458 - 2
         assert_eq!(Balances::total_balance(&ALICE), 156);
@@ -427,7 +463,9 @@ fn free_fee_limit_should_work() {
             total_fee_reduction_quota: 100,
             total_market_active_funds: 200,
             used_fee_reduction_quota: 76,
-            active_era: 10
+            active_era: 10,
+            market_participants: 2,
+            swork_participants: 0
         });
         assert_eq!(Balances::total_issuance(), 416); // 458 - 40
         assert_eq!(Balances::total_balance(&BOB), 260);
@@ -445,7 +483,9 @@ fn currency_is_insufficient() {
             total_fee_reduction_quota: 100,
             total_market_active_funds: 0,
             used_fee_reduction_quota: 0,
-            active_era: 10
+            active_era: 10,
+            market_participants: 0,
+            swork_participants: 0
         });
         assert_eq!(Benefits::maybe_reduce_fee(&ALICE, 40, WithdrawReasons::TRANSACTION_PAYMENT).is_err(), true);
         assert_ok!(Benefits::add_benefit_funds(Origin::signed(ALICE.clone()), 25, FundsType::MARKET));
@@ -461,7 +501,9 @@ fn cut_benefits_funds_should_exceed_max_chunks_limit() {
             total_fee_reduction_quota: 1,
             total_market_active_funds: 0,
             used_fee_reduction_quota: 0,
-            active_era: 10
+            active_era: 10,
+            market_participants: 0,
+            swork_participants: 0
         });
         let _ = Balances::make_free_balance_be(&ALICE, 200);
         assert_ok!(Benefits::add_benefit_funds(Origin::signed(ALICE.clone()), 100, FundsType::MARKET));
@@ -474,7 +516,9 @@ fn cut_benefits_funds_should_exceed_max_chunks_limit() {
                 total_fee_reduction_quota: 1,
                 total_market_active_funds: 95 - index as u64 * 5,
                 used_fee_reduction_quota: 0,
-                active_era: 10
+                active_era: 10,
+                market_participants: 1,
+                swork_participants: 0
             });
         }
 
@@ -495,7 +539,9 @@ fn withdraw_benefits_funds_should_work() {
             total_fee_reduction_quota: 1,
             total_market_active_funds: 0,
             used_fee_reduction_quota: 0,
-            active_era: 10
+            active_era: 10,
+            market_participants: 0,
+            swork_participants: 0
         });
         let _ = Balances::make_free_balance_be(&ALICE, 200);
         assert_ok!(Benefits::add_benefit_funds(Origin::signed(ALICE.clone()), 100, FundsType::MARKET));
@@ -561,7 +607,9 @@ fn rebond_market_benefits_funds_should_work() {
             total_fee_reduction_quota: 1,
             total_market_active_funds: 0,
             used_fee_reduction_quota: 0,
-            active_era: 10
+            active_era: 10,
+            market_participants: 0,
+            swork_participants: 0
         });
         let _ = Balances::make_free_balance_be(&ALICE, 200);
         assert_ok!(Benefits::add_benefit_funds(Origin::signed(ALICE.clone()), 100, FundsType::MARKET));
@@ -591,7 +639,9 @@ fn rebond_market_benefits_funds_should_work() {
             total_fee_reduction_quota: 1,
             total_market_active_funds: 65,
             used_fee_reduction_quota: 0,
-            active_era: 14
+            active_era: 14,
+            market_participants: 1,
+            swork_participants: 0
         });
         assert_ok!(Benefits::rebond_benefit_funds(Origin::signed(ALICE.clone()), 10, FundsType::MARKET));
         assert_eq!(Benefits::market_benefits(&ALICE), MarketBenefit {
@@ -608,7 +658,9 @@ fn rebond_market_benefits_funds_should_work() {
             total_fee_reduction_quota: 1,
             total_market_active_funds: 75,
             used_fee_reduction_quota: 0,
-            active_era: 14
+            active_era: 14,
+            market_participants: 1,
+            swork_participants: 0
         });
 
         assert_ok!(Benefits::rebond_benefit_funds(Origin::signed(ALICE.clone()), 100, FundsType::MARKET));
@@ -625,7 +677,9 @@ fn rebond_market_benefits_funds_should_work() {
             total_fee_reduction_quota: 1,
             total_market_active_funds: 100,
             used_fee_reduction_quota: 0,
-            active_era: 14
+            active_era: 14,
+            market_participants: 1,
+            swork_participants: 0
         });
     });
 }
@@ -638,7 +692,9 @@ fn rebond_swork_benefits_funds_should_work() {
             total_fee_reduction_quota: 1,
             total_market_active_funds: 0,
             used_fee_reduction_quota: 0,
-            active_era: 10
+            active_era: 10,
+            market_participants: 0,
+            swork_participants: 0
         });
         let _ = Balances::make_free_balance_be(&ALICE, 200);
         assert_ok!(Benefits::add_benefit_funds(Origin::signed(ALICE.clone()), 100, FundsType::SWORK));
@@ -695,7 +751,9 @@ fn withdraw_market_benefits_funds_in_weird_situation() {
             total_fee_reduction_quota: 1,
             total_market_active_funds: 0,
             used_fee_reduction_quota: 0,
-            active_era: 10
+            active_era: 10,
+            market_participants: 0,
+            swork_participants: 0
         });
         let _ = Balances::make_free_balance_be(&ALICE, 200);
         assert_ok!(Benefits::add_benefit_funds(Origin::signed(ALICE.clone()), 100, FundsType::MARKET));
@@ -711,7 +769,9 @@ fn withdraw_market_benefits_funds_in_weird_situation() {
             total_fee_reduction_quota: 1,
             total_market_active_funds: 100,
             used_fee_reduction_quota: 0,
-            active_era: 10
+            active_era: 10,
+            market_participants: 1,
+            swork_participants: 0
         });
         assert_ok!(Benefits::cut_benefit_funds(Origin::signed(ALICE.clone()), 200, FundsType::MARKET));
         assert_eq!(Benefits::market_benefits(&ALICE), MarketBenefit {
@@ -726,7 +786,9 @@ fn withdraw_market_benefits_funds_in_weird_situation() {
             total_fee_reduction_quota: 1,
             total_market_active_funds: 0,
             used_fee_reduction_quota: 0,
-            active_era: 10
+            active_era: 10,
+            market_participants: 0,
+            swork_participants: 0
         });
 
         // Slash this account with 150 and reserved should be 50
@@ -741,7 +803,9 @@ fn withdraw_market_benefits_funds_in_weird_situation() {
             total_fee_reduction_quota: 1,
             total_market_active_funds: 0,
             used_fee_reduction_quota: 0,
-            active_era: 13
+            active_era: 13,
+            market_participants: 0,
+            swork_participants: 0
         });
 
         let _ = Balances::make_free_balance_be(&ALICE, 200);
@@ -762,7 +826,9 @@ fn withdraw_market_benefits_funds_in_weird_situation() {
             total_fee_reduction_quota: 1,
             total_market_active_funds: 50,
             used_fee_reduction_quota: 0,
-            active_era: 13
+            active_era: 13,
+            market_participants: 1,
+            swork_participants: 0
         });
     });
 }
@@ -775,7 +841,9 @@ fn withdraw_swork_benefits_funds_in_weird_situation() {
             total_fee_reduction_quota: 1,
             total_market_active_funds: 0,
             used_fee_reduction_quota: 0,
-            active_era: 10
+            active_era: 10,
+            market_participants: 0,
+            swork_participants: 0
         });
         let _ = Balances::make_free_balance_be(&ALICE, 200);
         assert_ok!(Benefits::add_benefit_funds(Origin::signed(ALICE.clone()), 100, FundsType::SWORK));
@@ -809,7 +877,9 @@ fn withdraw_swork_benefits_funds_in_weird_situation() {
             total_fee_reduction_quota: 1,
             total_market_active_funds: 0,
             used_fee_reduction_quota: 0,
-            active_era: 13
+            active_era: 13,
+            market_participants: 0,
+            swork_participants: 0
         });
 
         let _ = Balances::make_free_balance_be(&ALICE, 200);
@@ -872,4 +942,227 @@ fn test_active_funds_and_total_funds_interface() {
         assert_eq!(Benefits::get_market_funds_ratio(&ALICE), Perbill::one());
         assert_eq!(Benefits::get_market_funds_ratio(&BOB), Perbill::zero());
     });
-}
\ No newline at end of file
+}
+
+#[test]
+fn auto_compounding_should_work() {
+    new_test_ext().execute_with(|| {
+        Benefits::update_era_benefit(10u32.into(), 100);
+        let _ = Balances::make_free_balance_be(&ALICE, 1000);
+
+        // 1. Nothing is compounded without opt-in
+        assert_eq!(Benefits::maybe_compound_reward(&ALICE, 500), 0);
+        assert_eq!(Balances::reserved_balance(&ALICE), 0);
+
+        // 2. The total ratio can't exceed 100%
+        assert_ok!(Benefits::set_auto_compounding(Origin::signed(ALICE.clone()), Perbill::from_percent(20), FundsType::SWORK));
+        assert_noop!(
+            Benefits::set_auto_compounding(Origin::signed(ALICE.clone()), Perbill::from_percent(81), FundsType::MARKET),
+            Error::<Test>::InvalidCompoundingRatio
+        );
+        assert_ok!(Benefits::set_auto_compounding(Origin::signed(ALICE.clone()), Perbill::from_percent(40), FundsType::MARKET));
+        assert_eq!(Benefits::auto_compounding_ratios(&ALICE, FundsType::SWORK), Perbill::from_percent(20));
+        assert_eq!(Benefits::auto_compounding_ratios(&ALICE, FundsType::MARKET), Perbill::from_percent(40));
+
+        // 3. The ratios of the reward go into each type of benefit funds
+        assert_eq!(Benefits::maybe_compound_reward(&ALICE, 500), 300);
+        assert_eq!(Balances::reserved_balance(&ALICE), 300);
+        assert_eq!(Benefits::swork_benefits(&ALICE).active_funds, 100);
+        assert_eq!(Benefits::swork_benefits(&ALICE).total_fee_reduction_count, 2);
+        assert_eq!(Benefits::market_benefits(&ALICE).active_funds, 200);
+        assert_eq!(Benefits::current_benefits().total_market_active_funds, 200);
+
+        // 4. Skip the funds type which can't be reserved
+        assert_eq!(Benefits::maybe_compound_reward(&ALICE, 2000), 400);
+        assert_eq!(Balances::reserved_balance(&ALICE), 700);
+        assert_eq!(Benefits::swork_benefits(&ALICE).active_funds, 500);
+        assert_eq!(Benefits::market_benefits(&ALICE).active_funds, 200);
+
+        // 5. Opt out
+        assert_ok!(Benefits::set_auto_compounding(Origin::signed(ALICE.clone()), Perbill::zero(), FundsType::SWORK));
+        assert_eq!(<AutoCompoundingRatios<Test>>::contains_key(&ALICE, FundsType::SWORK), false);
+    });
+}
+
+#[test]
+fn benefit_quota_should_work() {
+    new_test_ext().execute_with(|| {
+        Benefits::update_era_benefit(10u32.into(), 10_000);
+        let _ = Balances::make_free_balance_be(&ALICE, 200_000);
+        let _ = Balances::make_free_balance_be(&BOB, 200_000);
+        assert_ok!(Benefits::add_benefit_funds(Origin::signed(ALICE.clone()), 100, FundsType::MARKET));
+        assert_ok!(Benefits::add_benefit_funds(Origin::signed(ALICE.clone()), 100, FundsType::SWORK));
+        assert_ok!(Benefits::add_benefit_funds(Origin::signed(BOB.clone()), 100, FundsType::MARKET));
+
+        assert_ok!(Benefits::maybe_reduce_fee(&ALICE, 20, WithdrawReasons::TRANSACTION_PAYMENT));
+        assert_eq!(Benefits::maybe_free_count(&ALICE), true);
+        assert_eq!(Benefits::benefit_quota(&ALICE), BenefitQuota {
+            total_fee_reduction_quota: 50,
+            used_fee_reduction_quota: 19,
+            remaining_fee_reduction_quota: 31,
+            total_fee_reduction_count: 2,
+            used_fee_reduction_count: 1,
+            remaining_fee_reduction_count: 1,
+            active_era: 10
+        });
+        assert_eq!(Benefits::benefit_quota(&BOB).remaining_fee_reduction_quota, 50);
+        assert_eq!(Benefits::benefit_quota(&BOB).remaining_fee_reduction_count, 0);
+
+        // The quota is refreshed in the next era
+        Benefits::update_era_benefit(11u32.into(), 10_000);
+        assert_eq!(Benefits::benefit_quota(&ALICE), BenefitQuota {
+            total_fee_reduction_quota: 50,
+            used_fee_reduction_quota: 0,
+            remaining_fee_reduction_quota: 50,
+            total_fee_reduction_count: 2,
+            used_fee_reduction_count: 0,
+            remaining_fee_reduction_count: 2,
+            active_era: 11
+        });
+    });
+}
+
+#[test]
+fn participants_should_be_counted() {
+    use frame_support::{storage::migration::put_storage_value, traits::OnRuntimeUpgrade};
+
+    #[derive(Encode, Decode)]
+    struct OldEraBenefits<Balance: HasCompact> {
+        #[codec(compact)]
+        total_fee_reduction_quota: Balance,
+        #[codec(compact)]
+        total_market_active_funds: Balance,
+        #[codec(compact)]
+        used_fee_reduction_quota: Balance,
+        #[codec(compact)]
+        active_era: EraIndex
+    }
+
+    new_test_ext().execute_with(|| {
+        Benefits::update_era_benefit(10u32.into(), 100);
+        let _ = Balances::make_free_balance_be(&ALICE, 200);
+        let _ = Balances::make_free_balance_be(&BOB, 200);
+
+        // 1. Count the accounts with active funds
+        assert_ok!(Benefits::add_benefit_funds(Origin::signed(ALICE.clone()), 100, FundsType::MARKET));
+        assert_ok!(Benefits::add_benefit_funds(Origin::signed(ALICE.clone()), 50, FundsType::MARKET));
+        assert_ok!(Benefits::add_benefit_funds(Origin::signed(ALICE.clone()), 50, FundsType::SWORK));
+        assert_ok!(Benefits::add_benefit_funds(Origin::signed(BOB.clone()), 100, FundsType::MARKET));
+        assert_eq!(Benefits::current_benefits().market_participants, 2);
+        assert_eq!(Benefits::current_benefits().swork_participants, 1);
+
+        // 2. Cut all active funds
+        assert_ok!(Benefits::cut_benefit_funds(Origin::signed(BOB.clone()), 50, FundsType::MARKET));
+        assert_eq!(Benefits::current_benefits().market_participants, 2);
+        assert_ok!(Benefits::cut_benefit_funds(Origin::signed(BOB.clone()), 50, FundsType::MARKET));
+        assert_eq!(Benefits::current_benefits().market_participants, 1);
+
+        // 3. Rebond
+        assert_ok!(Benefits::rebond_benefit_funds(Origin::signed(BOB.clone()), 50, FundsType::MARKET));
+        assert_eq!(Benefits::current_benefits().market_participants, 2);
+
+        // 4. Compound
+        assert_ok!(Benefits::set_auto_compounding(Origin::signed(BOB.clone()), Perbill::from_percent(50), FundsType::SWORK));
+        assert_eq!(Benefits::maybe_compound_reward(&BOB, 20), 10);
+        assert_eq!(Benefits::current_benefits().swork_participants, 2);
+
+        // 5. Migrate from the old current benefits
+        put_storage_value(b"Benefits", b"CurrentBenefits", b"", OldEraBenefits::<Balance> {
+            total_fee_reduction_quota: 1,
+            total_market_active_funds: 200,
+            used_fee_reduction_quota: 0,
+            active_era: 10
+        });
+        StorageVersion::put(Releases::V1_0_0);
+        <Benefits as OnRuntimeUpgrade>::on_runtime_upgrade();
+        assert_eq!(Benefits::current_benefits(), EraBenefits {
+            total_fee_reduction_quota: 1,
+            total_market_active_funds: 200,
+            used_fee_reduction_quota: 0,
+            active_era: 10,
+            market_participants: 2,
+            swork_participants: 2
+        });
+        assert_eq!(StorageVersion::get(), Releases::V2_0_0);
+    });
+}
+
+#[test]
+fn transfer_fee_reduction_should_stop_when_quota_exhausted() {
+    new_test_ext().execute_with(|| {
+        let _ = Balances::make_free_balance_be(&ALICE, 1000);
+        Benefits::update_era_benefit(10u32.into(), 10_000);
+        assert_ok!(Benefits::add_benefit_funds(Origin::signed(ALICE.clone()), 100, FundsType::MARKET));
+        assert_eq!(Benefits::current_benefits().total_fee_reduction_quota, 100);
+
+        // 1. The reduced part is issued, the whole fee goes into the pot
+        let imbalance = Benefits::maybe_reduce_fee(&ALICE, 100, WithdrawReasons::TRANSFER).unwrap();
+        assert_eq!(imbalance.peek(), 100);
+        Balances::resolve_creating(&BOB, imbalance);
+        assert_eq!(Balances::free_balance(&ALICE), 895);
+        assert_eq!(Balances::free_balance(&BOB), 100);
+        assert_eq!(Balances::total_issuance(), 1095); // 1000 + 95
+        assert_eq!(Benefits::market_benefits(&ALICE).used_fee_reduction_quota, 95);
+        assert_eq!(Benefits::current_benefits().used_fee_reduction_quota, 95);
+
+        // 2. The quota is exhausted, the whole fee should be paid
+        let imbalance = Benefits::maybe_reduce_fee(&ALICE, 100, WithdrawReasons::TRANSFER).unwrap();
+        assert_eq!(imbalance.peek(), 100);
+        Balances::resolve_creating(&BOB, imbalance);
+        assert_eq!(Balances::free_balance(&ALICE), 795);
+        assert_eq!(Balances::free_balance(&BOB), 200);
+        assert_eq!(Balances::total_issuance(), 1095);
+        assert_eq!(Benefits::market_benefits(&ALICE).used_fee_reduction_quota, 95);
+        assert_eq!(Benefits::current_benefits().used_fee_reduction_quota, 95);
+
+        // 3. Can't afford the whole fee
+        assert!(Benefits::maybe_reduce_fee(&ALICE, 800, WithdrawReasons::TRANSFER).is_err());
+        assert_eq!(Balances::free_balance(&ALICE), 795);
+
+        // 4. The quota is refreshed in the next era
+        Benefits::update_era_benefit(11u32.into(), 10_000);
+        let imbalance = Benefits::maybe_reduce_fee(&ALICE, 100, WithdrawReasons::TRANSFER).unwrap();
+        Balances::resolve_creating(&BOB, imbalance);
+        assert_eq!(Balances::free_balance(&ALICE), 790);
+        assert_eq!(Balances::total_issuance(), 1190);
+    });
+}
+
+#[test]
+fn csm_should_give_extra_fee_reduction_quota() {
+    new_test_ext().execute_with(|| {
+        let _ = Balances::make_free_balance_be(&ALICE, 1000);
+        let _ = Balances::make_free_balance_be(&BOB, 1000);
+        let _ = Balances::make_free_balance_be(&CHARLIE, 1000);
+        Benefits::update_era_benefit(10u32.into(), 10_000);
+        assert_ok!(Benefits::add_benefit_funds(Origin::signed(ALICE.clone()), 100, FundsType::MARKET));
+        assert_ok!(Benefits::add_benefit_funds(Origin::signed(BOB.clone()), 100, FundsType::MARKET));
+        assert_eq!(Benefits::benefit_quota(&ALICE).total_fee_reduction_quota, 50);
+
+        // 1. The locking CSM gives the extra quota
+        set_locked_csm(&ALICE, 300);
+        assert_eq!(Benefits::benefit_quota(&ALICE).total_fee_reduction_quota, 80);
+        set_locked_csm(&CHARLIE, 200);
+        assert_eq!(Benefits::benefit_quota(&CHARLIE).total_fee_reduction_quota, 20);
+
+        // 2. The guarantee only counts when the target accepts guarantees
+        set_csm_guarantee(&CHARLIE, Some(BOB.clone()));
+        assert_eq!(Benefits::benefit_quota(&CHARLIE).total_fee_reduction_quota, 20);
+        assert_eq!(Benefits::benefit_quota(&BOB).total_fee_reduction_quota, 50);
+        set_csm_guarantee_pref(&BOB, Some(50));
+        assert_eq!(Benefits::benefit_quota(&CHARLIE).total_fee_reduction_quota, 0);
+        assert_eq!(Benefits::benefit_quota(&BOB).total_fee_reduction_quota, 70);
+
+        // 3. Withdrawing the pref gives the quota back to the guarantor
+        set_csm_guarantee_pref(&BOB, None);
+        assert_eq!(Benefits::benefit_quota(&CHARLIE).total_fee_reduction_quota, 20);
+        assert_eq!(Benefits::benefit_quota(&BOB).total_fee_reduction_quota, 50);
+
+        // 4. The extra quota can be used without any market funds
+        let imbalance = Benefits::maybe_reduce_fee(&CHARLIE, 20, WithdrawReasons::TRANSFER).unwrap();
+        assert_eq!(imbalance.peek(), 20);
+        assert_eq!(Balances::free_balance(&CHARLIE), 999);
+        assert_eq!(Benefits::benefit_quota(&CHARLIE).remaining_fee_reduction_quota, 1);
+        assert_eq!(Benefits::current_benefits().used_fee_reduction_quota, 19);
+    });
+}
@@ -23,6 +23,8 @@ mod tests;
 
 type BalanceOf<T> =
 	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+type CsmBalanceOf<T> =
+	<<T as Config>::CsmCurrency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 
 pub trait Config: system::Config + bridge::Config {
 	type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
@@ -34,11 +36,17 @@ pub trait Config: system::Config + bridge::Config {
 	type Currency: Currency<Self::AccountId>;
 
 	type BridgeTokenId: Get<[u8; 32]>;
+
+	/// The CSM currency mechanism.
+	type CsmCurrency: Currency<Self::AccountId>;
+
+	type BridgeCsmTokenId: Get<[u8; 32]>;
 }
 
 decl_storage! {
 	trait Store for Module<T: Config> as BridgeTransfer {
 		BridgeFee get(fn bridge_fee): map hasher(opaque_blake2_256) u8 => (BalanceOf<T>, u32);
+		CsmBridgeFee get(fn csm_bridge_fee): map hasher(opaque_blake2_256) u8 => (CsmBalanceOf<T>, u32);
 	}
 }
 
@@ -46,9 +54,12 @@ decl_event! {
 	pub enum Event<T>
 	where
 		Balance = BalanceOf<T>,
+		CsmBalance = CsmBalanceOf<T>,
 	{
 		/// [chainId, min_fee, fee_scale]
 		FeeUpdated(u8, Balance, u32),
+		/// [chainId, min_fee, fee_scale]
+		CsmFeeUpdated(u8, CsmBalance, u32),
 	}
 }
 
@@ -82,6 +93,16 @@ decl_module! {
 			Ok(())
 		}
 
+		/// Change extra bridge transfer fee that user should pay for CSM
+		#[weight = 195_000_000]
+		pub fn sudo_change_csm_fee(origin, min_fee: CsmBalanceOf<T>, fee_scale: u32, dest_id: u8) -> DispatchResult {
+			ensure_root(origin)?;
+			ensure!(fee_scale <= 1000u32, Error::<T>::InvalidFeeOption);
+			CsmBridgeFee::<T>::insert(dest_id, (min_fee, fee_scale));
+			Self::deposit_event(RawEvent::CsmFeeUpdated(dest_id, min_fee, fee_scale));
+			Ok(())
+		}
+
 		/// Transfers some amount of the native token to some recipient on a (whitelisted) destination chain.
 		#[weight = 195_000_000]
 		pub fn transfer_native(origin, amount: BalanceOf<T>, recipient: Vec<u8>, dest_id: u8) -> DispatchResult {
@@ -146,6 +167,26 @@ decl_module! {
 			<bridge::Module<T>>::transfer_fungible(dest_id, T::BridgeTokenId::get(), recipient, U256::from(amount.saturating_sub(fee).saturated_into::<u128>()))
 		}
 
+		/// Transfers some amount of CSM to some recipient on a (whitelisted) destination chain.
+		#[weight = 195_000_000]
+		pub fn transfer_csm_native(origin, amount: CsmBalanceOf<T>, recipient: Vec<u8>, dest_id: u8) -> DispatchResult {
+			let source = ensure_signed(origin)?;
+			ensure!(<bridge::Module<T>>::chain_whitelisted(dest_id), Error::<T>::InvalidTransfer);
+			let bridge_id = <bridge::Module<T>>::account_id();
+			ensure!(CsmBridgeFee::<T>::contains_key(&dest_id), Error::<T>::FeeOptionsMissiing);
+			let (min_fee, fee_scale) = Self::csm_bridge_fee(dest_id);
+			let fee_estimated = amount * fee_scale.into() / 1000u32.into();
+			let fee = if fee_estimated > min_fee {
+				fee_estimated
+			} else {
+				min_fee
+			};
+			ensure!(amount > fee, Error::<T>::LessThanFee);
+			T::CsmCurrency::transfer(&source, &bridge_id, amount.into(), AllowDeath)?;
+
+			<bridge::Module<T>>::transfer_fungible(dest_id, T::BridgeCsmTokenId::get(), recipient, U256::from(amount.saturating_sub(fee).saturated_into::<u128>()))
+		}
+
 		//
 		// Executable calls. These can be triggered by a bridge transfer initiated on another chain
 		//
@@ -175,5 +216,13 @@ decl_module! {
 			<T as Config>::Currency::transfer(&polkadot_parachain_pot, &to, amount.into(), AllowDeath)?;
 			Ok(())
 		}
+
+		/// Executes a simple CSM transfer using the bridge account as the source
+		#[weight = 195_000_000]
+		pub fn transfer_csm(origin, to: T::AccountId, amount: CsmBalanceOf<T>, _rid: [u8; 32]) -> DispatchResult {
+			let source = T::BridgeOrigin::ensure_origin(origin)?;
+			<T as Config>::CsmCurrency::transfer(&source, &to, amount.into(), AllowDeath)?;
+			Ok(())
+		}
 	}
 }
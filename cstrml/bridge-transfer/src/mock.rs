@@ -6,7 +6,7 @@
 use super::*;
 
 use hex_literal::hex;
-use frame_support::{ord_parameter_types, parameter_types, weights::Weight};
+use frame_support::{ord_parameter_types, parameter_types, weights::Weight, traits::StorageMapShim};
 use frame_system::{self as system};
 use sp_core::H256;
 use sp_runtime::{
@@ -30,6 +30,7 @@ frame_support::construct_runtime!(
 	{
 		System: frame_system::{Module, Call, Config, Storage, Event<T>},
 		Balances: balances::{Module, Call, Storage, Config<T>, Event<T>},
+		CSM: balances::<Instance1>::{Module, Call, Storage, Config<T>, Event<T>},
 		Bridge: bridge::{Module, Call, Storage, Event<T>},
 		BridgeTransfer: bridge_transfer::{Module, Call, Storage, Event<T>},
 		Timestamp: pallet_timestamp::{Module, Call, Storage, Inherent},
@@ -88,6 +89,21 @@ impl balances::Config for Test {
 	type MaxLocks = ();
 }
 
+impl balances::Config<balances::Instance1> for Test {
+	type Balance = u64;
+	type DustRemoval = ();
+	type Event = Event;
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = StorageMapShim<
+		balances::Account<Test, balances::Instance1>,
+		frame_system::Provider<Test>,
+		u64,
+		balances::AccountData<u64>,
+	>;
+	type WeightInfo = ();
+	type MaxLocks = ();
+}
+
 parameter_types! {
 	pub const TestChainId: u8 = 5;
 	pub const ProposalLifetime: u64 = 100;
@@ -104,6 +120,8 @@ impl bridge::Config for Test {
 parameter_types! {
 	// bridge::derive_resource_id(1, &bridge::hashing::blake2_128(b"CRU"));
 	pub const BridgeTokenId: [u8; 32] = hex!("000000000000000000000000000000608d1bc9a2d146ebc94667c336721b2801");
+	// bridge::derive_resource_id(1, &bridge::hashing::blake2_128(b"CSM"));
+	pub const BridgeCsmTokenId: [u8; 32] = hex!("00000000000000000000000000000098aef84ac01d96413445cf3dc4d5c44c01");
 }
 
 impl Config for Test {
@@ -111,6 +129,8 @@ impl Config for Test {
 	type BridgeOrigin = bridge::EnsureBridge<Test>;
 	type Currency = Balances;
 	type BridgeTokenId = BridgeTokenId;
+	type CsmCurrency = CSM;
+	type BridgeCsmTokenId = BridgeCsmTokenId;
 }
 
 impl pallet_timestamp::Config for Test {
@@ -136,6 +156,11 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
 	}
 	.assimilate_storage(&mut t)
 	.unwrap();
+	balances::GenesisConfig::<Test, balances::Instance1> {
+		balances: vec![(bridge_id, ENDOWED_BALANCE), (RELAYER_A, ENDOWED_BALANCE)],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
 	let mut ext = sp_io::TestExternalities::new(t);
 	ext.execute_with(|| System::set_block_number(1));
 	ext
@@ -6,11 +6,11 @@
 use super::mock::{
 	assert_events, balances, expect_event, new_test_ext, Balances, Bridge,
 	BridgeTransfer, Call, Event, Origin, ProposalLifetime, ENDOWED_BALANCE, RELAYER_A,
-	RELAYER_B, RELAYER_C, BridgeTokenId
+	RELAYER_B, RELAYER_C, BridgeTokenId, BridgeCsmTokenId, CSM, Test
 };
 
 use super::*;
-use frame_support::assert_ok;
+use frame_support::{assert_noop, assert_ok};
 
 use blake2_rfc;
 
@@ -40,6 +40,13 @@ fn constant_equality() {
 	assert_eq!(r_id, encoded);
 }
 
+#[test]
+fn csm_constant_equality() {
+	let r_id = bridge::derive_resource_id(1, &blake2_128(b"CSM"));
+	let encoded: [u8; 32] = hex_literal::hex!("00000000000000000000000000000098aef84ac01d96413445cf3dc4d5c44c01");
+	assert_eq!(r_id, encoded);
+}
+
 #[test]
 fn transfer_native() {
 	new_test_ext().execute_with(|| {
@@ -156,6 +163,75 @@ fn transfer() {
 	})
 }
 
+#[test]
+fn transfer_csm_native() {
+	new_test_ext().execute_with(|| {
+		let dest_chain = 0;
+		let resource_id = BridgeCsmTokenId::get();
+		let amount: u64 = 100;
+		let recipient = vec![99];
+
+		assert_ok!(Bridge::whitelist_chain(Origin::root(), dest_chain.clone()));
+		// CSM fee is configured separately from CRU fee
+		assert_ok!(BridgeTransfer::sudo_change_fee(
+			Origin::root(),
+			2,
+			2,
+			dest_chain.clone()
+		));
+		assert_noop!(
+			BridgeTransfer::transfer_csm_native(
+				Origin::signed(RELAYER_A),
+				amount.clone(),
+				recipient.clone(),
+				dest_chain,
+			),
+			Error::<Test>::FeeOptionsMissiing
+		);
+		assert_ok!(BridgeTransfer::sudo_change_csm_fee(
+			Origin::root(),
+			5,
+			2,
+			dest_chain.clone()
+		));
+		assert_ok!(BridgeTransfer::transfer_csm_native(
+			Origin::signed(RELAYER_A),
+			amount.clone(),
+			recipient.clone(),
+			dest_chain,
+		));
+
+		expect_event(bridge::RawEvent::FungibleTransfer(
+			dest_chain,
+			1,
+			resource_id,
+			(amount - 5).into(),
+			recipient,
+		));
+		assert_eq!(CSM::free_balance(&Bridge::account_id()), ENDOWED_BALANCE + 100);
+		assert_eq!(CSM::free_balance(RELAYER_A), ENDOWED_BALANCE - 100);
+		assert_eq!(Balances::free_balance(RELAYER_A), ENDOWED_BALANCE);
+	})
+}
+
+#[test]
+fn transfer_csm() {
+	new_test_ext().execute_with(|| {
+		let bridge_id: u64 = Bridge::account_id();
+		let resource_id = BridgeCsmTokenId::get();
+		assert_ok!(BridgeTransfer::transfer_csm(
+			Origin::signed(bridge_id),
+			RELAYER_B,
+			10,
+			resource_id,
+		));
+		assert_eq!(CSM::free_balance(&bridge_id), ENDOWED_BALANCE - 10);
+		assert_eq!(CSM::free_balance(RELAYER_B), 10);
+		assert_eq!(Balances::free_balance(&bridge_id), ENDOWED_BALANCE);
+		assert_eq!(Balances::free_balance(RELAYER_B), 0);
+	})
+}
+
 #[test]
 fn create_sucessful_transfer_proposal() {
 	new_test_ext().execute_with(|| {
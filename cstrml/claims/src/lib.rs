@@ -4,10 +4,11 @@
 //! Module to process claims from Ethereum addresses.
 #![cfg_attr(not(feature = "std"), no_std)]
 use sp_std::prelude::*;
-use sp_io::{hashing::keccak_256, crypto::secp256k1_ecdsa_recover};
+use sp_io::{hashing::{keccak_256, blake2_512}, crypto::secp256k1_ecdsa_recover};
 use frame_support::{
-    decl_event, decl_storage, decl_module, decl_error, ensure,
-    traits::{Currency, Get, ExistenceRequirement::AllowDeath, EnsureOrigin}
+    decl_event, decl_storage, decl_module, decl_error, ensure, IterableStorageDoubleMap,
+    traits::{Currency, Get, ExistenceRequirement::AllowDeath, EnsureOrigin},
+    weights::Weight
 };
 use frame_system::{ensure_signed, ensure_root, ensure_none};
 use codec::{Encode, Decode};
@@ -53,6 +54,9 @@ pub trait Config: frame_system::Config {
 
     /// The origin who can call claim_cru18
     type CRU18Origin: EnsureOrigin<Self::Origin>;
+
+    /// The ethereum chain id used in the EIP-712 domain.
+    type ChainId: Get<u64>;
 }
 
 /// An Ethereum address (i.e. 20 bytes, used to represent an Ethereum account).
@@ -138,6 +142,35 @@ impl<'de> Deserialize<'de> for EthereumTxHash {
     }
 }
 
+/// The name of the EIP-712 domain.
+pub const EIP712_DOMAIN_NAME: &[u8] = b"Crust Claims";
+/// The version of the EIP-712 domain.
+pub const EIP712_DOMAIN_VERSION: &[u8] = b"1";
+/// The EIP-712 type of the domain, `salt` is the genesis hash of the chain.
+pub const EIP712_DOMAIN_TYPE: &[u8] = b"EIP712Domain(string name,string version,uint256 chainId,bytes32 salt)";
+/// The EIP-712 type of the claim, `dest` is the SS58 address of the account id.
+pub const EIP712_CLAIM_TYPE: &[u8] = b"Claim(string dest,bytes32 tx,uint256 chainId)";
+
+/// The alphabet of the base58 encoding used by the SS58 address.
+const BASE58_ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// The max depth of the Merkle proof in an airdrop claim.
+pub const MAX_AIRDROP_PROOF_DEPTH: usize = 32;
+
+/// A Merkle airdrop batch, whose leaves are the SCALE encoded `(EthereumAddress, amount)`
+/// hashed by keccak256 and whose nodes are the keccak256 of the sorted pair of children.
+#[derive(Clone, PartialEq, Eq, Encode, Decode, Default, RuntimeDebug)]
+pub struct AirdropBatch<Balance, BlockNumber> {
+    /// The Merkle root of the leaves
+    pub root: [u8; 32],
+    /// The total amount could be claimed in this batch
+    pub total: Balance,
+    /// The amount already claimed in this batch
+    pub claimed: Balance,
+    /// The block number after which the unclaimed amount can be swept
+    pub deadline: Option<BlockNumber>,
+}
+
 decl_event!(
     pub enum Event<T> where
         Balance = BalanceOf<T>,
@@ -159,6 +192,14 @@ decl_event!(
         BondEthSuccess(AccountId, EthereumAddress),
         /// Someone claimed some CRU18s. [who, amount]
         CRU18Claimed(AccountId, Balance),
+        /// An airdrop batch was published. [batch_id, total]
+        AirdropBatchPublished(u32, Balance),
+        /// Someone claimed some CRUs from an airdrop batch. [who, batch_id, ethereum_address, amount]
+        AirdropClaimed(AccountId, u32, EthereumAddress, Balance),
+        /// An expired claim was swept and its value was restored into the claim limit. [tx, amount]
+        ClaimSwept(EthereumTxHash, Balance),
+        /// An expired airdrop batch was swept and its unclaimed value was restored into the claim limit. [batch_id, amount]
+        AirdropBatchSwept(u32, Balance),
     }
 );
 
@@ -182,6 +223,16 @@ decl_error! {
         SignatureNotMatch,
         /// Exceed claim limitation
         ExceedClaimLimit,
+        /// Airdrop batch not exist
+        AirdropBatchNotExist,
+        /// The Merkle proof does not match the airdrop batch
+        InvalidMerkleProof,
+        /// The deadline should be in the future
+        InvalidDeadline,
+        /// The claim is expired
+        ClaimExpired,
+        /// The claim is not expired yet
+        ClaimNotExpired,
     }
 }
 
@@ -199,6 +250,9 @@ decl_storage! {
         /// Mark if the claim transaction has already been claimed
         Claimed get(fn claimed): map hasher(identity) EthereumTxHash => bool;
 
+        /// The block number after which the unclaimed claim transaction can be swept
+        ClaimDeadlines get(fn claim_deadlines): map hasher(identity) EthereumTxHash => Option<T::BlockNumber>;
+
         /// [who] can set the claim limit
         Superior get(fn superior): Option<T::AccountId>;
 
@@ -206,7 +260,17 @@ decl_storage! {
         Miner get(fn miner): Option<T::AccountId>;
 
         /// Mark if the cru18 claim transaction has already been claimed
+        /// It never expires, the cru18 is transferred once it's marked and the mark prevents claiming twice
         CRU18Claimed get(fn cru18_clamined): map hasher(twox_64_concat) T::AccountId => bool;
+
+        /// The id of the next airdrop batch
+        NextAirdropBatchId get(fn next_airdrop_batch_id): u32;
+
+        /// The published Merkle airdrop batches
+        AirdropBatches get(fn airdrop_batches): map hasher(twox_64_concat) u32 => Option<AirdropBatch<BalanceOf<T>, T::BlockNumber>>;
+
+        /// Mark if the ethereum address has already claimed from the airdrop batch
+        AirdropClaimed get(fn airdrop_claimed): double_map hasher(twox_64_concat) u32, hasher(identity) EthereumAddress => bool;
     }
 }
 
@@ -222,6 +286,9 @@ decl_module! {
         /// The claim's module id, used for deriving its sovereign account ID.
         const ModuleId: ModuleId = T::ModuleId::get();
 
+        /// The ethereum chain id used in the EIP-712 domain.
+        const ChainId: u64 = T::ChainId::get();
+
         /// Change superior
         ///
         /// The dispatch origin for this call must be _Root_.
@@ -294,38 +361,33 @@ decl_module! {
         #[weight = 1000]
         fn mint_claim(origin, tx: EthereumTxHash, who: EthereumAddress, value: BalanceOf<T>) -> DispatchResult {
             let signer = ensure_signed(origin)?;
-            let maybe_miner = Self::miner();
-
-            // 1. Check if miner exist
-            ensure!(maybe_miner.is_some(), Error::<T>::MinerNotExist);
-
-            // 2. Check if this tx already be mint
-            ensure!(!Claims::<T>::contains_key(&tx), Error::<T>::AlreadyBeMint);
-
-            // 3. Check if signer is miner
-            ensure!(Some(&signer) == maybe_miner.as_ref(), Error::<T>::IllegalMiner);
-
-            // 4. Check claim limit
-            ensure!(Self::claim_limit() >= value, Error::<T>::ExceedClaimLimit);
-
-            // 5. Save into claims
-            Claims::<T>::insert(tx.clone(), (who.clone(), value.clone()));
-            Claimed::insert(tx, false);
-
-            // 6. Reduce claim limit
-            ClaimLimit::<T>::mutate(|l| *l = l.saturating_sub(value));
+            Self::do_mint_claim(signer, tx, who, value, None)
+        }
 
-            Self::deposit_event(RawEvent::MintSuccess(tx, who, value));
-            Ok(())
+        /// Mint the claim which expires after the deadline
+        ///
+        /// This dispatch origin for this call must be _Miner_.
+        ///
+        /// Parameters:
+        /// - `tx`: The claim ethereum tx hash
+        /// - `who`: The claimer ethereum address
+        /// - `value`: The amount of this tx, should be less than claim_limit
+        /// - `deadline`: The block number after which the unclaimed claim can be swept
+        #[weight = 1000]
+        fn mint_claim_with_deadline(origin, tx: EthereumTxHash, who: EthereumAddress, value: BalanceOf<T>, deadline: T::BlockNumber) -> DispatchResult {
+            let signer = ensure_signed(origin)?;
+            ensure!(deadline > <frame_system::Module<T>>::block_number(), Error::<T>::InvalidDeadline);
+            Self::do_mint_claim(signer, tx, who, value, Some(deadline))
         }
 
         #[weight = 0]
         fn claim(origin, dest: T::AccountId, tx: EthereumTxHash, sig: EcdsaSignature) -> DispatchResult {
             let _ = ensure_none(origin)?;
 
-            // 1. Check the tx already be mint and not be claimed
+            // 1. Check the tx already be mint and not be claimed or expired
             ensure!(Claims::<T>::contains_key(&tx), Error::<T>::SignerHasNoClaim);
             ensure!(!Self::claimed(&tx), Error::<T>::AlreadyBeClaimed);
+            ensure!(!Self::is_expired(Self::claim_deadlines(&tx)), Error::<T>::ClaimExpired);
 
             // 2. Sign data
             let data = dest.using_encoded(to_ascii_hex);
@@ -336,6 +398,159 @@ decl_module! {
             Self::process_claim(tx, signer, dest)
         }
 
+        /// Claim with the EIP-712 typed data signature over `Claim(string dest,bytes32 tx,uint256 chainId)`
+        #[weight = 0]
+        fn claim_with_typed_signature(origin, dest: T::AccountId, tx: EthereumTxHash, sig: EcdsaSignature) -> DispatchResult {
+            let _ = ensure_none(origin)?;
+
+            // 1. Check the tx already be mint and not be claimed or expired
+            ensure!(Claims::<T>::contains_key(&tx), Error::<T>::SignerHasNoClaim);
+            ensure!(!Self::claimed(&tx), Error::<T>::AlreadyBeClaimed);
+            ensure!(!Self::is_expired(Self::claim_deadlines(&tx)), Error::<T>::ClaimExpired);
+
+            // 2. Recover the typed data signer
+            let signer = Self::eth_recover_typed(&sig, &dest, &tx).ok_or(Error::<T>::InvalidEthereumSignature)?;
+
+            // 3. Make sure signer is match with claimer
+            Self::process_claim(tx, signer, dest)
+        }
+
+        /// Publish a Merkle airdrop batch
+        ///
+        /// The dispatch origin for this call must be _Root_ or _Superior_.
+        ///
+        /// Parameters:
+        /// - `root`: The Merkle root of the `(EthereumAddress, amount)` leaves
+        /// - `total`: The total amount of the leaves, should be less than claim_limit
+        /// - `deadline`: The block number after which the unclaimed amount can be swept
+        #[weight = 1000]
+        fn publish_airdrop_batch(origin, root: [u8; 32], total: BalanceOf<T>, deadline: Option<T::BlockNumber>) -> DispatchResult {
+            Self::ensure_root_or_superior(origin)?;
+
+            // 1. Check claim limit and deadline
+            ensure!(Self::claim_limit() >= total, Error::<T>::ExceedClaimLimit);
+            ensure!(deadline.map_or(true, |d| d > <frame_system::Module<T>>::block_number()), Error::<T>::InvalidDeadline);
+
+            // 2. Save the batch
+            let batch_id = Self::next_airdrop_batch_id();
+            AirdropBatches::<T>::insert(batch_id, AirdropBatch {
+                root,
+                total,
+                claimed: Zero::zero(),
+                deadline
+            });
+            NextAirdropBatchId::put(batch_id.saturating_add(1));
+
+            // 3. Reduce claim limit
+            ClaimLimit::<T>::mutate(|l| *l = l.saturating_sub(total));
+
+            Self::deposit_event(RawEvent::AirdropBatchPublished(batch_id, total));
+            Ok(())
+        }
+
+        /// Claim from a Merkle airdrop batch
+        ///
+        /// The ethereum signature should sign the `dest` with the `(batch_id, amount)` as the extra data.
+        #[weight = T::DbWeight::get().reads_writes(4, 4)
+            .saturating_add(100_000_000)
+            .saturating_add((1_000_000 as Weight).saturating_mul(proof.len() as Weight))]
+        fn claim_with_proof(origin, dest: T::AccountId, batch_id: u32, amount: BalanceOf<T>, proof: Vec<[u8; 32]>, sig: EcdsaSignature) -> DispatchResult {
+            let _ = ensure_none(origin)?;
+
+            // 1. Recover the claimer
+            let data = dest.using_encoded(to_ascii_hex);
+            let extra = (batch_id, amount).using_encoded(to_ascii_hex);
+            let signer = Self::eth_recover(&sig, &data, &extra).ok_or(Error::<T>::InvalidEthereumSignature)?;
+
+            // 2. Check the leaf is in the batch and not be claimed
+            let mut batch = Self::airdrop_batches(batch_id).ok_or(Error::<T>::AirdropBatchNotExist)?;
+            ensure!(!Self::is_expired(batch.deadline), Error::<T>::ClaimExpired);
+            ensure!(Self::verify_airdrop_proof(&batch.root, &signer, amount, &proof), Error::<T>::InvalidMerkleProof);
+            ensure!(!Self::airdrop_claimed(batch_id, &signer), Error::<T>::AlreadyBeClaimed);
+            ensure!(batch.claimed.saturating_add(amount) <= batch.total, Error::<T>::ExceedClaimLimit);
+
+            // 3. Give money to dest
+            T::Currency::transfer(&Self::claim_pot(), &dest, amount, AllowDeath)?;
+
+            // 4. Mark it be claimed
+            batch.claimed = batch.claimed.saturating_add(amount);
+            AirdropBatches::<T>::insert(batch_id, batch);
+            AirdropClaimed::insert(batch_id, signer, true);
+
+            Self::deposit_event(RawEvent::AirdropClaimed(dest, batch_id, signer, amount));
+            Ok(())
+        }
+
+        /// Sweep the expired and unclaimed claims, anyone can call it
+        ///
+        /// The value of the swept claims is restored into the claim limit and stays in the claim pot.
+        ///
+        /// Parameters:
+        /// - `txs`: The claim ethereum tx hashes to sweep
+        #[weight = T::DbWeight::get().reads_writes(3, 4)
+            .saturating_add(5_000_000)
+            .saturating_mul(txs.len() as Weight)
+            .saturating_add(10_000_000)]
+        fn sweep_expired_claims(origin, txs: Vec<EthereumTxHash>) -> DispatchResult {
+            let _ = ensure_signed(origin)?;
+
+            for tx in txs {
+                // 1. Only the expired and unclaimed tx can be swept
+                if Self::claimed(&tx) || !Self::is_expired(Self::claim_deadlines(&tx)) {
+                    continue;
+                }
+
+                // 2. Remove the claim and restore the claim limit
+                if let Some((_, value)) = Claims::<T>::take(&tx) {
+                    Claimed::remove(&tx);
+                    ClaimDeadlines::<T>::remove(&tx);
+                    ClaimLimit::<T>::mutate(|l| *l = l.saturating_add(value));
+                    Self::deposit_event(RawEvent::ClaimSwept(tx, value));
+                }
+            }
+            Ok(())
+        }
+
+        /// Sweep the expired airdrop batch, anyone can call it
+        ///
+        /// The unclaimed value of the batch is restored into the claim limit and stays in the claim pot.
+        /// The claimed marks of the batch are removed in chunks, the swept batch can be called again
+        /// until all of them are removed.
+        ///
+        /// Parameters:
+        /// - `batch_id`: The airdrop batch to sweep
+        /// - `max_claimed`: The max number of the claimed marks to remove in this call
+        #[weight = T::DbWeight::get().reads_writes(3, 2)
+            .saturating_add(10_000_000)
+            .saturating_add(T::DbWeight::get().reads_writes(1, 1).saturating_mul(max_claimed as Weight))]
+        fn sweep_expired_airdrop_batch(origin, batch_id: u32, max_claimed: u32) -> DispatchResult {
+            let _ = ensure_signed(origin)?;
+
+            // 1. Only the expired or already swept batch can be swept
+            if let Some(batch) = Self::airdrop_batches(batch_id) {
+                ensure!(batch.deadline.is_some() && Self::is_expired(batch.deadline), Error::<T>::ClaimNotExpired);
+
+                // 2. Remove the batch and restore the claim limit
+                let unclaimed = batch.total.saturating_sub(batch.claimed);
+                AirdropBatches::<T>::remove(batch_id);
+                ClaimLimit::<T>::mutate(|l| *l = l.saturating_add(unclaimed));
+                Self::deposit_event(RawEvent::AirdropBatchSwept(batch_id, unclaimed));
+            } else {
+                // The batch id is never reused, so the marks of a swept batch are useless
+                ensure!(batch_id < Self::next_airdrop_batch_id(), Error::<T>::AirdropBatchNotExist);
+            }
+
+            // 3. Remove the claimed marks of the batch
+            let claimed: Vec<EthereumAddress> = AirdropClaimed::iter_prefix(batch_id)
+                .take(max_claimed as usize)
+                .map(|(who, _)| who)
+                .collect();
+            for who in claimed {
+                AirdropClaimed::remove(batch_id, who);
+            }
+            Ok(())
+        }
+
         #[weight = 200_698_000]
         fn claim_cru18(origin, dest: T::AccountId, amount: BalanceOf<T>) -> DispatchResult {
             T::CRU18Origin::ensure_origin(origin)?;
@@ -357,6 +572,34 @@ decl_module! {
     }
 }
 
+/// Encodes the given number as the 32 bytes big-endian `uint256` of the ethereum ABI.
+fn eip712_uint256(value: u64) -> [u8; 32] {
+    let mut r = [0u8; 32];
+    r[24..].copy_from_slice(&value.to_be_bytes());
+    r
+}
+
+/// Encodes the given binary data into base58 with the bitcoin alphabet.
+fn to_base58(data: &[u8]) -> Vec<u8> {
+    let mut digits: Vec<u8> = Vec::with_capacity(data.len() * 138 / 100 + 1);
+    for &byte in data.iter() {
+        let mut carry = byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    let zeros = data.iter().take_while(|&&b| b == 0).count();
+    let mut r = vec![b'1'; zeros];
+    r.extend(digits.iter().rev().map(|&d| BASE58_ALPHABET[d as usize]));
+    r
+}
+
 /// Converts the given binary data into ASCII-encoded hex. It will be twice the length.
 fn to_ascii_hex(data: &[u8]) -> Vec<u8> {
     let mut r = Vec::with_capacity(data.len() * 2);
@@ -396,11 +639,149 @@ impl<T: Config> Module<T> {
     // the Ethereum RPC's `personal_sign` and `eth_sign`.
     fn eth_recover(s: &EcdsaSignature, what: &[u8], extra: &[u8]) -> Option<EthereumAddress> {
         let msg = keccak_256(&Self::ethereum_signable_message(what, extra));
+        Self::eth_recover_hash(s, &msg)
+    }
+
+    // Attempts to recover the Ethereum address from an EIP-712 typed data signature of the claim.
+    fn eth_recover_typed(s: &EcdsaSignature, dest: &T::AccountId, tx: &EthereumTxHash) -> Option<EthereumAddress> {
+        Self::eth_recover_hash(s, &Self::eip712_claim_digest(dest, tx))
+    }
+
+    fn eth_recover_hash(s: &EcdsaSignature, msg: &[u8; 32]) -> Option<EthereumAddress> {
         let mut res = EthereumAddress::default();
-        res.0.copy_from_slice(&keccak_256(&secp256k1_ecdsa_recover(&s.0, &msg).ok()?[..])[12..]);
+        res.0.copy_from_slice(&keccak_256(&secp256k1_ecdsa_recover(&s.0, msg).ok()?[..])[12..]);
         Some(res)
     }
 
+    // Constructs the EIP-712 digest that `eth_signTypedData_v4` would sign for the claim.
+    fn eip712_claim_digest(dest: &T::AccountId, tx: &EthereumTxHash) -> [u8; 32] {
+        let chain_id = eip712_uint256(T::ChainId::get());
+        // The genesis hash separates the networks sharing the same chain id, it's 32 bytes as `bytes32`
+        let genesis_hash = <frame_system::Module<T>>::block_hash(T::BlockNumber::zero());
+
+        let mut domain = keccak_256(EIP712_DOMAIN_TYPE).to_vec();
+        domain.extend_from_slice(&keccak_256(EIP712_DOMAIN_NAME));
+        domain.extend_from_slice(&keccak_256(EIP712_DOMAIN_VERSION));
+        domain.extend_from_slice(&chain_id);
+        domain.extend_from_slice(genesis_hash.as_ref());
+
+        let mut claim = keccak_256(EIP712_CLAIM_TYPE).to_vec();
+        claim.extend_from_slice(&keccak_256(&Self::ss58_address(dest)));
+        claim.extend_from_slice(&tx.0);
+        claim.extend_from_slice(&chain_id);
+
+        let mut v = b"\x19\x01".to_vec();
+        v.extend_from_slice(&keccak_256(&domain));
+        v.extend_from_slice(&keccak_256(&claim));
+        keccak_256(&v)
+    }
+
+    // Constructs the SS58 address of the account id with the network prefix, which is shown by the wallet.
+    fn ss58_address(who: &T::AccountId) -> Vec<u8> {
+        let prefix = <T as frame_system::Config>::SS58Prefix::get() as u16;
+        let mut v = if prefix < 64 {
+            vec![prefix as u8]
+        } else {
+            vec![((prefix & 0b1111_1100) as u8 >> 2) | 0b0100_0000, (prefix >> 8) as u8 | ((prefix & 0b11) as u8) << 6]
+        };
+        let account = who.encode();
+        v.extend_from_slice(&account);
+        // The checksum of the 32 bytes account id is 2 bytes, it's 1 byte for the shorter ones
+        let checksum_len = if account.len() >= 32 { 2 } else { 1 };
+        let checksum = blake2_512(&[&b"SS58PRE"[..], &v[..]].concat());
+        v.extend_from_slice(&checksum[..checksum_len]);
+        to_base58(&v)
+    }
+
+    // Whether the deadline has passed, the claim without deadline never expires.
+    fn is_expired(deadline: Option<T::BlockNumber>) -> bool {
+        deadline.map_or(false, |d| <frame_system::Module<T>>::block_number() > d)
+    }
+
+    fn ensure_root_or_superior(origin: T::Origin) -> DispatchResult {
+        if ensure_root(origin.clone()).is_ok() {
+            return Ok(());
+        }
+        let signer = ensure_signed(origin)?;
+        ensure!(Some(&signer) == Self::superior().as_ref(), Error::<T>::IllegalSuperior);
+        Ok(())
+    }
+
+    // Checks the `(who, amount)` leaf is in the airdrop Merkle tree with the given root.
+    fn verify_airdrop_proof(root: &[u8; 32], who: &EthereumAddress, amount: BalanceOf<T>, proof: &[[u8; 32]]) -> bool {
+        if proof.len() > MAX_AIRDROP_PROOF_DEPTH {
+            return false;
+        }
+        let leaf = keccak_256(&(who, amount).encode());
+        let computed = proof.iter().fold(leaf, |node, sibling| {
+            if node <= *sibling {
+                keccak_256(&[node, *sibling].concat())
+            } else {
+                keccak_256(&[*sibling, node].concat())
+            }
+        });
+        &computed == root
+    }
+
+    fn validate_claim_with_proof(dest: &T::AccountId, batch_id: u32, amount: BalanceOf<T>, proof: &[[u8; 32]], sig: &EcdsaSignature) -> TransactionValidity {
+        const PRIORITY: u64 = 100;
+
+        let data = dest.using_encoded(to_ascii_hex);
+        let extra = (batch_id, amount).using_encoded(to_ascii_hex);
+        let signer = Self::eth_recover(sig, &data, &extra)
+            .ok_or(InvalidTransaction::Custom(ValidityError::InvalidEthereumSignature.into()))?;
+
+        let e = InvalidTransaction::Custom(ValidityError::SignerHasNoClaim.into());
+        let batch = Self::airdrop_batches(batch_id).ok_or(e)?;
+        ensure!(Self::verify_airdrop_proof(&batch.root, &signer, amount, proof), e);
+
+        let e = InvalidTransaction::Custom(ValidityError::ClaimExpired.into());
+        ensure!(!Self::is_expired(batch.deadline), e);
+
+        let e = InvalidTransaction::Custom(ValidityError::AlreadyBeClaimed.into());
+        ensure!(!Self::airdrop_claimed(batch_id, &signer), e);
+
+        let e = InvalidTransaction::Custom(ValidityError::ExceedClaimLimit.into());
+        ensure!(batch.claimed.saturating_add(amount) <= batch.total, e);
+
+        Ok(ValidTransaction {
+            priority: PRIORITY,
+            requires: vec![],
+            provides: vec![("airdrop", batch_id, signer).encode()],
+            longevity: TransactionLongevity::max_value(),
+            propagate: true,
+        })
+    }
+
+    fn do_mint_claim(signer: T::AccountId, tx: EthereumTxHash, who: EthereumAddress, value: BalanceOf<T>, deadline: Option<T::BlockNumber>) -> DispatchResult {
+        let maybe_miner = Self::miner();
+
+        // 1. Check if miner exist
+        ensure!(maybe_miner.is_some(), Error::<T>::MinerNotExist);
+
+        // 2. Check if this tx already be mint
+        ensure!(!Claims::<T>::contains_key(&tx), Error::<T>::AlreadyBeMint);
+
+        // 3. Check if signer is miner
+        ensure!(Some(&signer) == maybe_miner.as_ref(), Error::<T>::IllegalMiner);
+
+        // 4. Check claim limit
+        ensure!(Self::claim_limit() >= value, Error::<T>::ExceedClaimLimit);
+
+        // 5. Save into claims
+        Claims::<T>::insert(tx.clone(), (who.clone(), value.clone()));
+        Claimed::insert(tx, false);
+        if let Some(deadline) = deadline {
+            ClaimDeadlines::<T>::insert(tx, deadline);
+        }
+
+        // 6. Reduce claim limit
+        ClaimLimit::<T>::mutate(|l| *l = l.saturating_sub(value));
+
+        Self::deposit_event(RawEvent::MintSuccess(tx, who, value));
+        Ok(())
+    }
+
     fn process_claim(tx: EthereumTxHash, signer: EthereumAddress, dest: T::AccountId) -> DispatchResult {
         if let Some((claimer, amount)) = Self::claims(&tx) {
             // 1. Ensure signer matches claimer
@@ -433,6 +814,10 @@ pub enum ValidityError {
     SignatureNotMatch = 2,
     /// This tx already be claimed.
     AlreadyBeClaimed = 3,
+    /// The claim is expired.
+    ClaimExpired = 4,
+    /// The claim exceeds the total of the airdrop batch.
+    ExceedClaimLimit = 5,
 }
 
 impl From<ValidityError> for u8 {
@@ -453,6 +838,12 @@ impl<T: Config> sp_runtime::traits::ValidateUnsigned for Module<T> {
                 let tx_data = tx.using_encoded(to_ascii_hex);
                 (Self::eth_recover(&sig, &data, &tx_data), tx)
             }
+            Call::claim_with_typed_signature(account, tx, sig) => {
+                (Self::eth_recover_typed(&sig, account, tx), tx)
+            }
+            Call::claim_with_proof(account, batch_id, amount, proof, sig) => {
+                return Self::validate_claim_with_proof(account, *batch_id, *amount, proof, sig);
+            }
             _ => return Err(InvalidTransaction::Call.into()),
         };
 
@@ -469,6 +860,9 @@ impl<T: Config> sp_runtime::traits::ValidateUnsigned for Module<T> {
         let e = InvalidTransaction::Custom(ValidityError::AlreadyBeClaimed.into());
         ensure!(!Self::claimed(&tx), e);
 
+        let e = InvalidTransaction::Custom(ValidityError::ClaimExpired.into());
+        ensure!(!Self::is_expired(Self::claim_deadlines(&tx)), e);
+
         Ok(ValidTransaction {
             priority: PRIORITY,
             requires: vec![],
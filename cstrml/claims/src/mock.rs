@@ -57,18 +57,24 @@ impl balances::Config for Test {
 
 parameter_types! {
     pub const UnlockPeriod: BlockNumber = 1000;
+    pub const MinVestedTransfer: u64 = 100;
+    pub const MaxVestingSchedules: u32 = 3;
 }
 
 impl locks::Config for Test {
     type Event = ();
     type Currency = Balances;
     type UnlockPeriod = UnlockPeriod;
+    type MinVestedTransfer = MinVestedTransfer;
+    type MaxVestingSchedules = MaxVestingSchedules;
+    type TransferLockOrigin = EnsureRoot<u64>;
     type WeightInfo = locks::weight::WeightInfo<Test>;
 }
 
 parameter_types!{
     pub const ClaimModuleId: ModuleId = ModuleId(*b"crclaims");
     pub Prefix: &'static [u8] = b"Pay RUSTs to the TEST account:";
+    pub const ClaimsChainId: u64 = 1;
 }
 impl Config for Test {
     type ModuleId = ClaimModuleId;
@@ -77,6 +83,7 @@ impl Config for Test {
     type Prefix = Prefix;
     type LocksInterface = CrustLocks;
     type CRU18Origin = EnsureRoot<u64>;
+    type ChainId = ClaimsChainId;
 }
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
 type Block = frame_system::mocking::MockBlock<Test>;
@@ -129,4 +136,44 @@ pub fn get_wrong_msg_eth_sig() -> EcdsaSignature {
     // data: wrong message
     EcdsaSignature(hex!["132ffc29ee017b5affa39367b31b66ff47d8db402dbee9c900128728c9b60096401f3126c6748c4f19bb262e80ab5f5d759dbe69c05d84464def96afe6d699ea1b"])
 
-}
\ No newline at end of file
+}
+
+pub fn eth_secret(seed: &[u8]) -> secp256k1::SecretKey {
+    secp256k1::SecretKey::parse(&keccak_256(seed)).unwrap()
+}
+
+pub fn eth_addr(secret: &secp256k1::SecretKey) -> EthereumAddress {
+    let mut res = EthereumAddress::default();
+    res.0.copy_from_slice(&keccak_256(&secp256k1::PublicKey::from_secret_key(secret).serialize()[1..65])[12..]);
+    res
+}
+
+pub fn eth_sign(secret: &secp256k1::SecretKey, what: &[u8], extra: &[u8]) -> EcdsaSignature {
+    let msg = keccak_256(&CrustClaims::ethereum_signable_message(&to_ascii_hex(what)[..], &to_ascii_hex(extra)[..]));
+    let (sig, recovery_id) = secp256k1::sign(&secp256k1::Message::parse(&msg), secret);
+    let mut r = [0u8; 65];
+    r[0..64].copy_from_slice(&sig.serialize()[..]);
+    r[64] = recovery_id.serialize();
+    EcdsaSignature(r)
+}
+
+pub fn airdrop_leaf(who: &EthereumAddress, amount: u64) -> [u8; 32] {
+    keccak_256(&(who, amount).encode())
+}
+
+pub fn merkle_parent(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+    if a <= b {
+        keccak_256(&[a, b].concat())
+    } else {
+        keccak_256(&[b, a].concat())
+    }
+}
+
+pub fn eth_sign_typed(secret: &secp256k1::SecretKey, dest: &u64, tx: &EthereumTxHash) -> EcdsaSignature {
+    let msg = CrustClaims::eip712_claim_digest(dest, tx);
+    let (sig, recovery_id) = secp256k1::sign(&secp256k1::Message::parse(&msg), secret);
+    let mut r = [0u8; 65];
+    r[0..64].copy_from_slice(&sig.serialize()[..]);
+    r[64] = recovery_id.serialize();
+    EcdsaSignature(r)
+}
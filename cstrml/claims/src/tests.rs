@@ -4,6 +4,8 @@
 use super::*;
 use crate::{mock::*, Error};
 use frame_support::{assert_noop, assert_ok, dispatch::DispatchError};
+use sp_runtime::traits::ValidateUnsigned;
+use hex_literal::hex;
 
 #[test]
 fn happy_pass_should_work() {
@@ -245,4 +247,232 @@ fn claim_cru18_should_work() {
             }
         );
     });
-}
\ No newline at end of file
+}
+
+#[test]
+fn claim_with_proof_should_work() {
+    new_test_ext().execute_with(|| {
+        // 0. Set superior and pot
+        assert_ok!(CrustClaims::change_superior(Origin::root(), 2));
+        let _ = Balances::deposit_creating(&CrustClaims::claim_pot(), 1000);
+        assert_ok!(CrustClaims::set_claim_limit(Origin::signed(2), 1000));
+
+        // 1. Build the airdrop tree of alice: 100, bob: 200 and charlie: 300
+        let (alice, bob, charlie) = (eth_secret(b"alice"), eth_secret(b"bob"), eth_secret(b"charlie"));
+        let alice_leaf = airdrop_leaf(&eth_addr(&alice), 100);
+        let bob_leaf = airdrop_leaf(&eth_addr(&bob), 200);
+        let charlie_leaf = airdrop_leaf(&eth_addr(&charlie), 300);
+        let alice_bob = merkle_parent(alice_leaf, bob_leaf);
+        let root = merkle_parent(alice_bob, charlie_leaf);
+
+        // 2. Publish the batch
+        assert_noop!(
+            CrustClaims::publish_airdrop_batch(Origin::signed(3), root, 600, None),
+            Error::<Test>::IllegalSuperior
+        );
+        assert_noop!(
+            CrustClaims::publish_airdrop_batch(Origin::signed(2), root, 2000, None),
+            Error::<Test>::ExceedClaimLimit
+        );
+        assert_ok!(CrustClaims::publish_airdrop_batch(Origin::signed(2), root, 600, None));
+        assert_eq!(CrustClaims::airdrop_batches(0), Some(AirdropBatch { root, total: 600, claimed: 0, deadline: None }));
+        assert_eq!(CrustClaims::next_airdrop_batch_id(), 1);
+        assert_eq!(CrustClaims::claim_limit(), 400);
+
+        // 3. Claim with proof
+        let sig = eth_sign(&alice, &5u64.encode(), &(0u32, 100u64).encode());
+        let call = crate::Call::<Test>::claim_with_proof(5, 0, 100, vec![bob_leaf, charlie_leaf], sig.clone());
+        assert!(<CrustClaims as ValidateUnsigned>::validate_unsigned(TransactionSource::External, &call).is_ok());
+        assert_ok!(CrustClaims::claim_with_proof(Origin::none(), 5, 0, 100, vec![bob_leaf, charlie_leaf], sig.clone()));
+        assert_eq!(Balances::free_balance(5), 100);
+        assert_eq!(CrustClaims::airdrop_claimed(0, eth_addr(&alice)), true);
+
+        // 4. Double claim should failed
+        assert!(<CrustClaims as ValidateUnsigned>::validate_unsigned(TransactionSource::External, &call).is_err());
+        assert_noop!(
+            CrustClaims::claim_with_proof(Origin::none(), 5, 0, 100, vec![bob_leaf, charlie_leaf], sig),
+            Error::<Test>::AlreadyBeClaimed
+        );
+
+        // 5. Claim with wrong amount should failed
+        let sig = eth_sign(&bob, &6u64.encode(), &(0u32, 250u64).encode());
+        assert_noop!(
+            CrustClaims::claim_with_proof(Origin::none(), 6, 0, 250, vec![alice_leaf, charlie_leaf], sig),
+            Error::<Test>::InvalidMerkleProof
+        );
+
+        // 6. Claim from not exist batch should failed
+        let sig = eth_sign(&charlie, &6u64.encode(), &(1u32, 300u64).encode());
+        assert_noop!(
+            CrustClaims::claim_with_proof(Origin::none(), 6, 1, 300, vec![alice_bob], sig),
+            Error::<Test>::AirdropBatchNotExist
+        );
+
+        // 7. Claim the leaf in the upper level
+        let sig = eth_sign(&charlie, &6u64.encode(), &(0u32, 300u64).encode());
+        assert_ok!(CrustClaims::claim_with_proof(Origin::none(), 6, 0, 300, vec![alice_bob], sig));
+        assert_eq!(Balances::free_balance(6), 300);
+        assert_eq!(CrustClaims::airdrop_batches(0).unwrap().claimed, 400);
+
+        // 8. Claim exceeding the total of the batch should failed
+        assert_ok!(CrustClaims::publish_airdrop_batch(Origin::signed(2), root, 250, None));
+        let sig = eth_sign(&bob, &7u64.encode(), &(1u32, 200u64).encode());
+        assert_ok!(CrustClaims::claim_with_proof(Origin::none(), 7, 1, 200, vec![alice_leaf, charlie_leaf], sig));
+        let sig = eth_sign(&charlie, &7u64.encode(), &(1u32, 300u64).encode());
+        let call = crate::Call::<Test>::claim_with_proof(7, 1, 300, vec![alice_bob], sig.clone());
+        assert!(<CrustClaims as ValidateUnsigned>::validate_unsigned(TransactionSource::External, &call).is_err());
+        assert_noop!(
+            CrustClaims::claim_with_proof(Origin::none(), 7, 1, 300, vec![alice_bob], sig),
+            Error::<Test>::ExceedClaimLimit
+        );
+        assert_eq!(Balances::free_balance(7), 200);
+    });
+}
+
+#[test]
+fn sweep_expired_claims_should_work() {
+    new_test_ext().execute_with(|| {
+        // 0. Set miner, superior and pot
+        System::set_block_number(1);
+        assert_ok!(CrustClaims::change_miner(Origin::root(), 1));
+        assert_ok!(CrustClaims::change_superior(Origin::root(), 2));
+        let _ = Balances::deposit_creating(&CrustClaims::claim_pot(), 1000);
+        assert_ok!(CrustClaims::set_claim_limit(Origin::signed(2), 1000));
+
+        // 1. Mint claims with and without deadline
+        let tx_hash1 = get_legal_tx_hash1();
+        let tx_hash2 = get_legal_tx_hash2();
+        let eth_addr = get_legal_eth_addr();
+        assert_noop!(
+            CrustClaims::mint_claim_with_deadline(Origin::signed(1), tx_hash1.clone(), eth_addr.clone(), 100, 1),
+            Error::<Test>::InvalidDeadline
+        );
+        assert_ok!(CrustClaims::mint_claim_with_deadline(Origin::signed(1), tx_hash1.clone(), eth_addr.clone(), 100, 10));
+        assert_ok!(CrustClaims::mint_claim(Origin::signed(1), tx_hash2.clone(), eth_addr.clone(), 200));
+        assert_eq!(CrustClaims::claim_deadlines(&tx_hash1), Some(10));
+        assert_eq!(CrustClaims::claim_limit(), 700);
+
+        // 2. Airdrop batch with deadline
+        assert_ok!(CrustClaims::publish_airdrop_batch(Origin::root(), [0u8; 32], 300, Some(10)));
+        assert_eq!(CrustClaims::claim_limit(), 400);
+        assert_noop!(
+            CrustClaims::sweep_expired_airdrop_batch(Origin::signed(3), 0, 10),
+            Error::<Test>::ClaimNotExpired
+        );
+
+        // 3. Nothing would be swept before the deadline
+        System::set_block_number(10);
+        assert_ok!(CrustClaims::sweep_expired_claims(Origin::signed(3), vec![tx_hash1.clone(), tx_hash2.clone()]));
+        assert_eq!(CrustClaims::claims(&tx_hash1), Some((eth_addr.clone(), 100)));
+        assert_eq!(CrustClaims::claim_limit(), 400);
+
+        // 4. Expired claim cannot be claimed anymore
+        System::set_block_number(11);
+        let sig = get_legal_eth_sig();
+        assert_noop!(
+            CrustClaims::claim(Origin::none(), 1, tx_hash1.clone(), sig.clone()),
+            Error::<Test>::ClaimExpired
+        );
+        let call = crate::Call::<Test>::claim(1, tx_hash1.clone(), sig);
+        assert!(<CrustClaims as ValidateUnsigned>::validate_unsigned(TransactionSource::External, &call).is_err());
+
+        // 5. Sweep the expired claim, the claim without deadline stays
+        assert_ok!(CrustClaims::sweep_expired_claims(Origin::signed(3), vec![tx_hash1.clone(), tx_hash2.clone()]));
+        assert_eq!(CrustClaims::claims(&tx_hash1), None);
+        assert_eq!(CrustClaims::claimed(&tx_hash1), false);
+        assert_eq!(CrustClaims::claim_deadlines(&tx_hash1), None);
+        assert_eq!(CrustClaims::claims(&tx_hash2), Some((eth_addr.clone(), 200)));
+        assert_eq!(CrustClaims::claim_limit(), 500);
+
+        // 6. Sweep the expired airdrop batch, the claimed marks are removed in chunks
+        for i in 1..4 {
+            AirdropClaimed::insert(0, EthereumAddress([i; 20]), true);
+        }
+        assert_ok!(CrustClaims::sweep_expired_airdrop_batch(Origin::signed(3), 0, 2));
+        assert_eq!(CrustClaims::airdrop_batches(0), None);
+        assert_eq!(CrustClaims::claim_limit(), 800);
+        assert_eq!(Balances::free_balance(CrustClaims::claim_pot()), 1000);
+        assert_eq!(AirdropClaimed::iter_prefix(0).count(), 1);
+        assert_ok!(CrustClaims::sweep_expired_airdrop_batch(Origin::signed(3), 0, 2));
+        assert_eq!(AirdropClaimed::iter_prefix(0).count(), 0);
+        assert_eq!(CrustClaims::claim_limit(), 800);
+        assert_noop!(
+            CrustClaims::sweep_expired_airdrop_batch(Origin::signed(3), 1, 2),
+            Error::<Test>::AirdropBatchNotExist
+        );
+    });
+}
+
+#[test]
+fn claim_with_typed_signature_should_work() {
+    new_test_ext().execute_with(|| {
+        // 0. Set miner, superior and pot
+        assert_ok!(CrustClaims::change_miner(Origin::root(), 1));
+        assert_ok!(CrustClaims::change_superior(Origin::root(), 2));
+        let _ = Balances::deposit_creating(&CrustClaims::claim_pot(), 1000);
+        assert_ok!(CrustClaims::set_claim_limit(Origin::signed(2), 1000));
+
+        // 1. Mint a claim
+        let alice = eth_secret(b"alice");
+        let tx_hash = get_legal_tx_hash1();
+        assert_ok!(CrustClaims::mint_claim(Origin::signed(1), tx_hash.clone(), eth_addr(&alice), 100));
+
+        // 2. Personal sign signature cannot be used as typed signature
+        let sig = eth_sign(&alice, &5u64.encode(), &tx_hash.encode());
+        assert_noop!(
+            CrustClaims::claim_with_typed_signature(Origin::none(), 5, tx_hash.clone(), sig),
+            Error::<Test>::SignatureNotMatch
+        );
+
+        // 3. Typed signature for another dest cannot be used
+        let sig = eth_sign_typed(&alice, &6, &tx_hash);
+        let call = crate::Call::<Test>::claim_with_typed_signature(5, tx_hash.clone(), sig.clone());
+        assert!(<CrustClaims as ValidateUnsigned>::validate_unsigned(TransactionSource::External, &call).is_err());
+        assert_noop!(
+            CrustClaims::claim_with_typed_signature(Origin::none(), 5, tx_hash.clone(), sig),
+            Error::<Test>::SignatureNotMatch
+        );
+
+        // 4. Claim with typed signature
+        let sig = eth_sign_typed(&alice, &5, &tx_hash);
+        let call = crate::Call::<Test>::claim_with_typed_signature(5, tx_hash.clone(), sig.clone());
+        assert!(<CrustClaims as ValidateUnsigned>::validate_unsigned(TransactionSource::External, &call).is_ok());
+        assert_ok!(CrustClaims::claim_with_typed_signature(Origin::none(), 5, tx_hash.clone(), sig.clone()));
+        assert_eq!(Balances::free_balance(5), 100);
+        assert_eq!(CrustClaims::claimed(&tx_hash), true);
+
+        // 5. Double claim should failed
+        assert_noop!(
+            CrustClaims::claim_with_typed_signature(Origin::none(), 5, tx_hash.clone(), sig),
+            Error::<Test>::AlreadyBeClaimed
+        );
+    });
+}
+
+#[test]
+fn eip712_claim_digest_should_match_fixed_vector() {
+    new_test_ext().execute_with(|| {
+        // The vector is what `eth_signTypedData_v4` signs with the key
+        // 0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318 for:
+        // domain: { name: "Crust Claims", version: "1", chainId: 1, salt: 0x4545...45 }
+        // message: { dest: "1Y1QsNeFGLscZ", tx: 0x6543...4324, chainId: 1 }
+        <frame_system::BlockHash<Test>>::insert(0, sp_core::H256::repeat_byte(69));
+        let tx_hash = get_legal_tx_hash1();
+        assert_eq!(CrustClaims::ss58_address(&42), b"1Y1QsNeFGLscZ".to_vec());
+        assert_eq!(
+            CrustClaims::eip712_claim_digest(&42, &tx_hash),
+            hex!["a81c14321a1550653fe39e979df4b1c163cae640db3318c5ae31ee51cced8824"]
+        );
+
+        let sig = EcdsaSignature(hex!["599c1468ac73ff01a239fb6de8e271594b89a91f0902b5d9f50374d68d68efbe0a129cb7d6836195c9ef3a3cb4b42f2b5e92addd821f9c84b36ea5c82c330be100"]);
+        assert_eq!(
+            CrustClaims::eth_recover_typed(&sig, &42, &tx_hash),
+            Some(EthereumAddress(hex!["2c7536e3605d9c16a7a3d7b1898e529396a65c23"]))
+        );
+        // The signature is bound to the dest
+        assert_ne!(
+            CrustClaims::eth_recover_typed(&sig, &43, &tx_hash),
+            Some(EthereumAddress(hex!["2c7536e3605d9c16a7a3d7b1898e529396a65c23"]))
+        );
+    });
+}
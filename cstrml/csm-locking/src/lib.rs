@@ -1,6 +1,5 @@
 // Copyright (C) 2019-2021 Crust Network Technologies Ltd.
 // This file is part of Crust.
-// TODO: Archived for a temp, may change into cru-locking
 
 #![recursion_limit = "128"]
 #![cfg_attr(not(feature = "std"), no_std)]
@@ -33,11 +32,12 @@ use sp_runtime::{
 use sp_std::{convert::TryInto, prelude::*};
 
 use frame_system::{ensure_root, ensure_signed};
-use primitives::BlockNumber;
+use primitives::{BlockNumber, traits::CsmInterface};
 
 pub mod weight;
 
 const MAX_UNLOCKING_CHUNKS: usize = 32;
+const MAX_GUARANTEE_PREF: u32 = 100;
 const LOCKING_ID: LockIdentifier = *b"csm-lock";
 
 pub trait WeightInfo {
@@ -124,6 +124,16 @@ impl<Balance: HasCompact + Copy + Saturating + AtLeast32BitUnsigned> CSMLedger<B
     }
 }
 
+/// The guarantee of an account's active locking CSM.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+pub struct CSMGuarantee<AccountId, Balance: HasCompact> {
+    /// The account being guaranteed.
+    pub target: AccountId,
+    /// The amount of CSM guaranteed to the target.
+    #[codec(compact)]
+    pub value: Balance,
+}
+
 pub type BalanceOf<T> =
     <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 
@@ -147,6 +157,21 @@ decl_storage! {
         pub Ledger get(fn ledger):
             map hasher(blake2_128_concat) T::AccountId
             => CSMLedger<BalanceOf<T>>;
+
+        /// Map from the guarantee targets to their guarantee pref, which is between 0 and 100.
+        pub GuaranteePrefs get(fn guarantee_prefs):
+            map hasher(blake2_128_concat) T::AccountId
+            => Option<u32>;
+
+        /// Map from the guarantors to their guarantee.
+        pub Guarantees get(fn guarantees):
+            map hasher(blake2_128_concat) T::AccountId
+            => Option<CSMGuarantee<T::AccountId, BalanceOf<T>>>;
+
+        /// Map from the guarantee targets to the total amount of CSM guaranteed to them.
+        pub GuaranteedTotals get(fn guaranteed_totals):
+            map hasher(blake2_128_concat) T::AccountId
+            => BalanceOf<T>;
     }
 }
 
@@ -164,10 +189,12 @@ decl_event!(
         Withdrawn(AccountId, Balance),
         /// Set guarantee pref success
         SetCSMGuaranteePerfSuccess(AccountId, u32),
-        /// Guarantee success
-        CSMGuaranteeSuccess(AccountId, AccountId),
+        /// Guarantee success. [guarantor, target, amount]
+        CSMGuaranteeSuccess(AccountId, AccountId, Balance),
         /// Cancel guarantee success
         CancelCSMGuaranteeSuccess(AccountId),
+        /// Cancel guarantee pref success
+        CancelCSMGuaranteePrefSuccess(AccountId),
     }
 );
 
@@ -182,6 +209,12 @@ decl_error! {
         InsufficientValue,
         /// Can not rebond without unlocking chunks.
         NoUnlockChunk,
+        /// The target has not set the guarantee pref.
+        InvalidTarget,
+        /// Not guaranteed.
+        NotGuaranteed,
+        /// Can not guarantee self.
+        SelfGuarantee,
     }
 }
 
@@ -317,28 +350,60 @@ decl_module! {
             .saturating_add(53 * WEIGHT_PER_MICROS)]
         fn set_guarantee_pref(origin, pref: u32) {
             let who = ensure_signed(origin)?;
-            let pref = pref.min(100u32);
+            let pref = pref.min(MAX_GUARANTEE_PREF);
+            <GuaranteePrefs<T>>::insert(&who, pref);
             Self::deposit_event(RawEvent::SetCSMGuaranteePerfSuccess(who, pref));
         }
 
         /// Guarantee all locking CSM to someone
         ///
         /// The dispatch used by layer-2
-        #[weight = T::DbWeight::get().reads_writes(4, 2)
+        #[weight = T::DbWeight::get().reads_writes(5, 3)
             .saturating_add(53 * WEIGHT_PER_MICROS)]
         fn guarantee(origin, target: <T::Lookup as StaticLookup>::Source) {
             let who = ensure_signed(origin)?;
             let target = T::Lookup::lookup(target)?;
-            Self::deposit_event(RawEvent::CSMGuaranteeSuccess(who, target));
+            ensure!(who != target, Error::<T>::SelfGuarantee);
+
+            // 1. Ensure who has active locking CSM
+            ensure!(<Ledger<T>>::contains_key(&who), Error::<T>::NotBonded);
+            let ledger = Self::ledger(&who);
+            ensure!(!ledger.active.is_zero(), Error::<T>::InsufficientValue);
+
+            // 2. Ensure target is willing to be guaranteed
+            ensure!(<GuaranteePrefs<T>>::contains_key(&target), Error::<T>::InvalidTarget);
+
+            // 3. Move the guarantee to the new target
+            Self::remove_guarantee(&who);
+            <GuaranteedTotals<T>>::mutate(&target, |total| *total = total.saturating_add(ledger.active));
+            <Guarantees<T>>::insert(&who, CSMGuarantee {
+                target: target.clone(),
+                value: ledger.active
+            });
+            Self::deposit_event(RawEvent::CSMGuaranteeSuccess(who, target, ledger.active));
         }
 
         /// Cut all guaranteed CSM
-        #[weight = T::DbWeight::get().reads_writes(4, 2)
+        #[weight = T::DbWeight::get().reads_writes(2, 2)
             .saturating_add(53 * WEIGHT_PER_MICROS)]
         fn cancel_guarantee(origin) {
             let who = ensure_signed(origin)?;
+            ensure!(Self::remove_guarantee(&who), Error::<T>::NotGuaranteed);
             Self::deposit_event(RawEvent::CancelCSMGuaranteeSuccess(who));
         }
+
+        /// Cancel the guarantee pref, the target won't accept any new guarantee.
+        /// The existing guarantees stay until their guarantors cancel them.
+        ///
+        /// The dispatch used by layer-2
+        #[weight = T::DbWeight::get().reads_writes(1, 1)
+            .saturating_add(53 * WEIGHT_PER_MICROS)]
+        fn cancel_guarantee_pref(origin) {
+            let who = ensure_signed(origin)?;
+            ensure!(<GuaranteePrefs<T>>::contains_key(&who), Error::<T>::InvalidTarget);
+            <GuaranteePrefs<T>>::remove(&who);
+            Self::deposit_event(RawEvent::CancelCSMGuaranteePrefSuccess(who));
+        }
     }
 }
 
@@ -361,12 +426,54 @@ impl<T: Config> Module<T> {
             WithdrawReasons::all(),
         );
         <Ledger<T>>::insert(who, ledger);
+        // the guarantee always follows the active locking CSM.
+        if let Some(mut guarantee) = Self::guarantees(who) {
+            <GuaranteedTotals<T>>::mutate(&guarantee.target, |total| {
+                *total = total.saturating_sub(guarantee.value).saturating_add(ledger.active)
+            });
+            guarantee.value = ledger.active;
+            <Guarantees<T>>::insert(who, guarantee);
+        }
     }
 
     fn kill_ledger(who: &T::AccountId) {
         // remove all locking-related information.
         <Ledger<T>>::remove(who);
+        Self::remove_guarantee(who);
         // remove the lock.
         T::Currency::remove_lock(LOCKING_ID, who);
     }
+
+    /// Remove the guarantee of who and return whether it existed.
+    fn remove_guarantee(who: &T::AccountId) -> bool {
+        if let Some(guarantee) = <Guarantees<T>>::take(who) {
+            let total = Self::guaranteed_totals(&guarantee.target).saturating_sub(guarantee.value);
+            if total.is_zero() {
+                <GuaranteedTotals<T>>::remove(&guarantee.target);
+            } else {
+                <GuaranteedTotals<T>>::insert(&guarantee.target, total);
+            }
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl<T: Config> CsmInterface<<T as frame_system::Config>::AccountId, BalanceOf<T>> for Module<T> {
+    fn locked_csm(who: &<T as frame_system::Config>::AccountId) -> BalanceOf<T> {
+        Self::ledger(who).active
+    }
+
+    fn guaranteed_csm(target: &<T as frame_system::Config>::AccountId) -> BalanceOf<T> {
+        Self::guaranteed_totals(target)
+    }
+
+    fn guarantee_of(who: &<T as frame_system::Config>::AccountId) -> Option<(<T as frame_system::Config>::AccountId, BalanceOf<T>)> {
+        Self::guarantees(who).map(|guarantee| (guarantee.target, guarantee.value))
+    }
+
+    fn guarantee_pref(target: &<T as frame_system::Config>::AccountId) -> Option<u32> {
+        Self::guarantee_prefs(target)
+    }
 }
\ No newline at end of file
@@ -320,4 +320,83 @@ fn force_unstake_should_work() {
         assert_eq!(<Ledger<Test>>::contains_key(&11), false);
         assert_eq!(Balances::locks(&1).len(), 0);
     });
-}
\ No newline at end of file
+}
+
+#[test]
+fn guarantee_should_work() {
+    ExtBuilder::default().build().execute_with(|| {
+        let _ = Balances::make_free_balance_be(&11, 3000);
+        let _ = Balances::make_free_balance_be(&21, 3000);
+
+        // 1. Guarantor should bond first
+        assert_noop!(
+            CSMLocking::guarantee(Origin::signed(11), 100),
+            Error::<Test>::NotBonded,
+        );
+        assert_ok!(CSMLocking::bond(Origin::signed(11), 1000));
+        assert_ok!(CSMLocking::bond(Origin::signed(21), 2000));
+
+        // 2. Target should set the guarantee pref first
+        assert_noop!(
+            CSMLocking::guarantee(Origin::signed(11), 100),
+            Error::<Test>::InvalidTarget,
+        );
+        assert_ok!(CSMLocking::set_guarantee_pref(Origin::signed(100), 200));
+        assert_eq!(CSMLocking::guarantee_prefs(&100), Some(100));
+        assert_ok!(CSMLocking::set_guarantee_pref(Origin::signed(101), 30));
+        assert_ok!(CSMLocking::set_guarantee_pref(Origin::signed(11), 30));
+
+        // 3. Can't guarantee self
+        assert_noop!(
+            CSMLocking::guarantee(Origin::signed(11), 11),
+            Error::<Test>::SelfGuarantee,
+        );
+
+        // 4. Guarantee all active CSM
+        assert_ok!(CSMLocking::guarantee(Origin::signed(11), 100));
+        assert_ok!(CSMLocking::guarantee(Origin::signed(21), 100));
+        assert_eq!(CSMLocking::guarantees(&11), Some(CSMGuarantee { target: 100, value: 1000 }));
+        assert_eq!(CSMLocking::guaranteed_csm(&100), 3000);
+        assert_eq!(CSMLocking::guarantee_of(&21), Some((100, 2000)));
+
+        // 5. Guarantee follows the ledger
+        assert_ok!(CSMLocking::unbond(Origin::signed(11), 400));
+        assert_eq!(CSMLocking::guarantees(&11), Some(CSMGuarantee { target: 100, value: 600 }));
+        assert_eq!(CSMLocking::guaranteed_csm(&100), 2600);
+        assert_ok!(CSMLocking::rebond(Origin::signed(11), 100));
+        assert_eq!(CSMLocking::guaranteed_csm(&100), 2700);
+
+        // 6. Move guarantee to another target
+        assert_ok!(CSMLocking::guarantee(Origin::signed(21), 101));
+        assert_eq!(CSMLocking::guaranteed_csm(&100), 700);
+        assert_eq!(CSMLocking::guaranteed_csm(&101), 2000);
+
+        // 7. Cancel guarantee
+        assert_ok!(CSMLocking::cancel_guarantee(Origin::signed(11)));
+        assert_eq!(CSMLocking::guarantees(&11), None);
+        assert_eq!(<GuaranteedTotals<Test>>::contains_key(&100), false);
+        assert_noop!(
+            CSMLocking::cancel_guarantee(Origin::signed(11)),
+            Error::<Test>::NotGuaranteed,
+        );
+
+        // 8. Cancel guarantee pref, the existing guarantee stays
+        assert_ok!(CSMLocking::cancel_guarantee_pref(Origin::signed(101)));
+        assert_eq!(CSMLocking::guarantee_pref(&101), None);
+        assert_eq!(CSMLocking::guarantee_of(&21), Some((101, 2000)));
+        assert_noop!(
+            CSMLocking::cancel_guarantee_pref(Origin::signed(101)),
+            Error::<Test>::InvalidTarget,
+        );
+        assert_noop!(
+            CSMLocking::guarantee(Origin::signed(11), 101),
+            Error::<Test>::InvalidTarget,
+        );
+
+        // 9. Force unstake removes the guarantee
+        assert_ok!(CSMLocking::force_unstake(Origin::root(), 21));
+        assert_eq!(CSMLocking::guarantees(&21), None);
+        assert_eq!(CSMLocking::guaranteed_csm(&101), 0);
+        assert_eq!(CSMLocking::locked_csm(&21), 0);
+    });
+}
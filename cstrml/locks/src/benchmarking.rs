@@ -31,6 +31,86 @@ benchmarks! {
     verify {
         assert_eq!(Locks::<T>::locks(&user).is_none(), true);
     }
+
+    unlock_for {
+        let caller = create_funded_user::<T>("caller", 100);
+        let user = create_funded_user::<T>("user", 100);
+        frame_system::Module::<T>::set_block_number(100u32.into());
+        Locks::<T>::issue_and_set_lock(&user, &(T::Currency::minimum_balance() * 1800u32.into()), CRU18);
+        frame_system::Module::<T>::set_block_number(30000000u32.into());
+        Locks::<T>::set_unlock_from(RawOrigin::Root.into(), 100u32.into()).expect("Something wrong during set unlock from");
+    }: _(RawOrigin::Signed(caller), user.clone())
+    verify {
+        assert_eq!(Locks::<T>::locks(&user).is_none(), true);
+    }
+
+    transfer_lock {
+        let user = create_funded_user::<T>("user", 100);
+        let target = create_funded_user::<T>("target", 100);
+        Locks::<T>::issue_and_set_lock(&user, &(T::Currency::minimum_balance() * 1800u32.into()), CRU18);
+    }: _(RawOrigin::Signed(user.clone()), target.clone())
+    verify {
+        assert_eq!(Locks::<T>::locks(&target).is_some(), true);
+    }
+
+    force_transfer_lock {
+        let user = create_funded_user::<T>("user", 100);
+        let target = create_funded_user::<T>("target", 100);
+        Locks::<T>::issue_and_set_lock(&user, &(T::Currency::minimum_balance() * 1800u32.into()), CRU18);
+    }: _(RawOrigin::Root, user.clone(), target.clone())
+    verify {
+        assert_eq!(Locks::<T>::locks(&target).is_some(), true);
+    }
+
+    vest {
+        let source = create_funded_user::<T>("source", 100);
+        let user = create_funded_user::<T>("user", 100);
+        let schedule = VestingSchedule {
+            total: T::MinVestedTransfer::get(),
+            start: 0,
+            cliff: 0,
+            period: 100,
+            period_count: 10,
+            tranches: vec![]
+        };
+        Locks::<T>::vested_transfer(RawOrigin::Signed(source).into(), user.clone(), schedule).expect("Something wrong during vested transfer");
+        frame_system::Module::<T>::set_block_number(1000u32.into());
+    }: _(RawOrigin::Signed(user.clone()))
+    verify {
+        assert_eq!(Locks::<T>::vesting_schedules(&user).is_empty(), true);
+    }
+
+    vested_transfer {
+        let source = create_funded_user::<T>("source", 100);
+        let user = create_funded_user::<T>("user", 100);
+        let schedule = VestingSchedule {
+            total: T::MinVestedTransfer::get(),
+            start: 0,
+            cliff: 0,
+            period: 100,
+            period_count: 10,
+            tranches: vec![]
+        };
+    }: _(RawOrigin::Signed(source), user.clone(), schedule)
+    verify {
+        assert_eq!(Locks::<T>::vesting_schedules(&user).len(), 1);
+    }
+
+    force_vested_transfer {
+        let source = create_funded_user::<T>("source", 100);
+        let user = create_funded_user::<T>("user", 100);
+        let schedule = VestingSchedule {
+            total: T::MinVestedTransfer::get(),
+            start: 0,
+            cliff: 0,
+            period: 100,
+            period_count: 10,
+            tranches: vec![]
+        };
+    }: _(RawOrigin::Root, source, user.clone(), schedule)
+    verify {
+        assert_eq!(Locks::<T>::vesting_schedules(&user).len(), 1);
+    }
 }
 
 
@@ -46,4 +126,46 @@ mod tests {
             assert_ok!(test_benchmark_unlock::<Test>());
         });
     }
+
+    #[test]
+    fn unlock_for() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_unlock_for::<Test>());
+        });
+    }
+
+    #[test]
+    fn transfer_lock() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_transfer_lock::<Test>());
+        });
+    }
+
+    #[test]
+    fn force_transfer_lock() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_force_transfer_lock::<Test>());
+        });
+    }
+
+    #[test]
+    fn vest() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_vest::<Test>());
+        });
+    }
+
+    #[test]
+    fn vested_transfer() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_vested_transfer::<Test>());
+        });
+    }
+
+    #[test]
+    fn force_vested_transfer() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_force_vested_transfer::<Test>());
+        });
+    }
 }
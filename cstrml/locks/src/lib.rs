@@ -8,7 +8,7 @@ use sp_std::{prelude::*, convert::TryInto};
 use frame_support::{
     decl_event, decl_storage, decl_module, decl_error, ensure,
     weights::{Weight},
-    traits::{LockableCurrency, Get, Currency, WithdrawReasons, LockIdentifier}
+    traits::{LockableCurrency, Get, Currency, WithdrawReasons, LockIdentifier, ExistenceRequirement, EnsureOrigin}
 };
 use frame_system::{ensure_signed, ensure_root};
 use codec::{Encode, Decode, HasCompact};
@@ -16,7 +16,7 @@ use codec::{Encode, Decode, HasCompact};
 use serde::{self, Serialize, Deserialize};
 
 use sp_runtime::{
-    RuntimeDebug, DispatchResult, Perbill, traits::Zero
+    RuntimeDebug, DispatchResult, Perbill, traits::{Zero, Saturating, AtLeast32BitUnsigned}
 };
 
 use primitives::BlockNumber;
@@ -37,6 +37,12 @@ const CRU_LOCK_ID: LockIdentifier = *b"crulock ";
 
 pub trait WeightInfo {
     fn unlock() -> Weight;
+    fn unlock_for() -> Weight;
+    fn vest() -> Weight;
+    fn vested_transfer() -> Weight;
+    fn force_vested_transfer() -> Weight;
+    fn transfer_lock() -> Weight;
+    fn force_transfer_lock() -> Weight;
 }
 
 /// The balance type of this module.
@@ -48,6 +54,12 @@ pub trait Config: frame_system::Config {
     type Currency: LockableCurrency<Self::AccountId>;
     /// One unlock period.
     type UnlockPeriod: Get<BlockNumber>;
+    /// The minimum amount transferred to create a vesting schedule.
+    type MinVestedTransfer: Get<BalanceOf<Self>>;
+    /// The maximum number of concurrent vesting schedules of an account.
+    type MaxVestingSchedules: Get<u32>;
+    /// The origin which can transfer the lock of any account. Root can always do this.
+    type TransferLockOrigin: EnsureOrigin<Self::Origin>;
     /// Weight information for extrinsics in this pallet.
     type WeightInfo: WeightInfo;
 }
@@ -87,6 +99,58 @@ pub struct Lock<Balance: HasCompact> {
     pub lock_type: LockType
 }
 
+/// A vesting schedule. Nothing is vested before `start + cliff`, then the tranche of each period
+/// passed since `start` is vested, linearly if `tranches` is empty.
+#[derive(Clone, Encode, Decode, Default, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct VestingSchedule<Balance: HasCompact> {
+    // Total amount of the schedule
+    #[codec(compact)]
+    pub total: Balance,
+    // The block number from which the periods are counted
+    pub start: BlockNumber,
+    // The blocks after start before anything is vested
+    pub cliff: BlockNumber,
+    // The length of one period
+    pub period: BlockNumber,
+    // The number of periods
+    pub period_count: u32,
+    // The ratio of total vested in each period, which should sum up to 100%
+    pub tranches: Vec<Perbill>,
+}
+
+impl<Balance: AtLeast32BitUnsigned + HasCompact + Copy> VestingSchedule<Balance> {
+    /// Whether the schedule has non-zero periods and well-formed tranches.
+    pub fn is_valid(&self) -> bool {
+        let tranches_sum: u64 = self.tranches.iter().map(|t| t.deconstruct() as u64).sum();
+        !self.period.is_zero() && !self.period_count.is_zero() &&
+            (self.tranches.is_empty() ||
+                (self.tranches.len() == self.period_count as usize &&
+                    tranches_sum == Perbill::one().deconstruct() as u64))
+    }
+
+    /// The amount vested at the given block number.
+    pub fn vested_at(&self, bn: BlockNumber) -> Balance {
+        if bn < self.start.saturating_add(self.cliff) {
+            return Zero::zero();
+        }
+        let passed_periods = (bn.saturating_sub(self.start) / self.period).min(self.period_count);
+        if passed_periods == self.period_count {
+            self.total
+        } else if self.tranches.is_empty() {
+            Perbill::from_rational_approximation(passed_periods, self.period_count) * self.total
+        } else {
+            let vested_parts: u32 = self.tranches.iter().take(passed_periods as usize).map(|t| t.deconstruct()).sum();
+            Perbill::from_parts(vested_parts) * self.total
+        }
+    }
+
+    /// The amount still locked at the given block number.
+    pub fn locked_at(&self, bn: BlockNumber) -> Balance {
+        self.total.saturating_sub(self.vested_at(bn))
+    }
+}
+
 impl<T: Config> LocksInterface<<T as frame_system::Config>::AccountId, BalanceOf<T>> for Module<T>
 {
     fn create_cru18_lock(who: &<T as frame_system::Config>::AccountId, amount: BalanceOf<T>) {
@@ -97,11 +161,21 @@ impl<T: Config> LocksInterface<<T as frame_system::Config>::AccountId, BalanceOf
 decl_event!(
     pub enum Event<T> where
         AccountId = <T as frame_system::Config>::AccountId,
+        Balance = BalanceOf<T>,
     {
         /// Set global unlock from date
         UnlockStartedFrom(BlockNumber),
         /// Unlock success
         UnlockSuccess(AccountId, BlockNumber),
+        /// A vesting schedule has been added.
+        /// [source, target, total]
+        VestingScheduleAdded(AccountId, AccountId, Balance),
+        /// The vested amount of the account has been released, and the remaining amount is still locked.
+        /// [who, locked]
+        VestingUpdated(AccountId, Balance),
+        /// The lock and its remaining locked amount have been transferred.
+        /// [source, target, locked]
+        LockTransferred(AccountId, AccountId, Balance),
     }
 );
 
@@ -115,6 +189,16 @@ decl_error! {
         LockNotExist,
         /// Wait for the next unlock date.
         TimeIsNotEnough,
+        /// The vesting schedule has zero periods or its tranches don't sum up to 100%.
+        InvalidVestingSchedule,
+        /// The amount is lower than the `MinVestedTransfer`.
+        AmountLow,
+        /// The account already has `MaxVestingSchedules` vesting schedules.
+        TooManyVestingSchedules,
+        /// The account doesn't have any vesting schedule.
+        NotVesting,
+        /// The target already has a lock or is the source itself.
+        InvalidLockTarget,
     }
 }
 
@@ -124,6 +208,9 @@ decl_storage! {
         Locks get(fn locks): map hasher(blake2_128_concat) T::AccountId => Option<Lock<BalanceOf<T>>>;
         // The global unlock date
         UnlockFrom get(fn unlock_from): Option<BlockNumber>;
+        // Vesting schedules of each account
+        VestingSchedules get(fn vesting_schedules):
+            map hasher(blake2_128_concat) T::AccountId => Vec<VestingSchedule<BalanceOf<T>>>;
     }
     add_extra_genesis {
         config(genesis_locks):
@@ -166,38 +253,59 @@ decl_module! {
         #[weight = T::WeightInfo::unlock()]
         fn unlock(origin) -> DispatchResult {
             let who = ensure_signed(origin)?;
-            let curr_bn = Self::get_current_block_number();
-
-            // 1. Ensure the unlock from is set and now unlocking period has started
-            ensure!(Self::unlock_from().is_some() && curr_bn > Self::unlock_from().unwrap(), Error::<T>::NotStarted);
-            // 2. Ensure who has the CRU18 or CRU24
-            ensure!(Self::locks(&who).is_some(), Error::<T>::LockNotExist);
-
-            let lock = Self::locks(&who).unwrap();
-            let unlock_from = Self::unlock_from().unwrap();
-            let curr_period = Self::round_bn_to_period(unlock_from, curr_bn);
+            Self::do_unlock(&who)
+        }
 
-            // 3. The first time that we would add the delay into checking
-            let last_unlock_at = if lock.last_unlock_at == 0 {
-                unlock_from + lock.lock_type.delay
-            } else {
-                lock.last_unlock_at
-            };
+        /// Unlock the CRU18 or CRU24 one period for the holder
+        /// Anyone can call it and the unlocked amount always stays in the holder's account
+        #[weight = T::WeightInfo::unlock_for()]
+        fn unlock_for(origin, who: T::AccountId) -> DispatchResult {
+            let _ = ensure_signed(origin)?;
+            Self::do_unlock(&who)
+        }
 
-            // 4. Ensure who has some CRU to unlock
-            ensure!(curr_period > last_unlock_at, Error::<T>::TimeIsNotEnough);
+        /// Move the lock and its remaining locked amount of the sender to the target
+        #[weight = T::WeightInfo::transfer_lock()]
+        fn transfer_lock(origin, target: T::AccountId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::do_transfer_lock(&who, &target)
+        }
 
-            // 5. Count the total unlock period => Count the total unlock amount => Refresh the remaining locked amount
-            let unlock_peroids = curr_period.saturating_sub(unlock_from).saturating_sub(lock.lock_type.delay) / T::UnlockPeriod::get();
-            let unlock_amount = Perbill::from_rational_approximation(unlock_peroids, lock.lock_type.lock_period) * lock.total;
-            let locked_amount = lock.total - unlock_amount;
+        /// Move the lock and its remaining locked amount of the source to the target
+        /// It can only be called by the `T::TransferLockOrigin`
+        #[weight = T::WeightInfo::force_transfer_lock()]
+        fn force_transfer_lock(origin, source: T::AccountId, target: T::AccountId) -> DispatchResult {
+            T::TransferLockOrigin::try_origin(origin)
+                .map(|_| ())
+                .or_else(ensure_root)?;
+            Self::do_transfer_lock(&source, &target)
+        }
 
-            // 6. Update the lock
-            Self::update_lock(&who, lock, locked_amount, curr_period);
+        /// Release the vested amount of all the vesting schedules of the sender
+        #[weight = T::WeightInfo::vest()]
+        fn vest(origin) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::update_vesting(&who)
+        }
 
-            Self::deposit_event(RawEvent::UnlockSuccess(who, curr_period));
+        /// Transfer some amount to the target and lock it with the vesting schedule
+        #[weight = T::WeightInfo::vested_transfer()]
+        fn vested_transfer(origin, target: T::AccountId, schedule: VestingSchedule<BalanceOf<T>>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::do_vested_transfer(&who, &target, schedule)
+        }
 
-            Ok(())
+        /// Transfer some amount from the source to the target and lock it with the vesting schedule
+        /// It can only be called by root
+        #[weight = T::WeightInfo::force_vested_transfer()]
+        fn force_vested_transfer(
+            origin,
+            source: T::AccountId,
+            target: T::AccountId,
+            schedule: VestingSchedule<BalanceOf<T>>
+        ) -> DispatchResult {
+            ensure_root(origin)?;
+            Self::do_vested_transfer(&source, &target, schedule)
         }
     }
 }
@@ -208,14 +316,109 @@ impl<T: Config> Module<T> {
         TryInto::<u32>::try_into(current_block_number).ok().unwrap()
     }
 
+    fn do_unlock(who: &T::AccountId) -> DispatchResult {
+        let curr_bn = Self::get_current_block_number();
+
+        // 1. Ensure the unlock from is set and now unlocking period has started
+        ensure!(Self::unlock_from().is_some() && curr_bn > Self::unlock_from().unwrap(), Error::<T>::NotStarted);
+        // 2. Ensure who has the CRU18 or CRU24
+        ensure!(Self::locks(who).is_some(), Error::<T>::LockNotExist);
+
+        let lock = Self::locks(who).unwrap();
+        let unlock_from = Self::unlock_from().unwrap();
+        let curr_period = Self::round_bn_to_period(unlock_from, curr_bn);
+
+        // 3. The first time that we would add the delay into checking
+        let last_unlock_at = if lock.last_unlock_at == 0 {
+            unlock_from + lock.lock_type.delay
+        } else {
+            lock.last_unlock_at
+        };
+
+        // 4. Ensure who has some CRU to unlock
+        ensure!(curr_period > last_unlock_at, Error::<T>::TimeIsNotEnough);
+
+        // 5. Count the total unlock period => Count the total unlock amount => Refresh the remaining locked amount
+        let unlock_peroids = curr_period.saturating_sub(unlock_from).saturating_sub(lock.lock_type.delay) / T::UnlockPeriod::get();
+        let unlock_amount = Perbill::from_rational_approximation(unlock_peroids, lock.lock_type.lock_period) * lock.total;
+        let locked_amount = lock.total - unlock_amount;
+
+        // 6. Update the lock
+        Self::update_lock(who, lock, locked_amount, curr_period);
+
+        Self::deposit_event(RawEvent::UnlockSuccess(who.clone(), curr_period));
+
+        Ok(())
+    }
+
+    fn do_transfer_lock(source: &T::AccountId, target: &T::AccountId) -> DispatchResult {
+        // 1. Ensure the source has a lock and the target doesn't
+        let lock = Self::locks(source).ok_or(Error::<T>::LockNotExist)?;
+        ensure!(source != target && Self::locks(target).is_none(), Error::<T>::InvalidLockTarget);
+
+        // 2. Release the lock of the source, then transfer the remaining locked amount
+        let locked_amount = Self::remaining_locked(&lock);
+        <Locks<T>>::remove(source);
+        Self::set_currency_lock(source);
+        if let Err(e) = T::Currency::transfer(source, target, locked_amount, ExistenceRequirement::AllowDeath) {
+            // Restore the lock of the source
+            <Locks<T>>::insert(source, lock);
+            Self::set_currency_lock(source);
+            return Err(e);
+        }
+
+        // 3. Lock the amount in the target with the same unlock progress
+        <Locks<T>>::insert(target, lock);
+        Self::set_currency_lock(target);
+
+        Self::deposit_event(RawEvent::LockTransferred(source.clone(), target.clone(), locked_amount));
+        Ok(())
+    }
+
     fn update_lock(who: &T::AccountId, mut lock: Lock<BalanceOf<T>>, locked_amount: BalanceOf<T>, curr_period: BlockNumber) {
         // Remove the lock or set the new lock
+        if locked_amount.is_zero() {
+            <Locks<T>>::remove(who);
+        } else {
+            // Update the last unlock at to the current period
+            lock.last_unlock_at = curr_period;
+            <Locks<T>>::insert(who, lock);
+        }
+        Self::set_currency_lock(who);
+    }
+
+    /// The remaining locked amount of the lock after its last unlock
+    fn remaining_locked(lock: &Lock<BalanceOf<T>>) -> BalanceOf<T> {
+        match Self::unlock_from() {
+            Some(unlock_from) if lock.last_unlock_at != 0 => {
+                let unlock_peroids = lock.last_unlock_at.saturating_sub(unlock_from).saturating_sub(lock.lock_type.delay) / T::UnlockPeriod::get();
+                let unlock_amount = Perbill::from_rational_approximation(unlock_peroids, lock.lock_type.lock_period) * lock.total;
+                lock.total.saturating_sub(unlock_amount)
+            },
+            _ => lock.total
+        }
+    }
+
+    /// The total amount still locked by the vesting schedules at the given block number
+    fn vesting_locked(who: &T::AccountId, bn: BlockNumber) -> BalanceOf<T> {
+        Self::vesting_schedules(who)
+            .iter()
+            .fold(Zero::zero(), |acc: BalanceOf<T>, schedule| acc.saturating_add(schedule.locked_at(bn)))
+    }
+
+    /// Set the currency lock to the sum of the lock and the vesting schedules,
+    /// since the currency locks with different ids overlap with each other
+    fn set_currency_lock(who: &T::AccountId) {
+        let curr_bn = Self::get_current_block_number();
+        let locked_amount = Self::locks(who)
+            .map(|lock| Self::remaining_locked(&lock))
+            .unwrap_or_default()
+            .saturating_add(Self::vesting_locked(who, curr_bn));
         if locked_amount.is_zero() {
             T::Currency::remove_lock(
                 CRU_LOCK_ID,
                 who
             );
-            <Locks<T>>::remove(who);
         } else {
             T::Currency::set_lock(
                 CRU_LOCK_ID,
@@ -223,11 +426,43 @@ impl<T: Config> Module<T> {
                 locked_amount,
                 WithdrawReasons::TRANSFER
             );
-            // Update the last unlock at to the current period
-            lock.last_unlock_at = curr_period;
-            <Locks<T>>::insert(who, lock);
         }
+    }
+
+    /// Remove the fully vested schedules and release the vested amount
+    fn update_vesting(who: &T::AccountId) -> DispatchResult {
+        let mut schedules = Self::vesting_schedules(who);
+        ensure!(!schedules.is_empty(), Error::<T>::NotVesting);
+
+        let curr_bn = Self::get_current_block_number();
+        schedules.retain(|schedule| !schedule.locked_at(curr_bn).is_zero());
+        if schedules.is_empty() {
+            <VestingSchedules<T>>::remove(who);
+        } else {
+            <VestingSchedules<T>>::insert(who, schedules);
+        }
+        Self::set_currency_lock(who);
+
+        Self::deposit_event(RawEvent::VestingUpdated(who.clone(), Self::vesting_locked(who, curr_bn)));
+        Ok(())
+    }
 
+    fn do_vested_transfer(source: &T::AccountId, target: &T::AccountId, schedule: VestingSchedule<BalanceOf<T>>) -> DispatchResult {
+        // 1. Ensure the schedule is valid and the target can hold one more schedule
+        ensure!(schedule.is_valid(), Error::<T>::InvalidVestingSchedule);
+        ensure!(schedule.total >= T::MinVestedTransfer::get(), Error::<T>::AmountLow);
+        let mut schedules = Self::vesting_schedules(target);
+        ensure!(schedules.len() < T::MaxVestingSchedules::get() as usize, Error::<T>::TooManyVestingSchedules);
+
+        // 2. Transfer the total amount and lock it
+        T::Currency::transfer(source, target, schedule.total, ExistenceRequirement::AllowDeath)?;
+        let total = schedule.total;
+        schedules.push(schedule);
+        <VestingSchedules<T>>::insert(target, schedules);
+        Self::set_currency_lock(target);
+
+        Self::deposit_event(RawEvent::VestingScheduleAdded(source.clone(), target.clone(), total));
+        Ok(())
     }
 
     fn create_or_extend_lock(who: &T::AccountId, amount: &BalanceOf<T>, lock_type: LockType) {
@@ -256,13 +491,7 @@ impl<T: Config> Module<T> {
 
         });
 
-        let total_amount = Self::locks(&who).unwrap().total;
-        T::Currency::set_lock(
-            CRU_LOCK_ID,
-            who,
-            total_amount,
-            WithdrawReasons::TRANSFER
-        );
+        Self::set_currency_lock(who);
     }
 
     fn round_bn_to_period(unlock_bn: BlockNumber, bn: BlockNumber) -> BlockNumber {
@@ -54,12 +54,17 @@ impl balances::Config for Test {
 
 parameter_types! {
     pub const UnlockPeriod: BlockNumber = 1000;
+    pub const MinVestedTransfer: u64 = 100;
+    pub const MaxVestingSchedules: u32 = 3;
 }
 
 impl Config for Test {
     type Event = ();
     type Currency = Balances;
     type UnlockPeriod = UnlockPeriod;
+    type MinVestedTransfer = MinVestedTransfer;
+    type MaxVestingSchedules = MaxVestingSchedules;
+    type TransferLockOrigin = frame_system::EnsureRoot<u64>;
     type WeightInfo = weight::WeightInfo<Test>;
 }
 
@@ -334,4 +334,250 @@ fn extend_lock_should_work() {
         assert_eq!(Balances::locks(&1)[0].amount, 1200);
         assert_eq!(Balances::locks(&1)[0].id, CRU_LOCK_ID);
     });
-}
\ No newline at end of file
+}
+
+fn linear_schedule(total: u64, start: BlockNumber, cliff: BlockNumber) -> VestingSchedule<u64> {
+    VestingSchedule {
+        total,
+        start,
+        cliff,
+        period: 100,
+        period_count: 10,
+        tranches: vec![]
+    }
+}
+
+#[test]
+fn vested_transfer_should_work() {
+    new_test_ext().execute_with(|| {
+        let _ = Balances::make_free_balance_be(&1, 10000);
+        assert_ok!(CrustLocks::vested_transfer(Origin::signed(1), 2, linear_schedule(1000, 100, 300)));
+        assert_eq!(Balances::free_balance(&1), 9000);
+        assert_eq!(Balances::free_balance(&2), 1000);
+        assert_eq!(Balances::locks(&2)[0].amount, 1000);
+        assert_eq!(Balances::locks(&2)[0].id, CRU_LOCK_ID);
+
+        // Nothing is vested before the cliff
+        run_to_block(399);
+        assert_ok!(CrustLocks::vest(Origin::signed(2)));
+        assert_eq!(Balances::locks(&2)[0].amount, 1000);
+
+        // The periods passed before the cliff are vested at once
+        run_to_block(400);
+        assert_ok!(CrustLocks::vest(Origin::signed(2)));
+        assert_eq!(Balances::locks(&2)[0].amount, 700);
+
+        run_to_block(1100);
+        assert_ok!(CrustLocks::vest(Origin::signed(2)));
+        assert_eq!(Balances::locks(&2).len(), 0);
+        assert_eq!(<VestingSchedules<Test>>::contains_key(&2), false);
+        assert_noop!(
+            CrustLocks::vest(Origin::signed(2)),
+            DispatchError::Module {
+                index: 2,
+                error: 7,
+                message: Some("NotVesting"),
+            }
+        );
+    });
+}
+
+#[test]
+fn vested_transfer_should_check_schedule() {
+    new_test_ext().execute_with(|| {
+        let _ = Balances::make_free_balance_be(&1, 10000);
+        let mut invalid_schedule = linear_schedule(1000, 0, 0);
+        invalid_schedule.period = 0;
+        assert_noop!(
+            CrustLocks::vested_transfer(Origin::signed(1), 2, invalid_schedule),
+            DispatchError::Module {
+                index: 2,
+                error: 4,
+                message: Some("InvalidVestingSchedule"),
+            }
+        );
+        let mut invalid_schedule = linear_schedule(1000, 0, 0);
+        invalid_schedule.tranches = vec![Perbill::from_percent(10); 9];
+        assert_noop!(
+            CrustLocks::vested_transfer(Origin::signed(1), 2, invalid_schedule),
+            DispatchError::Module {
+                index: 2,
+                error: 4,
+                message: Some("InvalidVestingSchedule"),
+            }
+        );
+        assert_noop!(
+            CrustLocks::vested_transfer(Origin::signed(1), 2, linear_schedule(99, 0, 0)),
+            DispatchError::Module {
+                index: 2,
+                error: 5,
+                message: Some("AmountLow"),
+            }
+        );
+        assert_noop!(
+            CrustLocks::force_vested_transfer(Origin::signed(1), 1, 2, linear_schedule(1000, 0, 0)),
+            DispatchError::BadOrigin
+        );
+
+        // Multiple schedules are allowed
+        assert_ok!(CrustLocks::vested_transfer(Origin::signed(1), 2, linear_schedule(1000, 0, 0)));
+        assert_ok!(CrustLocks::force_vested_transfer(Origin::root(), 1, 2, linear_schedule(1000, 500, 0)));
+        assert_ok!(CrustLocks::vested_transfer(Origin::signed(1), 2, linear_schedule(1000, 1000, 0)));
+        assert_eq!(Balances::locks(&2)[0].amount, 3000);
+        assert_noop!(
+            CrustLocks::vested_transfer(Origin::signed(1), 2, linear_schedule(1000, 0, 0)),
+            DispatchError::Module {
+                index: 2,
+                error: 6,
+                message: Some("TooManyVestingSchedules"),
+            }
+        );
+
+        run_to_block(1000);
+        assert_ok!(CrustLocks::vest(Origin::signed(2)));
+        assert_eq!(CrustLocks::vesting_schedules(&2).len(), 2);
+        assert_eq!(Balances::locks(&2)[0].amount, 1500);
+    });
+}
+
+#[test]
+fn vest_with_tranches_should_work() {
+    new_test_ext().execute_with(|| {
+        let _ = Balances::make_free_balance_be(&1, 10000);
+        let schedule = VestingSchedule {
+            total: 1000,
+            start: 0,
+            cliff: 0,
+            period: 100,
+            period_count: 4,
+            tranches: vec![
+                Perbill::from_percent(10),
+                Perbill::zero(),
+                Perbill::from_percent(40),
+                Perbill::from_percent(50)
+            ]
+        };
+        assert_ok!(CrustLocks::vested_transfer(Origin::signed(1), 2, schedule));
+
+        run_to_block(100);
+        assert_ok!(CrustLocks::vest(Origin::signed(2)));
+        assert_eq!(Balances::locks(&2)[0].amount, 900);
+
+        run_to_block(250);
+        assert_ok!(CrustLocks::vest(Origin::signed(2)));
+        assert_eq!(Balances::locks(&2)[0].amount, 900);
+
+        run_to_block(300);
+        assert_ok!(CrustLocks::vest(Origin::signed(2)));
+        assert_eq!(Balances::locks(&2)[0].amount, 500);
+
+        run_to_block(400);
+        assert_ok!(CrustLocks::vest(Origin::signed(2)));
+        assert_eq!(Balances::locks(&2).len(), 0);
+    });
+}
+
+#[test]
+fn vesting_should_stack_with_lock() {
+    new_test_ext().execute_with(|| {
+        run_to_block(300);
+        assert_ok!(CrustLocks::set_unlock_from(Origin::root(), 1000));
+        let _ = Balances::make_free_balance_be(&2, 10000);
+
+        CrustLocks::issue_and_set_lock(&1, &1800, CRU18);
+        assert_ok!(CrustLocks::vested_transfer(Origin::signed(2), 1, linear_schedule(1000, 5000, 0)));
+        assert_eq!(Balances::locks(&1).len(), 1);
+        assert_eq!(Balances::locks(&1)[0].amount, 2800);
+
+        run_to_block(2000);
+        assert_ok!(CrustLocks::unlock(Origin::signed(1)));
+        assert_eq!(Balances::locks(&1)[0].amount, 2700);
+
+        run_to_block(5500);
+        assert_ok!(CrustLocks::vest(Origin::signed(1)));
+        assert_eq!(Balances::locks(&1)[0].amount, 2200);
+    });
+}
+
+#[test]
+fn unlock_for_should_work() {
+    new_test_ext().execute_with(|| {
+        run_to_block(300);
+        assert_ok!(CrustLocks::set_unlock_from(Origin::root(), 1000));
+        CrustLocks::issue_and_set_lock(&1, &1800, CRU18);
+
+        run_to_block(2000);
+        assert_ok!(CrustLocks::unlock_for(Origin::signed(2), 1));
+        assert_eq!(Balances::locks(&1)[0].amount, 1700);
+        assert_eq!(Balances::free_balance(&1), 1800);
+        assert_eq!(Balances::free_balance(&2), 0);
+        assert_noop!(
+            CrustLocks::unlock_for(Origin::signed(2), 1),
+            DispatchError::Module {
+                index: 2,
+                error: 3,
+                message: Some("TimeIsNotEnough"),
+            }
+        );
+        assert_noop!(
+            CrustLocks::unlock_for(Origin::signed(1), 2),
+            DispatchError::Module {
+                index: 2,
+                error: 2,
+                message: Some("LockNotExist"),
+            }
+        );
+    });
+}
+
+#[test]
+fn transfer_lock_should_work() {
+    new_test_ext().execute_with(|| {
+        run_to_block(300);
+        assert_ok!(CrustLocks::set_unlock_from(Origin::root(), 1000));
+        CrustLocks::issue_and_set_lock(&1, &1800, CRU18);
+        CrustLocks::issue_and_set_lock(&3, &1800, CRU18);
+        let _ = Balances::deposit_creating(&1, 100);
+
+        run_to_block(2000);
+        assert_ok!(CrustLocks::unlock(Origin::signed(1)));
+        assert_noop!(
+            CrustLocks::transfer_lock(Origin::signed(1), 3),
+            DispatchError::Module {
+                index: 2,
+                error: 8,
+                message: Some("InvalidLockTarget"),
+            }
+        );
+        assert_noop!(
+            CrustLocks::transfer_lock(Origin::signed(1), 1),
+            DispatchError::Module {
+                index: 2,
+                error: 8,
+                message: Some("InvalidLockTarget"),
+            }
+        );
+
+        // The remaining locked amount is moved along with the lock
+        assert_ok!(CrustLocks::transfer_lock(Origin::signed(1), 2));
+        assert_eq!(<Locks<Test>>::contains_key(&1), false);
+        assert_eq!(Balances::locks(&1).len(), 0);
+        assert_eq!(Balances::free_balance(&1), 200);
+        assert_eq!(Balances::free_balance(&2), 1700);
+        assert_eq!(Balances::locks(&2)[0].amount, 1700);
+
+        // The unlock progress is kept
+        run_to_block(3000);
+        assert_ok!(CrustLocks::unlock(Origin::signed(2)));
+        assert_eq!(Balances::locks(&2)[0].amount, 1600);
+
+        // Governance can transfer the lock of any account
+        assert_noop!(
+            CrustLocks::force_transfer_lock(Origin::signed(2), 2, 4),
+            DispatchError::BadOrigin
+        );
+        assert_ok!(CrustLocks::force_transfer_lock(Origin::root(), 2, 4));
+        assert_eq!(Balances::free_balance(&4), 1600);
+        assert_eq!(Balances::locks(&4)[0].amount, 1600);
+    });
+}
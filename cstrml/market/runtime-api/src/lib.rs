@@ -0,0 +1,17 @@
+// Copyright (C) 2019-2021 Crust Network Technologies Ltd.
+// This file is part of Crust.
+
+//! Runtime API definition for the market module.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use sp_std::prelude::*;
+use primitives::{BlockNumber, MerkleRoot};
+
+sp_api::decl_runtime_apis! {
+    pub trait MarketApi {
+        /// Get the files expired in [from, to] which can be claimed via `calculate_reward`.
+        /// Return (cid, expired_at) pairs.
+        fn claimable_files(from: BlockNumber, to: BlockNumber) -> Vec<(MerkleRoot, BlockNumber)>;
+    }
+}
@@ -10,16 +10,19 @@ use codec::{Decode, Encode};
 use frame_support::{
     decl_event, decl_module, decl_storage, decl_error,
     dispatch::{DispatchResult, DispatchResultWithPostInfo}, ensure,
+    storage::{IterableStorageDoubleMap, generator::StorageMap, with_transaction},
     traits::{
         Currency, ReservableCurrency, Get, LockableCurrency, ExistenceRequirement,
         ExistenceRequirement::{AllowDeath, KeepAlive},
-        WithdrawReasons, Imbalance
+        WithdrawReasons, Imbalance, Randomness
     },
+    ReversibleStorageHasher,
     weights::{Weight, Pays}
 };
 use sp_std::{prelude::*, convert::TryInto, collections::btree_set::BTreeSet, collections::btree_map::BTreeMap};
 use frame_system::{self as system, ensure_signed, ensure_root};
-use sp_runtime::{SaturatedConversion, Perbill, ModuleId, traits::{Zero, CheckedMul, AccountIdConversion, Saturating}, DispatchError};
+use sp_runtime::{SaturatedConversion, Perbill, ModuleId, traits::{Zero, CheckedMul, AccountIdConversion, Saturating}, DispatchError, TransactionOutcome};
+use sp_io::hashing::blake2_256;
 
 #[cfg(feature = "std")]
 use serde::{Deserialize, Serialize};
@@ -38,7 +41,7 @@ pub mod benchmarking;
 use primitives::{
     constants::{market::*, swork::REPORT_SLOT}, traits::{
         BenefitInterface, MarketInterface, SworkerInterface, UsableCurrency
-    }, BlockNumber, MerkleRoot, ReportSlot, SworkerAnchor
+    }, BlockNumber, Hash, MerkleRoot, ReportSlot, SworkerAnchor, SpotCheckNonce
 };
 
 pub(crate) const LOG_TARGET: &'static str = "market";
@@ -47,6 +50,20 @@ const MAX_REPLICAS: usize = 200;
 // Any ratio change should re-design the `calculate_reward_amount` as well
 const REWARD_PERSON: u32 = 4;
 const MAX_CALCULATE_SPOWER_FILES_COUNT: usize = 300;
+// The maximum files count to be added into the expiry index in one call
+const MAX_INDEX_FILES_COUNT: usize = 300;
+// The maximum files count to be auto renewed in one block
+const MAX_AUTO_RENEW_FILES_COUNT: usize = 50;
+// The maximum expiry blocks to be scanned by auto renew in one block
+const MAX_AUTO_RENEW_SCAN_BLOCKS: u32 = 100;
+// The maximum files count to be queued in the renew queue of one block
+const MAX_RENEW_QUEUE_LENGTH: usize = 200;
+// The maximum following blocks a full renew queue could spill into
+const MAX_RENEW_QUEUE_SPILL_BLOCKS: u32 = 10;
+// The maximum files count to be scheduled for renewal in one call
+const MAX_SCHEDULE_RENEWAL_FILES_COUNT: usize = 300;
+// The maximum replicas count to be spot checked in one report slot
+const MAX_SPOT_CHECK_SAMPLE_SIZE: u32 = 100;
 
 #[macro_export]
 macro_rules! log {
@@ -157,6 +174,12 @@ impl<T: Config> MarketInterface<<T as system::Config>::AccountId, BalanceOf<T>>
         }
         staking_amount
     }
+
+    /// The market staking pot which would be distributed at the end of the active era
+    fn staking_pot_balance() -> BalanceOf<T> {
+        // Leave the minimum balance to keep this account live.
+        T::Currency::free_balance(&Self::staking_pot()).saturating_sub(T::Currency::minimum_balance())
+    }
     
     fn update_files_spower(changed_files: &Vec<(MerkleRoot, u64, Vec<(T::AccountId, T::AccountId, SworkerAnchor, Option<BlockNumber>)>)>) {
         for (cid, new_spower, changed_replicas) in changed_files {
@@ -179,6 +202,20 @@ impl<T: Config> MarketInterface<<T as system::Config>::AccountId, BalanceOf<T>>
             }
         }
     }
+
+    fn spot_check_nonce(anchor: &SworkerAnchor, cid: &MerkleRoot, report_slot: ReportSlot) -> Option<SpotCheckNonce> {
+        <SpotChecks<T>>::get(report_slot, (cid.clone(), anchor.clone())).map(|(_, nonce)| nonce)
+    }
+
+    fn pass_spot_check(anchor: &SworkerAnchor, cid: &MerkleRoot, report_slot: ReportSlot) -> bool {
+        let key = (cid.clone(), anchor.clone());
+        if <SpotChecks<T>>::contains_key(report_slot, &key) {
+            <SpotChecks<T>>::remove(report_slot, &key);
+            Self::deposit_event(RawEvent::SpotCheckPassed(cid.clone(), anchor.clone()));
+            return true;
+        }
+        false
+    }
 }
 
 /// The module's configuration trait.
@@ -243,6 +280,9 @@ pub trait Config: system::Config {
     /// Maximum file size
     type MaximumFileSize: Get<u64>;
 
+    /// Something that provides randomness in the runtime, used for drawing spot checks.
+    type Randomness: Randomness<Self::Hash>;
+
     /// Weight information for extrinsics in this pallet.
     type WeightInfo: WeightInfo;
 }
@@ -331,17 +371,45 @@ decl_storage! {
         /// The global market switch to enable place storage order service
         pub EnableMarket get(fn enable_market): bool = false;
 
+        /// The switch to charge the file base fee through the benefit fee reduction,
+        /// the reduced part is issued into the reserved pot and deducted from the staking payout of the era
+        pub BaseFeeReductionEnabled get(fn base_fee_reduction_enabled): bool = false;
+
+        /// The switch to charge the prepaid amount through the benefit fee reduction,
+        /// the reduced part is issued into the storage pot and deducted from the staking payout of the era
+        pub PrepaidReductionEnabled get(fn prepaid_reduction_enabled): bool = false;
+
         /// The sPower will become valid after this period, default is 3 months
         pub SpowerReadyPeriod get(fn spower_ready_period): BlockNumber = 1_296_000;
 
-        /// The crust-spower service account
-        pub SpowerSuperior get(fn spower_superior): Option<T::AccountId>;
-
         /// The last replicas update block
         pub LastReplicasUpdateBlock get (fn last_replicas_update_block): BlockNumber = 0;
 
         /// The last spower calculate block
         pub LastSpowerCalculateBlock get (fn last_spower_calculate_block): BlockNumber = 0;
+
+        /// Files with prepaid value waiting to be auto renewed, keyed by the block they expire at
+        pub RenewQueue get(fn renew_queue):
+        map hasher(twox_64_concat) BlockNumber => Vec<MerkleRoot>;
+
+        /// The next expiry block to be processed by auto renew
+        pub NextRenewBlock get(fn next_renew_block): BlockNumber = 0;
+
+        /// The expiry index of live files, the first key is the report slot which the file expires in,
+        /// the second key is the cid and the value is the expired_at of the file
+        pub FilesExpiryIndex get(fn files_expiry_index):
+        double_map hasher(twox_64_concat) ReportSlot, hasher(twox_64_concat) MerkleRoot => Option<BlockNumber>;
+
+        /// The replicas count to be spot checked in each report slot, 0 means disabled
+        pub SpotCheckSampleSize get(fn spot_check_sample_size): u32 = 0;
+
+        /// The spot checks drawn in each report slot, the first key is the report slot,
+        /// the second key is (cid, sworker anchor) and the value is the owner of the replica
+        /// with the random nonce of this spot check.
+        /// The sWorker should answer it with a TEE signed proof over the nonce in the next report slot,
+        /// otherwise the replica would be deleted.
+        pub SpotChecks get(fn spot_checks):
+        double_map hasher(twox_64_concat) ReportSlot, hasher(twox_64_concat) (MerkleRoot, SworkerAnchor) => Option<(T::AccountId, SpotCheckNonce)>;
     }
     add_extra_genesis {
 		build(|_config| {
@@ -375,18 +443,22 @@ decl_error! {
         PlaceOrderNotAvailable,
         /// The file does not exist. Please check the cid again.
         FileNotExist,
-        /// The spower superior account is not set. Please call the set_spower_superior extrinsic first.
-        SpowerSuperiorNotSet,
-        /// The caller account is not the spower superior account. Please check the caller account again.
-        IllegalSpowerSuperior,
         /// The files count exceeds limit. Please calculate less files.
         ExceedCalculateSpowerFilesLimit,
+        /// The files count exceeds limit. Please index less files.
+        ExceedIndexFilesLimit,
         /// The file fee adjust interval is invalid.
         FileFeeAdjustIntervalInvalid,
         /// The max fee should be greater than the min fee.
         MaxFeeLessThanMinFee,
         /// The file base fee increase threshold should be less than the decrease threshold.
         BaseFeeIncreaseThresholdLargerThanDecreaseThreshold,
+        /// The spot check sample size exceeds limit. Please set a smaller one.
+        ExceedSpotCheckSampleSizeLimit,
+        /// The replicas don't match the replicas queued by report works. Please replay the reported files again.
+        ReplicasCommitmentMismatch,
+        /// The files count exceeds limit. Please schedule less files.
+        ExceedScheduleRenewalFilesLimit,
     }
 }
 
@@ -453,7 +525,13 @@ decl_module! {
                 add_db_reads_writes(8, 4);
             }
             add_db_reads_writes(2, 0);
-            
+
+            consumed_weight += Self::auto_renew_files(now);
+
+            if ((now + SPOT_CHECK_OFFSET) % REPORT_SLOT as u32).is_zero() {
+                consumed_weight += Self::spot_check(now);
+            }
+
             consumed_weight
         }
 
@@ -488,15 +566,26 @@ decl_module! {
             // 4. Check client can afford the sorder
             ensure!(T::Currency::usable_balance(&who) >= file_base_fee + amount + tips, Error::<T>::InsufficientCurrency);
 
-            // 5. Split into reserved, storage and staking account
+            // 5. Charge the base fee with the benefit fee reduction
+            // The reduced part is issued into the reserved pot, it's bounded by the total fee reduction quota of the era
+            // and deducted from the staking payout at the end of the era, so the net issuance is unchanged
+            let file_base_fee = if Self::base_fee_reduction_enabled() {
+                let imbalance = T::BenefitInterface::maybe_reduce_fee(&who, file_base_fee, WithdrawReasons::TRANSFER)?;
+                T::Currency::resolve_creating(&Self::reserved_pot(), imbalance);
+                Zero::zero()
+            } else {
+                file_base_fee
+            };
+
+            // 6. Split into reserved, storage and staking account
             let amount = Self::split_into_reserved_and_storage_and_staking_pot(&who, amount.clone(), file_base_fee, tips, AllowDeath)?;
 
             let curr_bn = Self::get_current_block_number();
 
-            // 6. three scenarios: new file, extend time(refresh time)
+            // 7. three scenarios: new file, extend time(refresh time)
             Self::upsert_new_file_info(&cid, &amount, &curr_bn, charged_file_size);
 
-            // 7. Update new order status.
+            // 8. Update new order status.
             HasNewOrder::put(true);
             OrdersCount::mutate(|count| {*count = count.saturating_add(1)});
 
@@ -518,8 +607,16 @@ decl_module! {
             ensure!(T::Currency::usable_balance(&who) >= amount, Error::<T>::InsufficientCurrency);
 
             if let Some(mut file_info) = Self::filesv2(&cid) {
-                T::Currency::transfer(&who, &Self::storage_pot(), amount.clone(), AllowDeath)?;
+                if Self::prepaid_reduction_enabled() {
+                    // The prepaid is fully credited while part of it may be covered by the benefit fee reduction,
+                    // the covered part is issued into the storage pot and deducted from the staking payout at the end of the era
+                    let imbalance = T::BenefitInterface::maybe_reduce_fee(&who, amount.clone(), WithdrawReasons::TRANSFER)?;
+                    T::Currency::resolve_creating(&Self::storage_pot(), imbalance);
+                } else {
+                    T::Currency::transfer(&who, &Self::storage_pot(), amount.clone(), AllowDeath)?;
+                }
                 file_info.prepaid += amount;
+                Self::maybe_schedule_renewal(&cid, &file_info);
                 <FilesV2<T>>::insert(&cid, file_info);
             } else {
                 Err(Error::<T>::FileNotExist)?
@@ -530,23 +627,16 @@ decl_module! {
             Ok(())
         }
 
-        /// Set the crust-spower service superior account
-        #[weight = 1000]
-        pub fn set_spower_superior(origin, superior: T::AccountId) -> DispatchResult {
-            ensure_root(origin)?;
-
-            SpowerSuperior::<T>::put(superior.clone());
-
-            Self::deposit_event(RawEvent::SetSpowerSuperiorSuccess(superior));
-            Ok(())
-        }
-
-        /// Update file replicas from crust-spower offchain service
+        /// Update file replicas from crust-spower offchain service operators
+        /// It's only applied when enough operators submit the same replicas for the same last processed block,
+        /// and the operator would be slashed if it submits different replicas for the same last processed block.
         /// Emits `ReplicasUpdateSuccess` event if the call is success
         /// # params
         ///  - file_infos_Map: file replicas info map with the data structure as belowed:
         ///     Map<(CID, file_size, Vec<(reporter, owner, sworker_anchor, report_slot, report_block, valid_at, is_added))>>
         ///     The key is the file CID, and the value is a vector of file replicas info.
+        ///     The replicas must be in the order queued by report works, a CID can appear more than once to keep the order.
+        ///  - last_processed_block_wrs: the last block with replicas in this batch, or any processed block if the batch is empty
         ///     PS: We're not using the ReplicaToUpdate type in the argument directly, because this would fail traditional apps
         ///         which would need to decode extrinsics, which will then error out with 'Unable to decode on ReplicaToUpdate'.
         ///         So we directly use the raw types and tuple here as the argument
@@ -557,12 +647,18 @@ decl_module! {
             last_processed_block_wrs: BlockNumber
         ) -> DispatchResultWithPostInfo {
             let caller = ensure_signed(origin)?;
-            let maybe_superior = Self::spower_superior();
 
-            // 1. Check if superior exist
-            ensure!(maybe_superior.is_some(), Error::<T>::SpowerSuperiorNotSet);
-            // 2. Check if caller is superior
-            ensure!(Some(&caller) == maybe_superior.as_ref(), Error::<T>::IllegalSpowerSuperior);
+            // 1. Check if the replicas match the replicas queued by sWorkers' report works
+            ensure!(
+                T::SworkerInterface::check_replicas_commitment(last_processed_block_wrs, &file_infos_map),
+                Error::<T>::ReplicasCommitmentMismatch
+            );
+
+            // 2. Attest the batch, skip if not enough spower operators attest the same one
+            let batch_hash: Hash = (b"update_replicas", &file_infos_map, last_processed_block_wrs).using_encoded(blake2_256).into();
+            if !T::SworkerInterface::attest_spower_batch(&caller, b"update_replicas", last_processed_block_wrs, batch_hash)? {
+                return Ok(Pays::No.into());
+            }
 
             // 3. Internal update replicas
             let mut file_infos_map_ex: Vec<(MerkleRoot, u64, Vec<ReplicaToUpdateOf<T>>)> = vec![];
@@ -605,38 +701,44 @@ decl_module! {
         }
 
         /// Calculate and update spower for a list of files
+        /// It's only applied when enough operators submit the same cids for the same batch block.
         /// Emits `CalculateSpowerSuccess` event if the call is success
         /// # params
         ///  - cids: file CID list
+        ///  - batch_block: the block this batch is built at, should be larger than the one of the last applied batch
         #[weight = T::WeightInfo::calcuate_spowers(cids.len() as u32)]
         pub fn calculate_spowers(
             origin,
-            cids: Vec<MerkleRoot>
+            cids: Vec<MerkleRoot>,
+            batch_block: BlockNumber
         ) -> DispatchResultWithPostInfo {
             let caller = ensure_signed(origin)?;
-            let maybe_superior = Self::spower_superior();
 
-            // 1. Check if superior exist
-            ensure!(maybe_superior.is_some(), Error::<T>::SpowerSuperiorNotSet);
-            // 2. Check if caller is superior
-            ensure!(Some(&caller) == maybe_superior.as_ref(), Error::<T>::IllegalSpowerSuperior);
-
-            // 3. Check if the cid list exceeds the limit
+            // 1. Check if the cid list exceeds the limit
             if cids.len() > MAX_CALCULATE_SPOWER_FILES_COUNT {
                 Err(Error::<T>::ExceedCalculateSpowerFilesLimit)?
             }
 
-            // 4. Calculate and update spower
+            // 2. Attest the batch, only apply it when enough spower operators attest the same one.
+            // The cids are sorted, so the same cids in different orders are the same batch.
+            let mut cids = cids;
+            cids.sort();
+            let batch_hash: Hash = (b"calculate_spowers", &cids).using_encoded(blake2_256).into();
+            if !T::SworkerInterface::attest_spower_batch(&caller, b"calculate_spowers", batch_block, batch_hash)? {
+                return Ok(Pays::No.into());
+            }
+
+            // 3. Calculate and update spower
             let curr_bn = Self::get_current_block_number();
             let (sworker_changed_spower_map, updated_files_count) = Self::internal_calculate_spowers(&cids, curr_bn);
 
-            // 5. Update the changed spower of sworkers
+            // 4. Update the changed spower of sworkers
             T::SworkerInterface::update_sworkers_changed_spower(&sworker_changed_spower_map);
 
-            // 6. Update the LastSpowerCalculateBlock
+            // 5. Update the LastSpowerCalculateBlock
             LastSpowerCalculateBlock::put(curr_bn);
 
-            // 7. Emit the event if success
+            // 6. Emit the event if success
             Self::deposit_event(RawEvent::CalculateSpowersSuccess(caller, 
                 curr_bn, 
                 sworker_changed_spower_map.len() as u32, 
@@ -647,22 +749,62 @@ decl_module! {
         }
 
         /// Set spower ready period
+        /// It's only applied when enough operators submit the same period for the same batch block.
         #[weight = 1_000_000]
-        pub fn set_spower_ready_period(origin, ready_period: BlockNumber) -> DispatchResult {
+        pub fn set_spower_ready_period(origin, ready_period: BlockNumber, batch_block: BlockNumber) -> DispatchResult {
             let caller = ensure_signed(origin)?;
-            let maybe_superior = Self::spower_superior();
 
-            // 1. Check if superior exist
-            ensure!(maybe_superior.is_some(), Error::<T>::SpowerSuperiorNotSet);
-            // 2. Check if caller is superior
-            ensure!(Some(&caller) == maybe_superior.as_ref(), Error::<T>::IllegalSpowerSuperior);
+            // 1. Attest the batch, only apply it when enough spower operators attest the same one
+            let batch_hash: Hash = (b"set_spower_ready_period", ready_period).using_encoded(blake2_256).into();
+            if !T::SworkerInterface::attest_spower_batch(&caller, b"set_spower_ready_period", batch_block, batch_hash)? {
+                return Ok(());
+            }
 
+            // 2. Set the spower ready period
             SpowerReadyPeriod::put(ready_period);
 
             Self::deposit_event(RawEvent::SetSpowerReadyPeriodSuccess(ready_period));
             Ok(())
         }
 
+        /// Add the live files which were placed before the expiry index into it
+        /// # params
+        ///  - cids: file CID list
+        #[weight = T::DbWeight::get().reads_writes(2, 1).saturating_mul(cids.len() as Weight)]
+        pub fn index_files_expiry(
+            origin,
+            cids: Vec<MerkleRoot>
+        ) -> DispatchResult {
+            let _ = ensure_signed(origin)?;
+            ensure!(cids.len() <= MAX_INDEX_FILES_COUNT, Error::<T>::ExceedIndexFilesLimit);
+
+            for cid in cids.iter() {
+                if let Some(file_info) = Self::filesv2(cid) {
+                    Self::update_expiry_index(cid, 0, file_info.expired_at);
+                }
+            }
+            Ok(())
+        }
+
+        /// Schedule the files which got prepaid before the auto renewal into the renew queue
+        /// # params
+        ///  - cids: file CID list
+        #[weight = T::DbWeight::get().reads_writes(1 + MAX_RENEW_QUEUE_SPILL_BLOCKS as Weight, 1).saturating_mul(cids.len() as Weight)]
+        pub fn schedule_files_renewal(
+            origin,
+            cids: Vec<MerkleRoot>
+        ) -> DispatchResult {
+            let _ = ensure_signed(origin)?;
+            ensure!(cids.len() <= MAX_SCHEDULE_RENEWAL_FILES_COUNT, Error::<T>::ExceedScheduleRenewalFilesLimit);
+
+            for cid in cids.iter() {
+                if let Some(file_info) = Self::filesv2(cid) {
+                    Self::maybe_schedule_renewal(cid, &file_info);
+                }
+            }
+            Ok(())
+        }
+
         /// Calculate the reward for a file
         #[weight = T::WeightInfo::calculate_reward()]
         pub fn calculate_reward(
@@ -712,6 +854,9 @@ decl_module! {
             // 3. Set the reward to zero and push it back
             T::BenefitInterface::update_reward(&merchant, Zero::zero());
 
+            // 4. Add the reward into benefit funds if the merchant opts in
+            T::BenefitInterface::maybe_compound_reward(&merchant, reward);
+
             Self::deposit_event(RawEvent::RewardMerchantSuccess(merchant));
             Ok(())
         }
@@ -748,6 +893,27 @@ decl_module! {
             Ok(())
         }
 
+        /// Set whether the file base fee and the prepaid amount could be reduced by the benefit fee reduction quota
+        ///
+        /// NOTE: Like the transaction fee, the reduced part is issued in advance and counted as the used fee
+        /// reduction quota, which is deducted from the staking payout of the era, so the net issuance is unchanged.
+        ///
+        /// The dispatch origin for this call must be _Root_.
+        #[weight = 1000]
+        pub fn set_fee_reduction(
+            origin,
+            base_fee_enabled: bool,
+            prepaid_enabled: bool
+        ) -> DispatchResult {
+            let _ = ensure_root(origin)?;
+
+            BaseFeeReductionEnabled::put(base_fee_enabled);
+            PrepaidReductionEnabled::put(prepaid_enabled);
+
+            Self::deposit_event(RawEvent::SetFeeReductionSuccess(base_fee_enabled, prepaid_enabled));
+            Ok(())
+        }
+
         /// Set the file byte fee
         ///
         /// The dispatch origin for this call must be _Root_.
@@ -948,6 +1114,22 @@ decl_module! {
             Ok(())
         }
        
+        /// Set the replicas count to be spot checked in each report slot, 0 means disabled.
+        ///
+        /// The dispatch origin for this call must be _Root_.
+        #[weight = 1000]
+        pub fn set_spot_check_sample_size(
+            origin,
+            sample_size: u32
+        ) -> DispatchResult {
+            let _ = ensure_root(origin)?;
+            ensure!(sample_size <= MAX_SPOT_CHECK_SAMPLE_SIZE, Error::<T>::ExceedSpotCheckSampleSizeLimit);
+            SpotCheckSampleSize::put(sample_size);
+
+            Self::deposit_event(RawEvent::SetSpotCheckSampleSizeSuccess(sample_size));
+            Ok(())
+        }
+
         /// Set the file keys count fee adjust threshold, unit in number of on-chain files.
         /// The dispatch origin for this call must be _Root_.
         #[weight = 1000]
@@ -1086,7 +1268,7 @@ impl<T: Config> Module<T> {
                 }
 
                 // 2. Add replica data to storage
-                let is_replica_added = Self::upsert_replica(&mut file_info, &reporter, &owner, &sworker_anchor, *valid_at);
+                let is_replica_added = Self::upsert_replica(&mut file_info, &cid, &reporter, &owner, &sworker_anchor, *valid_at);
                 // If the replica is not added (due to exceed MAX_REPLICA, or same owner reported), just ignore this replica
                 if is_replica_added {
                     // Update related sworker's changed spower
@@ -1142,6 +1324,7 @@ impl<T: Config> Module<T> {
                     // This should not have error => discard the result
                     let _ = T::Currency::transfer(&Self::storage_pot(), &Self::reserved_pot(), total_amount, KeepAlive);
                 }
+                Self::update_expiry_index(cid, file_info.expired_at, 0);
                 <FilesV2<T>>::remove(cid);
                 FileKeysCount::mutate(|count| *count = count.saturating_sub(1));
                 OrdersCount::mutate(|count| {*count = count.saturating_sub(1)});
@@ -1155,6 +1338,7 @@ impl<T: Config> Module<T> {
     }
 
     fn upsert_replica(file_info: &mut FileInfoV2<T::AccountId, BalanceOf<T>>, 
+                      cid: &MerkleRoot,
                       who: &<T as system::Config>::AccountId,
                       owner: &<T as system::Config>::AccountId,
                       anchor: &SworkerAnchor,
@@ -1194,6 +1378,8 @@ impl<T: Config> Module<T> {
         if file_info.expired_at == 0 {
             file_info.calculated_at = curr_bn;
             file_info.expired_at = curr_bn + T::FileDuration::get();
+            Self::update_expiry_index(cid, 0, file_info.expired_at);
+            Self::maybe_schedule_renewal(cid, file_info);
         }
 
         is_replica_added
@@ -1295,6 +1481,7 @@ impl<T: Config> Module<T> {
                 let _ = Self::update_replicas_spower(&mut file_info, None);
 
                 // Remove files
+                Self::update_expiry_index(cid, file_info.expired_at, 0);
                 <FilesV2<T>>::remove(&cid);
                 FileKeysCount::mutate(|count| *count = count.saturating_sub(1));
                 Self::deposit_event(RawEvent::FileClosed(cid.clone()));
@@ -1314,6 +1501,7 @@ impl<T: Config> Module<T> {
             let total_amount = file_base_fee.clone() + file_amount.clone();
             // 2. Check if prepaid pool can afford the price
             if file_info.prepaid >= total_amount {
+                let prev_expired_at = file_info.expired_at;
                 file_info.prepaid = file_info.prepaid.saturating_sub(total_amount.clone());
                 // 3. Split into reserved, storage and staking account
                 let file_amount = Self::split_into_reserved_and_storage_and_staking_pot(&Self::storage_pot(), file_amount.clone(), file_base_fee, Zero::zero(), KeepAlive)?;
@@ -1328,7 +1516,9 @@ impl<T: Config> Module<T> {
                     // Refresh the file to the new file
                     file_info.expired_at = curr_bn + T::FileDuration::get();
                     file_info.calculated_at = curr_bn;
+                    Self::maybe_schedule_renewal(cid, &file_info);
                 }
+                Self::update_expiry_index(cid, prev_expired_at, file_info.expired_at);
                 <FilesV2<T>>::insert(cid, file_info);
 
                 // 5. Update new order status.
@@ -1340,6 +1530,215 @@ impl<T: Config> Module<T> {
         Ok(())
     }
 
+    /// Move the file from the old expiry slot into the new one, 0 means not in the index
+    fn update_expiry_index(cid: &MerkleRoot, prev_expired_at: BlockNumber, expired_at: BlockNumber) {
+        if !prev_expired_at.is_zero() {
+            <FilesExpiryIndex>::remove(Self::convert_bn_to_rs(prev_expired_at), cid);
+        }
+        if !expired_at.is_zero() {
+            <FilesExpiryIndex>::insert(Self::convert_bn_to_rs(expired_at), cid, expired_at);
+        }
+    }
+
+    fn convert_bn_to_rs(bn: BlockNumber) -> ReportSlot {
+        let report_index = bn as ReportSlot / REPORT_SLOT;
+        report_index * REPORT_SLOT
+    }
+
+    /// Get the files expired in [from, to] which can be claimed via `calculate_reward` right now.
+    /// Used by the runtime api.
+    pub fn claimable_files(from: BlockNumber, to: BlockNumber) -> Vec<(MerkleRoot, BlockNumber)> {
+        let to = to.min(Self::get_current_block_number());
+        let mut files: Vec<(MerkleRoot, BlockNumber)> = vec![];
+        if from > to {
+            return files;
+        }
+        let mut slot = Self::convert_bn_to_rs(from);
+        while slot <= to as ReportSlot {
+            for (cid, expired_at) in <FilesExpiryIndex>::iter_prefix(slot) {
+                if expired_at >= from && expired_at <= to {
+                    files.push((cid, expired_at));
+                }
+            }
+            slot += REPORT_SLOT;
+        }
+        files.sort_by(|a, b| a.1.cmp(&b.1));
+        files
+    }
+
+    /// Settle the spot checks of the last report slot and draw new ones for the current report slot
+    fn spot_check(now: BlockNumber) -> Weight {
+        let mut consumed_weight: Weight = 0;
+        let curr_slot = Self::convert_bn_to_rs(now);
+        // 1. Delete the replicas which didn't answer the spot checks of the last report slot
+        if let Some(prev_slot) = curr_slot.checked_sub(REPORT_SLOT) {
+            consumed_weight = consumed_weight.saturating_add(Self::settle_spot_checks(prev_slot));
+        }
+        // 2. Draw new spot checks
+        let sample_size = Self::spot_check_sample_size();
+        if !sample_size.is_zero() {
+            consumed_weight = consumed_weight.saturating_add(Self::draw_spot_checks(now, curr_slot, sample_size));
+        }
+        consumed_weight.saturating_add(T::DbWeight::get().reads(1))
+    }
+
+    fn settle_spot_checks(report_slot: ReportSlot) -> Weight {
+        let mut settled_count: u64 = 0;
+        let mut sworker_changed_spower_map: BTreeMap<SworkerAnchor, i64> = BTreeMap::new();
+        for ((cid, anchor), (owner, _)) in <SpotChecks<T>>::drain_prefix(report_slot) {
+            settled_count += 1;
+            if let Some(mut file_info) = Self::filesv2(&cid) {
+                // Only delete the replica which is still stored by the checked sWorker
+                let maybe_who = file_info.replicas.get(&owner)
+                    .filter(|replica| replica.anchor == anchor)
+                    .map(|replica| replica.who.clone());
+                if let Some(who) = maybe_who {
+                    let (is_replica_deleted, to_delete_spower) = Self::delete_replica(&mut file_info, &who, &owner, &anchor);
+                    if is_replica_deleted {
+                        if let Some(changed_spower) = sworker_changed_spower_map.get_mut(&anchor) {
+                            *changed_spower -= to_delete_spower as i64;
+                        } else {
+                            sworker_changed_spower_map.insert(anchor.clone(), 0-(to_delete_spower as i64));
+                        }
+                        <FilesV2<T>>::insert(&cid, file_info);
+                        Self::deposit_event(RawEvent::SpotCheckFailed(cid, anchor));
+                    }
+                }
+            }
+        }
+        T::SworkerInterface::update_sworkers_changed_spower(&sworker_changed_spower_map);
+        T::DbWeight::get().reads_writes(3, 3).saturating_mul(settled_count)
+    }
+
+    /// Draw (cid, anchor) pairs with the chain randomness.
+    /// Each sample jumps to a random position of FilesV2 and takes the next file,
+    /// then takes a random replica of this file.
+    fn draw_spot_checks(now: BlockNumber, report_slot: ReportSlot, sample_size: u32) -> Weight {
+        let prefix = <FilesV2<T>>::prefix_hash();
+        let random_seed = T::Randomness::random(&(b"spot_check", now).encode());
+        for index in 0..sample_size {
+            let seed = (random_seed, index).using_encoded(blake2_256);
+            let start_key = [&prefix[..], &seed[..8]].concat();
+            // Wrap around to the first file if reach the end
+            let maybe_next = sp_io::storage::next_key(&start_key)
+                .filter(|key| key.starts_with(&prefix))
+                .or_else(|| sp_io::storage::next_key(&prefix).filter(|key| key.starts_with(&prefix)));
+            if let Some(next_key) = maybe_next {
+                let mut key_material = <FilesV2<T> as StorageMap<MerkleRoot, FileInfoV2<T::AccountId, BalanceOf<T>>>>::Hasher::reverse(&next_key[prefix.len()..]);
+                if let Ok(cid) = MerkleRoot::decode(&mut key_material) {
+                    if let Some(file_info) = Self::filesv2(&cid) {
+                        if file_info.replicas.is_empty() {
+                            continue;
+                        }
+                        let replica_index = u32::from_le_bytes([seed[8], seed[9], seed[10], seed[11]]) as usize % file_info.replicas.len();
+                        if let Some((owner, replica)) = file_info.replicas.iter().nth(replica_index) {
+                            let nonce = (seed, &cid, &replica.anchor).using_encoded(blake2_256);
+                            <SpotChecks<T>>::insert(report_slot, (cid.clone(), replica.anchor.clone()), (owner.clone(), nonce));
+                            Self::deposit_event(RawEvent::SpotCheckDrawn(cid, replica.anchor.clone(), report_slot, nonce));
+                        }
+                    }
+                }
+            }
+        }
+        T::DbWeight::get().reads_writes(2, 1).saturating_mul(sample_size as Weight)
+    }
+
+    /// Put the file into the renew queue if it is live and has prepaid value.
+    /// If the queue of the expiry block is full, the file would spill into the following blocks,
+    /// and it would be left to the liquidators if all of them are full.
+    fn maybe_schedule_renewal(cid: &MerkleRoot, file_info: &FileInfoV2<T::AccountId, BalanceOf<T>>) {
+        if file_info.expired_at.is_zero() || file_info.prepaid.is_zero() {
+            return;
+        }
+        // The current block may have been processed already, so schedule it to the next block at least
+        let renew_at = file_info.expired_at.max(Self::get_current_block_number() + 1);
+        for offset in 0..MAX_RENEW_QUEUE_SPILL_BLOCKS {
+            let mut cids = <RenewQueue>::get(renew_at + offset);
+            if cids.contains(cid) {
+                return;
+            }
+            if cids.len() < MAX_RENEW_QUEUE_LENGTH {
+                cids.push(cid.clone());
+                <RenewQueue>::insert(renew_at + offset, cids);
+                return;
+            }
+        }
+        log!(
+            warn,
+            "🏢 Renew queue is full from {:?}, file {:?} would not be auto renewed",
+            renew_at,
+            cid
+        );
+    }
+
+    /// Renew the expired files in the renew queue with their prepaid value.
+    /// At most MAX_AUTO_RENEW_FILES_COUNT files would be renewed in one block,
+    /// the rest would be left in the queue and handled in the following blocks.
+    fn auto_renew_files(now: BlockNumber) -> Weight {
+        let mut consumed_weight: Weight = 0;
+        let mut renew_at = Self::next_renew_block();
+        // Nothing could be scheduled before the first run
+        if renew_at.is_zero() {
+            renew_at = now;
+        }
+        let mut scanned_blocks: u32 = 0;
+        let mut renewed_count: usize = 0;
+        while renew_at <= now && scanned_blocks < MAX_AUTO_RENEW_SCAN_BLOCKS {
+            let mut cids = <RenewQueue>::get(renew_at);
+            consumed_weight = consumed_weight.saturating_add(T::DbWeight::get().reads(1));
+            let left_count = MAX_AUTO_RENEW_FILES_COUNT.saturating_sub(renewed_count);
+            let rest = if cids.len() > left_count { cids.split_off(left_count) } else { vec![] };
+            for cid in cids.iter() {
+                Self::auto_renew_file(cid, now);
+                renewed_count += 1;
+            }
+            consumed_weight = consumed_weight.saturating_add(T::DbWeight::get().reads_writes(8, 6).saturating_mul(cids.len() as Weight));
+            if !rest.is_empty() {
+                // Run out of the limit, continue from here in the next block
+                <RenewQueue>::insert(renew_at, rest);
+                consumed_weight = consumed_weight.saturating_add(T::DbWeight::get().writes(1));
+                break;
+            }
+            <RenewQueue>::remove(renew_at);
+            renew_at += 1;
+            scanned_blocks += 1;
+        }
+        NextRenewBlock::put(renew_at);
+        consumed_weight.saturating_add(T::DbWeight::get().reads_writes(1, 1))
+    }
+
+    fn auto_renew_file(cid: &MerkleRoot, curr_bn: BlockNumber) {
+        if let Some(mut file_info) = Self::filesv2(cid) {
+            // The file is pending or has been refreshed by someone else, skip it
+            if file_info.expired_at.is_zero() || file_info.expired_at > curr_bn {
+                return;
+            }
+            // Leave it to the liquidators if the prepaid cannot afford the renewal
+            let (file_base_fee, file_amount) = Self::get_file_fee(file_info.file_size);
+            if file_info.prepaid < file_base_fee.saturating_add(file_amount) {
+                return;
+            }
+            // Nothing would be rolled back in on_initialize, so revert all changes if it fails halfway
+            let result = with_transaction(|| {
+                // No liquidator here, the remaining amount would be kept in the file
+                file_info.calculated_at = file_info.expired_at;
+                <FilesV2<T>>::insert(cid, file_info);
+                match Self::try_to_renew_file(cid, curr_bn, &Self::storage_pot()) {
+                    Ok(()) => TransactionOutcome::Commit(Ok(())),
+                    Err(e) => TransactionOutcome::Rollback(Err(e))
+                }
+            });
+            if let Err(e) = result {
+                log!(
+                    warn,
+                    "🏢 Auto renew file {:?} failed with {:?}",
+                    cid,
+                    e
+                );
+            }
+        }
+    }
+
     fn maybe_reward_liquidator(cid: &MerkleRoot, curr_bn: BlockNumber, liquidator: &T::AccountId) -> DispatchResult {
         if let Some(mut file_info) = Self::filesv2(cid) {
             if curr_bn >= file_info.expired_at {
@@ -1357,6 +1756,7 @@ impl<T: Config> Module<T> {
     fn upsert_new_file_info(cid: &MerkleRoot, amount: &BalanceOf<T>, curr_bn: &BlockNumber, file_size: u64) {
         // Extend expired_at
         if let Some(mut file_info) = Self::filesv2(cid) {
+            let prev_expired_at = file_info.expired_at;
             // expired_at > calculated_at => file is ongoing.
             // expired_at == calculated_at => file is ready to be closed(wait to be refreshed).
             // expired_at < calculated_at => file is not live yet. This situation only happen for new file.
@@ -1372,7 +1772,9 @@ impl<T: Config> Module<T> {
             } else {
                 // Refresh the file to be a new file
                 file_info.expired_at = curr_bn + T::FileDuration::get();
+                Self::maybe_schedule_renewal(cid, &file_info);
             }
+            Self::update_expiry_index(cid, prev_expired_at, file_info.expired_at);
 
             file_info.amount += amount.clone();
             <FilesV2<T>>::insert(cid, file_info);
@@ -1533,9 +1935,7 @@ impl<T: Config> Module<T> {
         let staking_amount = T::StakingRatio::get() * value;
         let storage_amount = T::StorageRatio::get() * value;
 
-        // Calculate the discount for the total amount
-        // discount_amount = total_amount * min(market_funds_ratio, 0.1)
-        // reserved_amount = total_amount - staking_amount - storage_amount - discount_amount
+        // reserved_amount = total_amount - staking_amount - storage_amount
         let total_amount = value.saturating_add(base_fee);
         let reserved_amount = total_amount.saturating_sub(staking_amount).saturating_sub(storage_amount);
 
@@ -1548,12 +1948,6 @@ impl<T: Config> Module<T> {
         Ok(storage_amount)
     }
 
-    // discount feature is not implemented yet, comment out first to remove the build warning
-    // fn get_discount_ratio(who: &T::AccountId) -> Perbill {
-    //     let discount_max_ratio = Perbill::one().saturating_sub(T::StakingRatio::get()).saturating_sub(T::StorageRatio::get());
-    //     T::BenefitInterface::get_market_funds_ratio(who).min(discount_max_ratio)
-    // }
-
 
     fn get_current_block_number() -> BlockNumber {
         let current_block_number = <system::Module<T>>::block_number();
@@ -1670,8 +2064,9 @@ decl_event!(
         SetEnableMarketSuccess(bool),
         /// Set the file base fee success.
         SetBaseFeeSuccess(Balance),
-        /// Set the crust-spower service superior account.
-        SetSpowerSuperiorSuccess(AccountId),
+        /// Set the fee reduction switches success.
+        /// The first item is for the file base fee and the second item is for the prepaid amount.
+        SetFeeReductionSuccess(bool, bool),
         /// Update replicas success
         /// The first item is the account who update the replicas.
         /// The second item is the current block number
@@ -1690,5 +2085,21 @@ decl_event!(
         /// Set the spower ready period success
         /// The first item is the new spower ready period
         SetSpowerReadyPeriodSuccess(BlockNumber),
+        /// A replica is drawn to be spot checked
+        /// The first item is the cid of the file
+        /// The second item is the sworker anchor which should answer the spot check
+        /// The third item is the report slot of the spot check
+        /// The fourth item is the random nonce which should be signed in the proof
+        SpotCheckDrawn(MerkleRoot, SworkerAnchor, ReportSlot, SpotCheckNonce),
+        /// A spot check is answered with a valid proof
+        /// The first item is the cid of the file
+        /// The second item is the sworker anchor
+        SpotCheckPassed(MerkleRoot, SworkerAnchor),
+        /// A replica is deleted due to no answer of the spot check
+        /// The first item is the cid of the file
+        /// The second item is the sworker anchor
+        SpotCheckFailed(MerkleRoot, SworkerAnchor),
+        /// Set the spot check sample size success
+        SetSpotCheckSampleSizeSuccess(u32),
     }
 );
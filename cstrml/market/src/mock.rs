@@ -7,7 +7,7 @@ use crate as market;
 use frame_support::{
     parameter_types, assert_ok,
     weights::constants::RocksDbWeight,
-    traits::{OnFinalize, OnInitialize, Get}
+    traits::{OnFinalize, OnInitialize, Get, TestRandomness}
 };
 // use sp_core::H256;
 pub use sp_core::{crypto::{AccountId32, Ss58Codec}, H256};
@@ -162,14 +162,21 @@ impl BenefitInterface<AccountId, BalanceOf<Test>, NegativeImbalanceOf<Test>> for
         MerchantLedgers::set_reward(who, value);
     }
 
-    fn maybe_reduce_fee(_: &AccountId, _: BalanceOf<Test>, _: WithdrawReasons) -> Result<NegativeImbalance<Test>, DispatchError> {
-        Ok(NegativeImbalance::new(0))
+    fn maybe_reduce_fee(who: &AccountId, fee: BalanceOf<Test>, reasons: WithdrawReasons) -> Result<NegativeImbalance<Test>, DispatchError> {
+        let fee_reduction = Self::get_market_funds_ratio(who) * fee;
+        let mut imbalance = Balances::withdraw(who, fee - fee_reduction, reasons, ExistenceRequirement::KeepAlive)?;
+        imbalance.subsume(Balances::issue(fee_reduction));
+        Ok(imbalance)
     }
 
     fn maybe_free_count(_: &AccountId) -> bool {
         return true;
     }
 
+    fn maybe_compound_reward(_: &AccountId, _: BalanceOf<Test>) -> BalanceOf<Test> {
+        Zero::zero()
+    }
+
     fn get_collateral_and_reward(who: &AccountId) -> (BalanceOf<Test>, BalanceOf<Test>) {
         let ledgers = MerchantLedgers::get();
         let merchant_ledger = ledgers.get(who).unwrap_or( &MockMerchantLedger { collateral: 0, reward: 0});
@@ -185,6 +192,7 @@ impl BenefitInterface<AccountId, BalanceOf<Test>, NegativeImbalanceOf<Test>> for
 parameter_types! {
     pub const PunishmentSlots: u32 = 1;
     pub const MaxGroupSize: u32 = 100;
+    pub const SpowerOperatorBond: Balance = 0;
 }
 
 impl swork::Config for Test {
@@ -194,6 +202,7 @@ impl swork::Config for Test {
     type Works = ();
     type MarketInterface = Market;
     type MaxGroupSize = MaxGroupSize;
+    type SpowerOperatorBond = SpowerOperatorBond;
     type BenefitInterface = TestBenefitInterface;
     type WeightInfo = swork::weight::WeightInfo<Test>;
 }
@@ -240,6 +249,7 @@ impl Config for Test {
     type MaximumFileSize = MaximumFileSize;
     type WeightInfo = weight::WeightInfo<Test>;
     type RenewRewardRatio = RenewRewardRatio;
+    type Randomness = TestRandomness;
 }
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
@@ -318,13 +328,17 @@ pub fn add_who_into_replica(
     report_block: BlockNumber,
     valid_at: BlockNumber) {
     
+    // Fake the replicas queued by report works
+    let bn = Swork::last_processed_block_work_reports() + 1;
+    <swork::LatestReplicasCommitment>::put(Swork::processed_replicas_commitment());
+    Swork::queue_replica_commitment(bn, cid, reported_size, &who, &owner, &anchor, report_slot, report_block, valid_at, true);
     assert_ok!(Market::update_replicas(
             Origin::signed(SPOWER.clone()), 
             vec![(cid.clone(), 
                   reported_size, 
                   vec![(who.clone(), owner.clone(), anchor.clone(), report_slot, report_block, valid_at, true)]
                 )], 
-            400));
+            bn));
 }
 
 pub fn delete_replica(
@@ -337,13 +351,17 @@ pub fn delete_replica(
     report_block: BlockNumber,
     valid_at: BlockNumber) {
     
+    // Fake the replicas queued by report works
+    let bn = Swork::last_processed_block_work_reports() + 1;
+    <swork::LatestReplicasCommitment>::put(Swork::processed_replicas_commitment());
+    Swork::queue_replica_commitment(bn, cid, reported_size, &who, &owner, &anchor, report_slot, report_block, valid_at, false);
     assert_ok!(Market::update_replicas(
             Origin::signed(SPOWER.clone()), 
             vec![(cid.clone(), 
                   reported_size, 
                   vec![(who.clone(), owner.clone(), anchor.clone(), report_slot, report_block, valid_at, false)]
                 )], 
-            400));
+            bn));
 }
 
 pub fn legal_work_report_with_added_files() -> ReportWorksInfo {
@@ -182,7 +182,8 @@ fn place_storage_order_should_work_for_extend_scenarios() {
                 legal_wr_info.deleted_files,
                 legal_wr_info.srd_root,
                 legal_wr_info.files_root,
-                legal_wr_info.sig
+                legal_wr_info.sig,
+                vec![]
             ));
         // Swork::report_works should not change filesv2 right now
         assert_eq!(Market::filesv2(&cid).unwrap_or_default(),
@@ -199,14 +200,33 @@ fn place_storage_order_should_work_for_extend_scenarios() {
             }
         );
         run_to_block(700);
-        assert_ok!(Market::set_spower_superior(Origin::root(), spower.clone()));
+        assert_ok!(Swork::register_spower_operator(Origin::root(), spower.clone()));
+        // The replicas should be the same with the reported ones
+        assert_noop!(Market::update_replicas(
+            Origin::signed(spower.clone()), 
+            vec![(cid.clone(), 
+                  file_size, 
+                  vec![(merchant.clone(), merchant.clone(), legal_pk.clone(), legal_wr_info.block_number, 400, 303, true)]
+                )], 
+            400),
+            DispatchError::Module {
+                index: 3,
+                error: 13,
+                message: Some("ReplicasCommitmentMismatch")
+            });
         assert_ok!(Market::update_replicas(
             Origin::signed(spower.clone()), 
             vec![(cid.clone(), 
                   file_size, 
                   vec![(merchant.clone(), merchant.clone(), legal_pk.clone(), legal_wr_info.block_number, 400, 303, true)]
+                ),
+                ("QmdwgqZy1MZBfWPi7GcxVsYgJEtmvHg6rsLzbCej3tf3oH".as_bytes().to_vec(), 
+                  268578816, 
+                  vec![(merchant.clone(), merchant.clone(), legal_pk.clone(), legal_wr_info.block_number, 400, 303, true)]
                 )], 
             400));
+        assert_eq!(Swork::replicas_commitments(400), None);
+        assert_eq!(Swork::processed_replicas_commitment(), Swork::latest_replicas_commitment());
         assert_eq!(Market::filesv2(&cid).unwrap_or_default(),
             FileInfoV2 {
                 file_size,
@@ -283,12 +303,11 @@ fn place_storage_order_should_work_for_extend_scenarios() {
         assert_eq!(Market::orders_count(), 4);
 
         // 5. Update spower should work
-        assert_ok!(Swork::set_spower_superior(Origin::root(), spower.clone()));
         let file_spower = Market::calculate_spower(file_size, 1);
         assert_ok!(Swork::update_spower(
             Origin::signed(spower.clone()), 
             vec![(legal_pk.clone(), (file_spower - file_size).try_into().unwrap())],
-            vec![(cid.clone(), file_spower, vec![(merchant.clone(), merchant.clone(), legal_pk.clone(), None)])]));
+            vec![(cid.clone(), file_spower, vec![(merchant.clone(), merchant.clone(), legal_pk.clone(), None)])], 1));
         assert_eq!(Market::filesv2(&cid).unwrap_or_default(),
             FileInfoV2 {
                 file_size,
@@ -360,7 +379,7 @@ fn update_replicas_should_work_for_more_replicas() {
         let legal_wr_info = legal_work_report_with_added_files();
         let legal_pk = legal_wr_info.curr_pk.clone();
 
-        assert_ok!(Market::set_spower_superior(Origin::root(), spower.clone()));
+        assert_ok!(Swork::register_spower_operator(Origin::root(), spower.clone()));
         add_who_into_replica(&cid, file_size, ferdie.clone(), ferdie.clone(), legal_pk.clone(),legal_wr_info.block_number, 203, 103);
         add_who_into_replica(&cid, file_size, charlie.clone(), charlie.clone(), legal_pk.clone(), legal_wr_info.block_number, 203, 203);
         add_who_into_replica(&cid, file_size, dave.clone(), dave.clone(), legal_pk.clone(), legal_wr_info.block_number, 203, 203);
@@ -380,7 +399,8 @@ fn update_replicas_should_work_for_more_replicas() {
                 legal_wr_info.deleted_files,
                 legal_wr_info.srd_root,
                 legal_wr_info.files_root,
-                legal_wr_info.sig
+                legal_wr_info.sig,
+                vec![]
             ));
         <swork::ReportedInSlot>::insert(legal_pk.clone(), 0, true);
         add_who_into_replica(&cid, file_size, merchant.clone(), merchant.clone(), legal_pk.clone(), legal_wr_info.block_number, 303, 303);
@@ -486,7 +506,7 @@ fn update_replicas_should_only_pay_the_groups() {
         let _ = Balances::make_free_balance_be(&source, 20_000_000);
         let merchants = vec![merchant.clone(), charlie.clone(), dave.clone(), eve.clone(), ferdie.clone()];
 
-        assert_ok!(Market::set_spower_superior(Origin::root(), spower.clone()));
+        assert_ok!(Swork::register_spower_operator(Origin::root(), spower.clone()));
 
         assert_ok!(Market::place_storage_order(
             Origin::signed(source), cid.clone(),
@@ -548,7 +568,8 @@ fn update_replicas_should_only_pay_the_groups() {
                 legal_wr_info.deleted_files,
                 legal_wr_info.srd_root,
                 legal_wr_info.files_root,
-                legal_wr_info.sig
+                legal_wr_info.sig,
+                vec![]
             ));
 
         add_who_into_replica(&cid, file_size, merchant.clone(), merchant.clone(), legal_pk.clone(), legal_wr_info.block_number, 303, 303);
@@ -934,7 +955,7 @@ fn scenario_test_for_reported_file_size_is_not_same_with_file_size() {
         let _ = Balances::make_free_balance_be(&source, 20000);
         let _ = Balances::make_free_balance_be(&merchant, 20000);
 
-        assert_ok!(Market::set_spower_superior(Origin::root(), spower.clone()));
+        assert_ok!(Swork::register_spower_operator(Origin::root(), spower.clone()));
 
         mock_bond_owner(&merchant, &merchant);
         add_collateral(&merchant, 6000);
@@ -1021,7 +1042,7 @@ fn double_place_storage_order_file_size_check_should_work() {
         mock_bond_owner(&merchant, &merchant);
         add_collateral(&merchant, 6000);
 
-        assert_ok!(Market::set_spower_superior(Origin::root(), spower.clone()));
+        assert_ok!(Swork::register_spower_operator(Origin::root(), spower.clone()));
 
         assert_ok!(Market::place_storage_order(
             Origin::signed(source.clone()), cid1.clone(),
@@ -1132,7 +1153,7 @@ fn place_storage_order_for_expired_file_should_inherit_the_status() {
             add_collateral(&who, 6_000_000);
         }
 
-        assert_ok!(Market::set_spower_superior(Origin::root(), spower.clone()));
+        assert_ok!(Swork::register_spower_operator(Origin::root(), spower.clone()));
 
         assert_ok!(Market::place_storage_order(
             Origin::signed(source.clone()), cid.clone(),
@@ -1172,7 +1193,8 @@ fn place_storage_order_for_expired_file_should_inherit_the_status() {
                 legal_wr_info.deleted_files,
                 legal_wr_info.srd_root,
                 legal_wr_info.files_root,
-                legal_wr_info.sig
+                legal_wr_info.sig,
+                vec![]
             ));
         add_who_into_replica(&cid, file_size, merchant.clone(), merchant.clone(), legal_pk.clone(), legal_wr_info.block_number, 303, 303);
         assert_eq!(Market::filesv2(&cid).unwrap_or_default(),
@@ -1196,12 +1218,11 @@ fn place_storage_order_for_expired_file_should_inherit_the_status() {
         );
 
         run_to_block(503);
-        assert_ok!(Swork::set_spower_superior(Origin::root(), spower.clone()));
         let file_spower = Market::calculate_spower(file_size, 1);
         assert_ok!(Swork::update_spower(
             Origin::signed(spower.clone()), 
             vec![(legal_pk.clone(), (file_spower - file_size).try_into().unwrap())],
-            vec![(cid.clone(), file_spower, vec![(merchant.clone(), merchant.clone(), legal_pk.clone(), None)])]));
+            vec![(cid.clone(), file_spower, vec![(merchant.clone(), merchant.clone(), legal_pk.clone(), None)])], 1));
 
         assert_eq!(Market::filesv2(&cid).unwrap_or_default(),
             FileInfoV2 {
@@ -1330,7 +1351,7 @@ fn place_storage_order_for_file_should_make_it_pending_if_replicas_is_zero() {
             add_collateral(&who, 6_000_000);
         }
 
-        assert_ok!(Market::set_spower_superior(Origin::root(), spower.clone()));
+        assert_ok!(Swork::register_spower_operator(Origin::root(), spower.clone()));
 
         assert_ok!(Market::place_storage_order(
             Origin::signed(source.clone()), cid.clone(),
@@ -1370,7 +1391,8 @@ fn place_storage_order_for_file_should_make_it_pending_if_replicas_is_zero() {
                 legal_wr_info.deleted_files,
                 legal_wr_info.srd_root,
                 legal_wr_info.files_root,
-                legal_wr_info.sig
+                legal_wr_info.sig,
+                vec![]
             ));
 
         add_who_into_replica(&cid, file_size, merchant.clone(), merchant.clone(), legal_pk.clone(), legal_wr_info.block_number, 303, 303);
@@ -1396,12 +1418,11 @@ fn place_storage_order_for_file_should_make_it_pending_if_replicas_is_zero() {
         );
 
         run_to_block(503);
-        assert_ok!(Swork::set_spower_superior(Origin::root(), spower.clone()));
         let file_spower = Market::calculate_spower(file_size, 1);
         assert_ok!(Swork::update_spower(
             Origin::signed(spower.clone()), 
             vec![(legal_pk.clone(), (file_spower - file_size).try_into().unwrap())],
-            vec![(cid.clone(), file_spower, vec![(merchant.clone(), merchant.clone(), legal_pk.clone(), None)])]));
+            vec![(cid.clone(), file_spower, vec![(merchant.clone(), merchant.clone(), legal_pk.clone(), None)])], 1));
 
         assert_eq!(Market::filesv2(&cid).unwrap_or_default(),
             FileInfoV2 {
@@ -1464,7 +1485,7 @@ fn place_storage_order_for_file_should_make_it_pending_if_replicas_is_zero() {
         assert_ok!(Swork::update_spower(
             Origin::signed(spower.clone()), 
             vec![(legal_pk.clone(), (file_size as i64 - file_spower as i64))],
-            vec![(cid.clone(), new_file_spower, vec![(merchant.clone(), merchant.clone(), legal_pk.clone(), None)])]));
+            vec![(cid.clone(), new_file_spower, vec![(merchant.clone(), merchant.clone(), legal_pk.clone(), None)])], 2));
 
         assert_eq!(Market::filesv2(&cid).unwrap_or_default(),
             FileInfoV2 {
@@ -1523,8 +1544,7 @@ fn dynamic_spower_should_work() {
             add_collateral(&who, 6_000_000);
         }
 
-        assert_ok!(Market::set_spower_superior(Origin::root(), spower.clone()));
-        assert_ok!(Swork::set_spower_superior(Origin::root(), spower.clone()));
+        assert_ok!(Swork::register_spower_operator(Origin::root(), spower.clone()));
 
         assert_ok!(Market::place_storage_order(
             Origin::signed(source), cid.clone(),
@@ -1559,7 +1579,7 @@ fn dynamic_spower_should_work() {
         assert_ok!(Swork::update_spower(
             Origin::signed(spower.clone()), 
             vec![(legal_pk.clone(), (file_spower_10 as i64 - file_size as i64))],
-            vec![(cid.clone(), file_spower_10, vec![(merchant.clone(), merchant.clone(), legal_pk.clone(), None)])]));
+            vec![(cid.clone(), file_spower_10, vec![(merchant.clone(), merchant.clone(), legal_pk.clone(), None)])], 1));
 
         assert_eq!(Market::filesv2(&cid).unwrap_or_default().spower, Market::calculate_spower(file_size, 10));
         assert_eq!(Market::filesv2(&cid).unwrap_or_default().reported_replica_count, 10);
@@ -1571,7 +1591,7 @@ fn dynamic_spower_should_work() {
         assert_ok!(Swork::update_spower(
             Origin::signed(spower.clone()), 
             vec![(legal_pk.clone(), (file_spower_20 as i64 - file_spower_10 as i64))],
-            vec![(cid.clone(), file_spower_20, vec![(merchant.clone(), merchant.clone(), legal_pk.clone(), None)])]));
+            vec![(cid.clone(), file_spower_20, vec![(merchant.clone(), merchant.clone(), legal_pk.clone(), None)])], 2));
         
         assert_eq!(Market::filesv2(&cid).unwrap_or_default().spower, Market::calculate_spower(file_size, 20));
         assert_eq!(Market::filesv2(&cid).unwrap_or_default().reported_replica_count, 20);
@@ -1584,7 +1604,7 @@ fn dynamic_spower_should_work() {
         assert_ok!(Swork::update_spower(
             Origin::signed(spower.clone()), 
             vec![(legal_pk.clone(), (file_spower_200 as i64 - file_spower_20 as i64))],
-            vec![(cid.clone(), file_spower_200, vec![(merchant.clone(), merchant.clone(), legal_pk.clone(), None)])]));
+            vec![(cid.clone(), file_spower_200, vec![(merchant.clone(), merchant.clone(), legal_pk.clone(), None)])], 3));
 
         assert_eq!(Market::filesv2(&cid).unwrap_or_default().spower, Market::calculate_spower(file_size, 200));
         assert_eq!(Market::filesv2(&cid).unwrap_or_default().reported_replica_count, 200);
@@ -1597,7 +1617,7 @@ fn dynamic_spower_should_work() {
         assert_ok!(Swork::update_spower(
             Origin::signed(spower.clone()), 
             vec![(legal_pk.clone(), (file_spower_60 as i64 - file_spower_200 as i64))],
-            vec![(cid.clone(), file_spower_60, vec![(merchant.clone(), merchant.clone(), legal_pk.clone(), None)])]));
+            vec![(cid.clone(), file_spower_60, vec![(merchant.clone(), merchant.clone(), legal_pk.clone(), None)])], 4));
 
         assert_eq!(Market::filesv2(&cid).unwrap_or_default().spower, Market::calculate_spower(file_size, 60));
         assert_eq!(Market::filesv2(&cid).unwrap_or_default().reported_replica_count, 60);
@@ -1640,8 +1660,7 @@ fn delete_spower_should_work() {
             add_collateral(&who, 6_000_000);
         }
 
-        assert_ok!(Market::set_spower_superior(Origin::root(), spower.clone()));
-        assert_ok!(Swork::set_spower_superior(Origin::root(), spower.clone()));
+        assert_ok!(Swork::register_spower_operator(Origin::root(), spower.clone()));
 
         assert_ok!(Market::place_storage_order(
             Origin::signed(source), cid.clone(),
@@ -1692,7 +1711,7 @@ fn delete_spower_should_work() {
         assert_ok!(Swork::update_spower(
             Origin::signed(spower.clone()), 
             changed_spowers,
-            vec![(cid.clone(), file_spower_20, changed_replicas)]));
+            vec![(cid.clone(), file_spower_20, changed_replicas)], 1));
 
         assert_eq!(Market::filesv2(&cid).unwrap_or_default().spower, Market::calculate_spower(file_size, 20));
         assert_eq!(Market::filesv2(&cid).unwrap_or_default().reported_replica_count, 20);
@@ -1713,7 +1732,7 @@ fn delete_spower_should_work() {
         assert_ok!(Swork::update_spower(
             Origin::signed(spower.clone()), 
             changed_spowers_19,
-            vec![(cid.clone(), file_spower_19, vec![])]));
+            vec![(cid.clone(), file_spower_19, vec![])], 2));
 
         assert_eq!(Market::filesv2(&cid).unwrap_or_default().spower, Market::calculate_spower(file_size, 19));
         assert_eq!(Market::filesv2(&cid).unwrap_or_default().reported_replica_count, 19);
@@ -1734,7 +1753,7 @@ fn delete_spower_should_work() {
         assert_ok!(Swork::update_spower(
             Origin::signed(spower.clone()), 
             changed_spowers_18,
-            vec![(cid.clone(), file_spower_18, vec![])]));
+            vec![(cid.clone(), file_spower_18, vec![])], 3));
 
         assert_eq!(Market::filesv2(&cid).unwrap_or_default().spower, Market::calculate_spower(file_size, 18));
         assert_eq!(Market::filesv2(&cid).unwrap_or_default().reported_replica_count, 18);
@@ -1768,7 +1787,7 @@ fn reward_liquidator_should_work() {
             add_collateral(&who, 6_000_000);
         }
 
-        assert_ok!(Market::set_spower_superior(Origin::root(), spower.clone()));
+        assert_ok!(Swork::register_spower_operator(Origin::root(), spower.clone()));
 
         assert_ok!(Market::calculate_reward(Origin::signed(charlie.clone()), cid.clone()));
 
@@ -1804,7 +1823,8 @@ fn reward_liquidator_should_work() {
                 legal_wr_info.deleted_files,
                 legal_wr_info.srd_root,
                 legal_wr_info.files_root,
-                legal_wr_info.sig
+                legal_wr_info.sig,
+                vec![]
         ));
 
         // Calculate reward cannot work in the middle of the file
@@ -1940,8 +1960,7 @@ fn renew_file_should_work() {
             add_collateral(&who, 6_000_000);
         }
         
-        assert_ok!(Market::set_spower_superior(Origin::root(), spower.clone()));
-        assert_ok!(Swork::set_spower_superior(Origin::root(), spower.clone()));
+        assert_ok!(Swork::register_spower_operator(Origin::root(), spower.clone()));
 
         assert_noop!(
             Market::add_prepaid(Origin::signed(source.clone()), cid.clone(), 400_000),
@@ -1975,7 +1994,8 @@ fn renew_file_should_work() {
                 legal_wr_info.deleted_files,
                 legal_wr_info.srd_root,
                 legal_wr_info.files_root,
-                legal_wr_info.sig
+                legal_wr_info.sig,
+                vec![]
         ));
         
         add_who_into_replica(&cid, file_size, merchant.clone(), merchant.clone(), legal_pk.clone(), legal_wr_info.block_number, 303, 303);
@@ -2088,6 +2108,284 @@ fn renew_file_should_work() {
     });
 }
 
+#[test]
+fn auto_renew_file_should_work() {
+    new_test_ext().execute_with(|| {
+        // generate 50 blocks first
+        run_to_block(50);
+
+        let cid =
+            "QmdwgqZy1MZBfWPi7GcxVsYgJEtmvHg6rsLzbCej3tf3oF".as_bytes().to_vec();
+        let file_size = 134289408; // should less than merchant
+        let source = ALICE;
+        let merchant = BOB;
+        let spower = SPOWER;
+
+        let storage_pot = Market::storage_pot();
+        let reserved_pot = Market::reserved_pot();
+        let _ = Balances::make_free_balance_be(&storage_pot, 1);
+
+        let _ = Balances::make_free_balance_be(&source, 20_000_000);
+        let _ = Balances::make_free_balance_be(&merchant, 20_000_000);
+        mock_bond_owner(&merchant, &merchant);
+        add_collateral(&merchant, 6_000_000);
+
+        assert_ok!(Swork::register_spower_operator(Origin::root(), spower.clone()));
+
+        assert_ok!(Market::place_storage_order(
+            Origin::signed(source.clone()), cid.clone(),
+            file_size, 0, vec![]
+        ));
+        run_to_block(303);
+
+        let legal_pk = legal_work_report_with_added_files().curr_pk;
+        register(&legal_pk, LegalCode::get());
+        add_who_into_replica(&cid, file_size, merchant.clone(), merchant.clone(), legal_pk.clone(), 300, 303, 303);
+        // No prepaid, no renewal
+        assert_eq!(Market::renew_queue(1303), Vec::<MerkleRoot>::new());
+
+        assert_ok!(Market::add_prepaid(Origin::signed(source.clone()), cid.clone(), 400_000));
+        assert_eq!(Market::renew_queue(1303), vec![cid.clone()]);
+
+        // Not expired yet
+        run_to_block(1302);
+        Market::on_initialize(1302);
+        assert_eq!(Market::filesv2(&cid).unwrap_or_default().expired_at, 1303);
+        assert_eq!(Market::next_renew_block(), 1303);
+
+        // Renewed without any liquidator, the remaining amount is kept in the file
+        run_to_block(1303);
+        Market::on_initialize(1303);
+        assert_eq!(Market::filesv2(&cid).unwrap_or_default(),
+            FileInfoV2 {
+                file_size,
+                spower: 0,
+                expired_at: 2303,
+                calculated_at: 1303,
+                amount: 43215, // 19995 + 23220
+                prepaid: 270000,
+                reported_replica_count: 1,
+                remaining_paid_count: 3,
+                replicas: BTreeMap::from_iter(vec![(merchant.clone(), Replica {
+                    who: merchant.clone(),
+                    valid_at: 303,
+                    anchor: legal_pk.clone(),
+                    is_reported: true,
+                    created_at: Some(303)
+                })])
+            }
+        );
+        assert_eq!(Balances::free_balance(&reserved_pot), 27800);
+        assert_eq!(Market::renew_queue(1303), Vec::<MerkleRoot>::new());
+        assert_eq!(Market::renew_queue(2303), vec![cid.clone()]);
+        assert_eq!(Market::next_renew_block(), 1304);
+
+        // Prepaid runs out after 3 renewals, then it should wait for liquidation
+        for renew_at in vec![2303u32, 3303, 4303] {
+            run_to_block(renew_at as u64);
+            // Skip the empty blocks in between
+            NextRenewBlock::put(renew_at);
+            Market::on_initialize(renew_at as u64);
+        }
+        // The file is left untouched for the liquidators
+        let file_info = Market::filesv2(&cid).unwrap_or_default();
+        assert_eq!(file_info.expired_at, 4303);
+        assert_eq!(file_info.calculated_at, 3303);
+        assert_eq!(file_info.prepaid, 10000);
+        assert_eq!(Market::renew_queue(5303), Vec::<MerkleRoot>::new());
+
+        assert_ok!(Market::calculate_reward(Origin::signed(CHARLIE), cid.clone()));
+        assert_eq!(Market::filesv2(&cid).is_none(), true);
+    });
+}
+
+#[test]
+fn auto_renew_should_be_limited_per_block() {
+    new_test_ext().execute_with(|| {
+        run_to_block(50);
+        let cids: Vec<MerkleRoot> = (0..MAX_AUTO_RENEW_FILES_COUNT + 10).map(|i| format!("cid-{}", i).as_bytes().to_vec()).collect();
+        <RenewQueue>::insert(100, cids.clone());
+
+        run_to_block(100);
+        Market::on_initialize(100);
+        // The rest would be handled in the next block
+        assert_eq!(Market::renew_queue(100), cids[MAX_AUTO_RENEW_FILES_COUNT..].to_vec());
+        assert_eq!(Market::next_renew_block(), 100);
+
+        run_to_block(101);
+        Market::on_initialize(101);
+        assert_eq!(Market::renew_queue(100), Vec::<MerkleRoot>::new());
+        assert_eq!(Market::next_renew_block(), 102);
+    });
+}
+
+#[test]
+fn renew_queue_should_spill_into_following_blocks() {
+    new_test_ext().execute_with(|| {
+        run_to_block(50);
+        let cid = "QmdwgqZy1MZBfWPi7GcxVsYgJEtmvHg6rsLzbCej3tf3oF".as_bytes().to_vec();
+        let full_queue: Vec<MerkleRoot> = (0..MAX_RENEW_QUEUE_LENGTH).map(|i| format!("cid-{}", i).as_bytes().to_vec()).collect();
+        <RenewQueue>::insert(1303, full_queue.clone());
+        <RenewQueue>::insert(1304, full_queue.clone());
+        <FilesV2<Test>>::insert(&cid, FileInfoV2 {
+            file_size: 100,
+            spower: 0,
+            expired_at: 1303,
+            calculated_at: 303,
+            amount: 1000,
+            prepaid: 1000,
+            reported_replica_count: 1,
+            remaining_paid_count: 4,
+            replicas: BTreeMap::new()
+        });
+
+        // Files placed before the auto renewal are scheduled by anyone
+        let cids = vec![cid.clone(); MAX_SCHEDULE_RENEWAL_FILES_COUNT + 1];
+        assert_noop!(
+            Market::schedule_files_renewal(Origin::signed(ALICE), cids),
+            DispatchError::Module {
+                index: 3,
+                error: 14,
+                message: Some("ExceedScheduleRenewalFilesLimit"),
+            }
+        );
+        assert_ok!(Market::schedule_files_renewal(Origin::signed(ALICE), vec![cid.clone()]));
+        assert_eq!(Market::renew_queue(1303).len(), MAX_RENEW_QUEUE_LENGTH);
+        assert_eq!(Market::renew_queue(1304).len(), MAX_RENEW_QUEUE_LENGTH);
+        assert_eq!(Market::renew_queue(1305), vec![cid.clone()]);
+
+        // Scheduling it again should not duplicate it
+        assert_ok!(Market::schedule_files_renewal(Origin::signed(ALICE), vec![cid.clone()]));
+        assert_eq!(Market::renew_queue(1305), vec![cid.clone()]);
+    });
+}
+
+#[test]
+fn files_expiry_index_should_work() {
+    new_test_ext().execute_with(|| {
+        run_to_block(50);
+
+        let cid =
+            "QmdwgqZy1MZBfWPi7GcxVsYgJEtmvHg6rsLzbCej3tf3oF".as_bytes().to_vec();
+        let file_size = 134289408; // should less than merchant
+        let source = ALICE;
+        let merchant = BOB;
+        let spower = SPOWER;
+
+        let _ = Balances::make_free_balance_be(&Market::storage_pot(), 1);
+        let _ = Balances::make_free_balance_be(&source, 20_000_000);
+        let _ = Balances::make_free_balance_be(&merchant, 20_000_000);
+        mock_bond_owner(&merchant, &merchant);
+        add_collateral(&merchant, 6_000_000);
+        assert_ok!(Swork::register_spower_operator(Origin::root(), spower.clone()));
+
+        assert_ok!(Market::place_storage_order(
+            Origin::signed(source.clone()), cid.clone(),
+            file_size, 0, vec![]
+        ));
+        // Pending file is not in the index
+        assert_eq!(Market::files_expiry_index(0, &cid), None);
+
+        run_to_block(303);
+        let legal_pk = legal_work_report_with_added_files().curr_pk;
+        register(&legal_pk, LegalCode::get());
+        add_who_into_replica(&cid, file_size, merchant.clone(), merchant.clone(), legal_pk.clone(), 300, 303, 303);
+        assert_eq!(Market::files_expiry_index(1200, &cid), Some(1303));
+
+        // Not expired yet
+        run_to_block(1000);
+        assert_eq!(Market::claimable_files(0, 2000), vec![]);
+
+        run_to_block(1400);
+        assert_eq!(Market::claimable_files(0, 1302), vec![]);
+        assert_eq!(Market::claimable_files(1303, 2000), vec![(cid.clone(), 1303)]);
+
+        // Refresh the file, it should be moved into the new slot
+        assert_ok!(Market::place_storage_order(
+            Origin::signed(source.clone()), cid.clone(),
+            file_size, 0, vec![]
+        ));
+        assert_eq!(Market::files_expiry_index(1200, &cid), None);
+        assert_eq!(Market::files_expiry_index(2400, &cid), Some(2400));
+        assert_eq!(Market::claimable_files(0, 3000), vec![]);
+
+        run_to_block(2500);
+        assert_eq!(Market::claimable_files(0, 3000), vec![(cid.clone(), 2400)]);
+        assert_ok!(Market::calculate_reward(Origin::signed(CHARLIE), cid.clone()));
+        assert_eq!(Market::filesv2(&cid).is_none(), true);
+        assert_eq!(Market::files_expiry_index(2400, &cid), None);
+        assert_eq!(Market::claimable_files(0, 3000), vec![]);
+    });
+}
+
+#[test]
+fn spot_check_should_work() {
+    new_test_ext().execute_with(|| {
+        run_to_block(50);
+
+        let cid =
+            "QmdwgqZy1MZBfWPi7GcxVsYgJEtmvHg6rsLzbCej3tf3oF".as_bytes().to_vec();
+        let file_size = 134289408; // should less than merchant
+        let source = ALICE;
+        let merchant = BOB;
+        let spower = SPOWER;
+
+        let _ = Balances::make_free_balance_be(&Market::storage_pot(), 1);
+        let _ = Balances::make_free_balance_be(&source, 20_000_000);
+        let _ = Balances::make_free_balance_be(&merchant, 20_000_000);
+        mock_bond_owner(&merchant, &merchant);
+        add_collateral(&merchant, 6_000_000);
+        assert_ok!(Swork::register_spower_operator(Origin::root(), spower.clone()));
+
+        assert_noop!(
+            Market::set_spot_check_sample_size(Origin::root(), 101),
+            DispatchError::Module {
+                index: 3,
+                error: 12,
+                message: Some("ExceedSpotCheckSampleSizeLimit")
+            }
+        );
+        assert_ok!(Market::set_spot_check_sample_size(Origin::root(), 1));
+
+        assert_ok!(Market::place_storage_order(
+            Origin::signed(source.clone()), cid.clone(),
+            file_size, 0, vec![]
+        ));
+
+        run_to_block(303);
+        let legal_pk = legal_work_report_with_added_files().curr_pk;
+        register(&legal_pk, LegalCode::get());
+        add_who_into_replica(&cid, file_size, merchant.clone(), merchant.clone(), legal_pk.clone(), 300, 303, 303);
+
+        // 1. Draw the spot check of slot 300 and answer it
+        run_to_block(587);
+        Market::on_initialize(587);
+        let (owner, nonce) = Market::spot_checks(300, (cid.clone(), legal_pk.clone())).unwrap();
+        assert_eq!(owner, merchant.clone());
+        assert_eq!(Market::spot_check_nonce(&legal_pk, &cid, 300), Some(nonce));
+        assert_eq!(Market::spot_check_nonce(&legal_pk, &cid, 0), None);
+        assert_eq!(Market::pass_spot_check(&legal_pk, &cid, 0), false);
+        assert_eq!(Market::pass_spot_check(&legal_pk, &cid, 300), true);
+        assert_eq!(Market::spot_checks(300, (cid.clone(), legal_pk.clone())), None);
+
+        // 2. Settle the answered spot check, replica should be kept
+        run_to_block(887);
+        Market::on_initialize(887);
+        assert_eq!(Market::filesv2(&cid).unwrap().replicas.len(), 1);
+        assert_eq!(Market::spot_checks(600, (cid.clone(), legal_pk.clone())).map(|(owner, _)| owner), Some(merchant.clone()));
+
+        // 3. Settle the unanswered spot check, replica should be deleted
+        run_to_block(1187);
+        Market::on_initialize(1187);
+        assert_eq!(Market::spot_checks(600, (cid.clone(), legal_pk.clone())), None);
+        let file_info = Market::filesv2(&cid).unwrap();
+        assert_eq!(file_info.replicas.len(), 0);
+        assert_eq!(file_info.reported_replica_count, 0);
+        // Nothing to be drawn for the file without replicas
+        assert_eq!(Market::spot_checks(900, (cid.clone(), legal_pk.clone())), None);
+    });
+}
+
 #[test]
 fn renew_onging_file_should_not_work() {
     new_test_ext().execute_with(|| {
@@ -2117,8 +2415,7 @@ fn renew_onging_file_should_not_work() {
             add_collateral(&who, 6_000_000);
         }
 
-        assert_ok!(Market::set_spower_superior(Origin::root(), spower.clone()));
-        assert_ok!(Swork::set_spower_superior(Origin::root(), spower.clone()));
+        assert_ok!(Swork::register_spower_operator(Origin::root(), spower.clone()));
 
         assert_noop!(
             Market::add_prepaid(Origin::signed(source.clone()), cid.clone(), 400_000),
@@ -2152,7 +2449,8 @@ fn renew_onging_file_should_not_work() {
                 legal_wr_info.deleted_files,
                 legal_wr_info.srd_root,
                 legal_wr_info.files_root,
-                legal_wr_info.sig
+                legal_wr_info.sig,
+                vec![]
         ));
         add_who_into_replica(&cid, file_size, merchant.clone(), merchant.clone(), legal_pk.clone(), legal_wr_info.block_number, 303, 303);
 
@@ -2225,7 +2523,7 @@ fn change_base_fee_should_work() {
 
         add_collateral(&merchant, 60_000);
 
-        assert_ok!(Market::set_spower_superior(Origin::root(), spower.clone()));
+        assert_ok!(Swork::register_spower_operator(Origin::root(), spower.clone()));
 
         // Change base fee to 10000
         assert_ok!(Market::set_base_fee(Origin::root(), 50000));
@@ -2271,7 +2569,8 @@ fn change_base_fee_should_work() {
             legal_wr_info.deleted_files,
             legal_wr_info.srd_root,
             legal_wr_info.files_root,
-            legal_wr_info.sig
+            legal_wr_info.sig,
+            vec![]
         ));
 
         add_who_into_replica(&cid, file_size, merchant.clone(), merchant.clone(), legal_pk.clone(), legal_wr_info.block_number, 303, 303);
@@ -2348,7 +2647,7 @@ fn storage_pot_should_be_balanced() {
             add_collateral(&who, 6_000_000);
         }
 
-        assert_ok!(Market::set_spower_superior(Origin::root(), spower.clone()));
+        assert_ok!(Swork::register_spower_operator(Origin::root(), spower.clone()));
 
         assert_ok!(Market::place_storage_order(
             Origin::signed(source.clone()), cid.clone(),
@@ -2372,7 +2671,8 @@ fn storage_pot_should_be_balanced() {
                 legal_wr_info.deleted_files,
                 legal_wr_info.srd_root,
                 legal_wr_info.files_root,
-                legal_wr_info.sig
+                legal_wr_info.sig,
+                vec![]
         ));
 
         add_who_into_replica(&cid, file_size, merchant.clone(), merchant.clone(), legal_pk.clone(), legal_wr_info.block_number, 303, 303);
@@ -2446,7 +2746,7 @@ fn one_owner_should_work() {
         let legal_wr_info = legal_work_report_with_added_files();
         let legal_pk = legal_wr_info.curr_pk.clone();
 
-        assert_ok!(Market::set_spower_superior(Origin::root(), spower.clone()));
+        assert_ok!(Swork::register_spower_operator(Origin::root(), spower.clone()));
         add_who_into_replica(&cid, file_size, ferdie.clone(), zikun.clone(), legal_pk.clone(), legal_wr_info.block_number, 303, 303);
         add_who_into_replica(&cid, file_size, charlie.clone(), bob.clone(), legal_pk.clone(), legal_wr_info.block_number, 303, 303);
         add_who_into_replica(&cid, file_size, dave.clone(), bob.clone(), legal_pk.clone(), legal_wr_info.block_number, 303, 303);
@@ -2466,7 +2766,8 @@ fn one_owner_should_work() {
                 legal_wr_info.deleted_files,
                 legal_wr_info.srd_root,
                 legal_wr_info.files_root,
-                legal_wr_info.sig
+                legal_wr_info.sig,
+                vec![]
             ));
 
         add_who_into_replica(&cid, file_size, eve.clone(), zikun.clone(), legal_pk.clone(), legal_wr_info.block_number, 403, 403);
@@ -2486,98 +2787,71 @@ fn one_owner_should_work() {
     });
 }
 
-// #[test]
-// fn place_storage_order_with_discount_should_work() {
-//     new_test_ext().execute_with(|| {
-//         // generate 50 blocks first
-//         run_to_block(50);
-//         set_discount_ratio(1, 20); // 5% discount
-
-//         let source = ALICE;
-//         let merchant = MERCHANT;
-
-//         let cid =
-//             hex::decode("4e2883ddcbc77cf19979770d756fd332d0c8f815f9de646636169e460e6af6ff").unwrap();
-//         let file_size = 100; // should less than
-//         let reserved_pot = Market::reserved_pot();
-//         let staking_pot = Market::staking_pot();
-//         let storage_pot = Market::storage_pot();
-//         assert_eq!(Balances::free_balance(&staking_pot), 0);
-//         let _ = Balances::make_free_balance_be(&source, 10000);
-//         let _ = Balances::make_free_balance_be(&merchant, 200);
-
-//         mock_bond_owner(&merchant, &merchant);
-
-//         <FileKeysCountFee<Test>>::put(1000);
-//         assert_ok!(Market::place_storage_order(
-//             Origin::signed(source.clone()), cid.clone(),
-//             file_size, 0, vec![]
-//         ));
-//         assert_eq!(Market::filesv2(&cid).unwrap_or_default(),
-//             FileInfoV2 {
-//                 file_size,
-//                 spower: 0,
-//                 expired_at: 0,
-//                 calculated_at: 50,
-//                 amount: 360, // ( 1000 * 1 + 0 + 1000 ) * 0.18
-//                 prepaid: 0,
-//                 reported_replica_count: 0,
-//                 replicas: vec![]
-//             }
-//         );
-//         assert_eq!(Balances::free_balance(&reserved_pot), 1050);
-//         assert_eq!(Balances::free_balance(&staking_pot), 1440);
-//         assert_eq!(Balances::free_balance(&storage_pot), 360);
-//         assert_eq!(Balances::free_balance(&source), 7150);
-
-//         set_discount_ratio(1, 10); // 10% discount
-
-//         assert_ok!(Market::place_storage_order(
-//             Origin::signed(source.clone()), cid.clone(),
-//             file_size, 0, vec![]
-//         ));
-//         assert_eq!(Market::filesv2(&cid).unwrap_or_default(),
-//             FileInfoV2 {
-//                 file_size,
-//                 spower: 0,
-//                 expired_at: 0,
-//                 calculated_at: 50,
-//                 amount: 720, // ( 1000 + 1000 * 1 + 0 + 1000 ) * 0.18
-//                 prepaid: 0,
-//                 reported_replica_count: 0,
-//                 replicas: vec![]
-//             }
-//         );
-//         assert_eq!(Balances::free_balance(&reserved_pot), 1950); // 150 + 0
-//         assert_eq!(Balances::free_balance(&staking_pot), 2880);
-//         assert_eq!(Balances::free_balance(&storage_pot), 720);
-//         assert_eq!(Balances::free_balance(&source), 4450);
-
-
-//         set_discount_ratio(1, 5); // 10% discount
-
-//         assert_ok!(Market::place_storage_order(
-//             Origin::signed(source.clone()), cid.clone(),
-//             file_size, 0, vec![]
-//         ));
-//         assert_eq!(Market::filesv2(&cid).unwrap_or_default(),
-//            FileInfoV2 {
-//                file_size,
-//                spower: 0,
-//                expired_at: 0,
-//                calculated_at: 50,
-//                amount: 1080, // ( 1000 + 1000 * 1 + 0 + 1000 ) * 0.18
-//                prepaid: 0,
-//                reported_replica_count: 0,
-//                replicas: vec![]
-//            }
-//         );
-//         assert_eq!(Balances::free_balance(reserved_pot), 2850); // 150 + 0
-//         assert_eq!(Balances::free_balance(staking_pot), 4320);
-//         assert_eq!(Balances::free_balance(storage_pot), 1080);
-//         assert_eq!(Balances::free_balance(&source), 1750);
-//     });
-// }
+#[test]
+fn place_storage_order_and_add_prepaid_with_fee_reduction_should_work() {
+    new_test_ext().execute_with(|| {
+        // generate 50 blocks first
+        run_to_block(50);
+        set_discount_ratio(1, 10); // 10% fee reduction
+
+        let source = ALICE;
+        let merchant = MERCHANT;
+
+        let cid =
+            hex::decode("4e2883ddcbc77cf19979770d756fd332d0c8f815f9de646636169e460e6af6ff").unwrap();
+        let file_size = 100; // should less than
+        let reserved_pot = Market::reserved_pot();
+        let staking_pot = Market::staking_pot();
+        let storage_pot = Market::storage_pot();
+        let _ = Balances::make_free_balance_be(&source, 8000);
+        let _ = Balances::make_free_balance_be(&merchant, 200);
+
+        mock_bond_owner(&merchant, &merchant);
+        add_collateral(&merchant, 60);
+        <FileKeysCountFee<Test>>::put(1000);
+
+        // 1. Only root can switch the fee reduction
+        assert_noop!(
+            Market::set_fee_reduction(Origin::signed(source.clone()), true, true),
+            DispatchError::BadOrigin
+        );
+
+        // 2. Fee reduction is disabled by default
+        assert_ok!(Market::place_storage_order(
+            Origin::signed(source.clone()), cid.clone(),
+            file_size, 0, vec![]
+        ));
+        assert_eq!(Balances::free_balance(&reserved_pot), 1200);
+        assert_eq!(Balances::free_balance(&staking_pot), 1440);
+        assert_eq!(Balances::free_balance(&storage_pot), 360);
+        assert_eq!(Balances::free_balance(&source), 5000);
+
+        // 3. Base fee is reduced while the pots receive the full amount
+        assert_ok!(Market::set_fee_reduction(Origin::root(), true, false));
+        assert_ok!(Market::place_storage_order(
+            Origin::signed(source.clone()), cid.clone(),
+            file_size, 0, vec![]
+        ));
+        assert_eq!(Market::filesv2(&cid).unwrap_or_default().amount, 720);
+        assert_eq!(Balances::free_balance(&reserved_pot), 2400);
+        assert_eq!(Balances::free_balance(&staking_pot), 2880);
+        assert_eq!(Balances::free_balance(&storage_pot), 720);
+        assert_eq!(Balances::free_balance(&source), 2100); // 5000 - 900 - 2000
+
+        // 4. Prepaid is not reduced yet
+        assert_ok!(Market::add_prepaid(Origin::signed(source.clone()), cid.clone(), 1000));
+        assert_eq!(Market::filesv2(&cid).unwrap_or_default().prepaid, 1000);
+        assert_eq!(Balances::free_balance(&storage_pot), 1720);
+        assert_eq!(Balances::free_balance(&source), 1100);
+
+        // 5. Prepaid is reduced and fully credited
+        assert_ok!(Market::set_fee_reduction(Origin::root(), false, true));
+        assert_ok!(Market::add_prepaid(Origin::signed(source.clone()), cid.clone(), 1000));
+        assert_eq!(Market::filesv2(&cid).unwrap_or_default().prepaid, 2000);
+        assert_eq!(Balances::free_balance(&storage_pot), 2720);
+        assert_eq!(Balances::free_balance(&source), 200);
+    });
+}
 
 #[test]
 fn spower_delay_should_work() {
@@ -2608,8 +2882,7 @@ fn spower_delay_should_work() {
             add_collateral(&who, 6_000_000);
         }
 
-        assert_ok!(Market::set_spower_superior(Origin::root(), spower.clone()));
-        assert_ok!(Swork::set_spower_superior(Origin::root(), spower.clone()));
+        assert_ok!(Swork::register_spower_operator(Origin::root(), spower.clone()));
 
         assert_ok!(Market::place_storage_order(
             Origin::signed(source), cid.clone(),
@@ -2649,7 +2922,8 @@ fn spower_delay_should_work() {
             legal_wr_info.deleted_files,
             legal_wr_info.srd_root,
             legal_wr_info.files_root,
-            legal_wr_info.sig
+            legal_wr_info.sig,
+            vec![]
         ));
 
         add_who_into_replica(&cid, file_size, merchant.clone(), merchant.clone(), legal_pk.clone(), legal_wr_info.block_number, 303, 303);
@@ -2679,7 +2953,7 @@ fn spower_delay_should_work() {
         assert_ok!(Swork::update_spower(
             Origin::signed(spower.clone()), 
             vec![(legal_pk.clone(), (file_spower as i64 - file_size as i64))],
-            vec![(cid.clone(), file_spower, vec![(merchant.clone(), merchant.clone(), legal_pk.clone(), None)])]));
+            vec![(cid.clone(), file_spower, vec![(merchant.clone(), merchant.clone(), legal_pk.clone(), None)])], 1));
 
         assert_eq!(Market::filesv2(&cid).unwrap_or_default(),
            FileInfoV2 {
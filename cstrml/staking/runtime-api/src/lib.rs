@@ -0,0 +1,26 @@
+// Copyright (C) 2019-2021 Crust Network Technologies Ltd.
+// This file is part of Crust.
+
+//! Runtime API definition for the staking module.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Codec, HasCompact};
+pub use staking::{
+    SlashingReport, PendingSlash, SlashingSpanInfo, RewardProjection, EraRewardProjection
+};
+
+sp_api::decl_runtime_apis! {
+    pub trait StakingApi<AccountId, Balance> where
+        AccountId: Codec,
+        Balance: Codec + HasCompact,
+    {
+        /// Get the slashing status of the stash, including the pending unapplied slashes,
+        /// the slashing span history and the guarantees at risk.
+        fn slashing_report(stash: AccountId) -> SlashingReport<AccountId, Balance>;
+
+        /// Get the projected rewards and their breakdown of the next `eras` eras from the
+        /// active era, along with the yearly reward curve.
+        fn reward_projection(eras: u32) -> RewardProjection<Balance>;
+    }
+}
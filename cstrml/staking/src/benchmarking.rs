@@ -107,6 +107,21 @@ pub fn create_one_validator_with_one_guarantor<T: Config>(n: u32) -> Result<(T::
     return Ok((n_controller, v_stash))
 }
 
+// This function generates v validators with g guarantors each, the guarantors are shared by the neighbour validators.
+pub fn create_vg_graph<T: Config>(v: u32, g: u32) -> BTreeMap<T::AccountId, Vec<IndividualExposure<T::AccountId, BalanceOf<T>>>> {
+    let mut vg_graph = BTreeMap::new();
+    for i in 0 .. v {
+        let v_stash: T::AccountId = account("validator", i, SEED);
+        Staking::<T>::upsert_stake_limit(&v_stash, T::Currency::minimum_balance() * STAKE_LIMIT_RATIO.into());
+        let voters = (0 .. g).map(|j| IndividualExposure {
+            who: account("guarantor", i + j, SEED),
+            value: T::Currency::minimum_balance() * (10u32 + i).into()
+        }).collect::<Vec<_>>();
+        vg_graph.insert(v_stash, voters);
+    }
+    vg_graph
+}
+
 benchmarks! {
     bond {
         let stash = create_funded_user::<T>("stash",100);
@@ -202,6 +217,16 @@ benchmarks! {
     }: {
         Staking::<T>::select_and_update_validators(0);
     }
+
+    balance_vg_graph {
+        let v in 1 .. MAX_BALANCED_VALIDATORS as u32;
+        let g in 1 .. T::MaxGuarantorsPerValidator::get();
+        let mut vg_graph = create_vg_graph::<T>(v, g);
+    }: {
+        Staking::<T>::balance_vg_graph(&mut vg_graph);
+    } verify {
+        assert_eq!(vg_graph.len(), v as usize);
+    }
 }
 
 #[cfg(test)]
@@ -239,6 +264,7 @@ mod tests {
             assert_ok!(test_benchmark_cut_guarantee::<Test>());
             assert_ok!(test_benchmark_new_era::<Test>());
             assert_ok!(test_benchmark_select_and_update_validators::<Test>());
+            assert_ok!(test_benchmark_balance_vg_graph::<Test>());
             assert_ok!(test_benchmark_chill::<Test>());
             assert_ok!(test_benchmark_set_controller::<Test>());
             assert_ok!(test_benchmark_withdraw_unbonded::<Test>());
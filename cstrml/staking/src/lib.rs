@@ -19,8 +19,8 @@ use codec::{Decode, Encode, HasCompact};
 use total_stake_limit_ratio::total_stake_limit_ratio;
 use frame_support::{
     decl_module, decl_event, decl_storage, ensure, decl_error,
-    storage::IterableStorageMap,
-    weights::{Weight, constants::{WEIGHT_PER_MICROS, WEIGHT_PER_NANOS}},
+    storage::{IterableStorageMap, migration::StorageIterator},
+    weights::{Weight, DispatchClass, constants::{WEIGHT_PER_MICROS, WEIGHT_PER_NANOS}},
     traits::{
         Currency, LockIdentifier, LockableCurrency, WithdrawReasons, OnUnbalanced, Imbalance, Get,
         UnixTime, EnsureOrigin, Randomness
@@ -29,7 +29,7 @@ use frame_support::{
 };
 use pallet_session::historical;
 use sp_runtime::{
-    Perbill, Permill, RuntimeDebug, SaturatedConversion, ModuleId,
+    Perbill, Permill, PerThing, RuntimeDebug, SaturatedConversion, ModuleId,
     traits::{
         Convert, Zero, One, StaticLookup, Saturating, AtLeast32Bit,
         CheckedAdd, CheckedSub, AtLeast32BitUnsigned
@@ -52,13 +52,15 @@ pub mod weight;
 use swork;
 use primitives::{
     EraIndex,
-    constants::{currency::*, time::*, staking::*},
+    constants::{currency::*, time::*},
     traits::{UsableCurrency, MarketInterface, BenefitInterface}
 };
 
 const DEFAULT_MINIMUM_VALIDATOR_COUNT: u32 = 4;
 const MAX_UNLOCKING_CHUNKS: usize = 32;
+const MAX_AUTHORING_REWARD_RATIO_TIERS: usize = 16;
 const MAX_GUARANTEE: usize = 16;
+const MAX_BALANCED_VALIDATORS: usize = 128;
 const STAKING_ID: LockIdentifier = *b"staking ";
 
 pub(crate) const LOG_TARGET: &'static str = "staking";
@@ -88,11 +90,15 @@ pub trait WeightInfo {
     // The following two doesn't used to generate weight info
     fn new_era(v: u32, n: u32, m: u32, ) -> Weight;
     fn select_and_update_validators(v: u32, n: u32, m: u32, ) -> Weight;
+    fn balance_vg_graph(v: u32, g: u32, ) -> Weight;
 }
 
 /// Counter for the number of "reward" points earned by a given validator.
 pub type RewardPoint = u32;
 
+/// The index of a page of the validator's exposure.
+pub type PageIndex = u32;
+
 /// Reward points of an era. Used to split era total payout between validators.
 ///
 /// This points will be used to reward validators and their respective guarantors.
@@ -178,6 +184,97 @@ pub struct Guarantee<AccountId, Balance: HasCompact> {
     pub suppressed: bool,
 }
 
+/// A guarantee whose votes are spread across the preferred validators at the end of each era.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+pub struct GuaranteePreference<AccountId, Balance: HasCompact> {
+    /// The preferred targets(validators) in order, this vector's element is unique.
+    pub targets: Vec<AccountId>,
+    /// The total votes to be spread.
+    #[codec(compact)]
+    pub total: Balance,
+    /// The era the preference was submitted.
+    pub submitted_in: EraIndex,
+}
+
+/// A pending unapplied slash which touches a stash.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+pub struct PendingSlash<AccountId, Balance: HasCompact> {
+    /// The era at which the slash will be applied.
+    pub apply_at: EraIndex,
+    /// The stash ID of the offending validator.
+    pub validator: AccountId,
+    /// The amount to be slashed from the stash.
+    #[codec(compact)]
+    pub value: Balance,
+}
+
+/// A slashing span of a stash and the amount slashed in it.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
+pub struct SlashingSpanInfo<Balance: HasCompact> {
+    /// The index of the span.
+    pub index: slashing::SpanIndex,
+    /// The start era of the span.
+    pub start: EraIndex,
+    /// The length of the span, `None` means it's the ongoing span.
+    pub length: Option<EraIndex>,
+    /// The value of stash balance slashed in the span.
+    #[codec(compact)]
+    pub slashed: Balance,
+    /// The value of the slashing reward paid out in the span.
+    #[codec(compact)]
+    pub paid_out: Balance,
+}
+
+/// The slashing status of a stash, including validator and guarantor side.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, Default, RuntimeDebug)]
+pub struct SlashingReport<AccountId, Balance: HasCompact> {
+    /// All the pending unapplied slashes which touch the stash.
+    pub pending_slashes: Vec<PendingSlash<AccountId, Balance>>,
+    /// The slashing spans of the stash, most recent first.
+    pub spans: Vec<SlashingSpanInfo<Balance>>,
+    /// The last era at which a non-zero slash occurred.
+    pub last_nonzero_slash: EraIndex,
+    /// The guarantees of the stash whose targets have pending unapplied slashes, including the
+    /// votes spread from the guarantee preference in the planned era.
+    pub guarantees_at_risk: Vec<IndividualExposure<AccountId, Balance>>,
+}
+
+/// The projected rewards of an era, `inflation + extra + market = authoring + staking = total`.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, Default, RuntimeDebug)]
+pub struct EraRewardProjection<Balance: HasCompact> {
+    /// The era index.
+    pub era: EraIndex,
+    /// The inflation rewards, excluding the extra rewards.
+    #[codec(compact)]
+    pub inflation: Balance,
+    /// The extra rewards due to low effective staking ratio.
+    #[codec(compact)]
+    pub extra: Balance,
+    /// The rewards from the market staking pot.
+    #[codec(compact)]
+    pub market: Balance,
+    /// The part of total rewards for block authoring.
+    #[codec(compact)]
+    pub authoring: Balance,
+    /// The part of total rewards for staking.
+    #[codec(compact)]
+    pub staking: Balance,
+    /// The total rewards of the era.
+    #[codec(compact)]
+    pub total: Balance,
+}
+
+/// The projected rewards of the coming eras and the yearly reward curve they cover.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, Default, RuntimeDebug)]
+pub struct RewardProjection<Balance: HasCompact> {
+    /// The number of eras in a reward year.
+    pub year_in_eras: u64,
+    /// The projected rewards of each era.
+    pub eras: Vec<EraRewardProjection<Balance>>,
+    /// The inflation rewards (including the extra rewards) of each reward year covered by `eras`.
+    pub yearly_rewards: Vec<(u64, Balance)>,
+}
+
 /// Just a Balance/BlockNumber tuple to encode when a chunk of funds will be unlocked.
 #[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
 pub struct UnlockChunk<Balance: HasCompact> {
@@ -205,9 +302,9 @@ pub struct StakingLedger<AccountId, Balance: HasCompact> {
     /// Any balance that is becoming free, which may eventually be transferred out
     /// of the stash (assuming it doesn't get slashed first).
     pub unlocking: Vec<UnlockChunk<Balance>>,
-    /// List of eras for which the stakers behind a validator and guarantor have claimed rewards.
-    /// Only updated for validators.
-    pub claimed_rewards: Vec<EraIndex>,
+    /// List of eras and exposure pages for which the stakers behind a validator and guarantor
+    /// have claimed rewards. Only updated for validators.
+    pub claimed_rewards: Vec<(EraIndex, PageIndex)>,
 }
 
 impl<AccountId, Balance: HasCompact + Copy + Saturating + AtLeast32BitUnsigned> StakingLedger<AccountId, Balance> {
@@ -454,6 +551,11 @@ pub trait Config: frame_system::Config {
     /// their reward. This used to limit the i/o cost for the guarantor payout.
     type MaxGuarantorRewardedPerValidator: Get<u32>;
 
+    /// The maximum number of guarantors backing one validator in an era.
+    ///
+    /// The smallest guarantees of a validator beyond this number are evicted at the end of era.
+    type MaxGuarantorsPerValidator: Get<u32>;
+
     /// Number of eras that slashes are deferred by, after computation. This
     /// should be less than the bonding duration. Set to 0 if slashes should be
     /// applied immediately, without opportunity for intervention.
@@ -462,6 +564,14 @@ pub trait Config: frame_system::Config {
     /// The origin which can cancel a deferred slash. Root can always do this.
     type SlashCancelOrigin: EnsureOrigin<Self::Origin>;
 
+    /// The origin which can update the inflation curve and the authoring reward ratio.
+    /// Root can always do this.
+    type TokenomicsOrigin: EnsureOrigin<Self::Origin>;
+
+    /// Whether the guarantees submitted before the most recent slash of their target
+    /// validators are filtered out in the election.
+    type FilterSlashedGuarantees: Get<bool>;
+
     /// Interface for interacting with a session module.
     type SessionInterface: self::SessionInterface<Self::AccountId>;
 
@@ -480,6 +590,15 @@ pub trait Config: frame_system::Config {
     /// Used for bonding buffer
     type UncheckedFrozenBondFund: Get<BalanceOf<Self>>;
 
+    /// The maximum commission increase of a validator in one change. The commission is the
+    /// part of rewards not shared with guarantors, which is `1 - ValidatorPrefs.fee`.
+    /// Only one change can be announced at a time, so it's applied at most once in
+    /// `CommissionIncreaseDelay` eras.
+    type MaxCommissionIncreasePerChange: Get<Perbill>;
+
+    /// Number of full eras that a commission increase must be announced before it's applied.
+    type CommissionIncreaseDelay: Get<EraIndex>;
+
     /// Weight information for extrinsics in this pallet.
     type WeightInfo: WeightInfo;
 }
@@ -504,6 +623,41 @@ impl Default for Forcing {
     }
 }
 
+// A value placed in storage that represents the current version of the Staking storage.
+// This value is used by the `on_runtime_upgrade` logic to determine whether we run
+// storage migration logic.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+enum Releases {
+    V1_0_0,
+    V2_0_0,
+}
+
+impl Default for Releases {
+    fn default() -> Self {
+        Releases::V1_0_0
+    }
+}
+
+/// Mode of selecting the elected validators among the candidates.
+#[derive(Copy, Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum ElectionMode {
+    /// Elect the top `to_elect` candidates by their `valid` stakes.
+    TopDown,
+    /// Randomly elect `to_elect` validators from the top `2 * to_elect` candidates,
+    /// the chance of each candidate is weighted by its `valid` stakes.
+    RandomWeighted,
+    /// Rebalance the guarantees into the stake limit of validators to maximise
+    /// the total `valid` stakes, then elect the top `to_elect` candidates.
+    Balanced,
+}
+
+impl Default for ElectionMode {
+    fn default() -> Self {
+        ElectionMode::TopDown
+    }
+}
+
 decl_storage! {
     trait Store for Module<T: Config> as Staking {
         /// Number of eras to keep in history.
@@ -514,6 +668,28 @@ decl_storage! {
         /// Start era for reward curve
         StartRewardEra get(fn start_reward_era) config(): EraIndex = 100000;
 
+        /// The inflation rewards in the first reward year.
+        FirstYearRewards get(fn first_year_rewards) config(): BalanceOf<T> =
+            BalanceOf::<T>::saturated_from(5_000_000 * CRUS);
+
+        /// The ratio of the rewards of a reward year to the previous one.
+        RewardDecreaseRatio get(fn reward_decrease_ratio) config(): Perbill = Perbill::from_percent(88);
+
+        /// The minimal yearly inflation rewards in ratio of the total issuance.
+        MinRewardRatio get(fn min_reward_ratio) config(): Perbill = Perbill::from_perthousand(28);
+
+        /// The reward year from which the extra rewards for low effective staking ratio are supplied.
+        ExtraRewardStartYear get(fn extra_reward_start_year) config(): u64 = 4;
+
+        /// The ratio of total rewards for block authoring, in tiers of the minimal number of validators.
+        AuthoringRewardRatioTiers get(fn authoring_reward_ratio_tiers) config(): Vec<(u32, Perbill)> = vec![
+            (0, Perbill::from_percent(20)),
+            (501, Perbill::from_percent(25)),
+            (1001, Perbill::from_percent(30)),
+            (2501, Perbill::from_percent(40)),
+            (5001, Perbill::from_percent(50)),
+        ];
+
         /// Map from all locked "stash" accounts to the controller account.
         pub Bonded get(fn bonded): map hasher(twox_64_concat) T::AccountId => Option<T::AccountId>;
 
@@ -533,6 +709,11 @@ decl_storage! {
         Guarantors get(fn guarantors):
             map hasher(twox_64_concat) T::AccountId => Option<Guarantee<T::AccountId, BalanceOf<T>>>;
 
+        /// The map from guarantor stash key to the preferred validators, the votes are spread across
+        /// these validators' stake limit headroom in order at the end of each era.
+        GuaranteePreferences get(fn guarantee_preferences):
+            map hasher(twox_64_concat) T::AccountId => Option<GuaranteePreference<T::AccountId, BalanceOf<T>>>;
+
         /// The stake limit, determined all the staking operations
         /// This is keyed by the stash account.
         pub StakeLimit get(fn stake_limit):
@@ -548,20 +729,28 @@ decl_storage! {
             double_map hasher(twox_64_concat) EraIndex, hasher(twox_64_concat) T::AccountId
             => Exposure<T::AccountId, BalanceOf<T>>;
 
-        /// Clipped Exposure of validator at era.
+        /// Paged Exposure of validator at era.
         ///
-        /// This is similar to [`ErasStakers`] but number of guarantors exposed is reduced to the
-        /// `T::MaxGuarantorRewardedPerValidator` biggest stakers.
-        /// (Note: the field `total` and `own` of the exposure remains unchanged).
-        /// This is used to limit the i/o cost for the guarantor payout.
+        /// This is similar to [`ErasStakers`] but guarantors are sorted by their stakes and split
+        /// into pages, each page has at most `T::MaxGuarantorRewardedPerValidator` guarantors.
+        /// (Note: the field `total` and `own` of the exposure remains unchanged in all the pages).
+        /// This is used to limit the i/o cost for each guarantor payout.
         ///
-        /// This is keyed fist by the era index to allow bulk deletion and then the stash account.
+        /// This is keyed fist by the era index to allow bulk deletion and then the stash account
+        /// with the page index.
         ///
         /// Is it removed after `HISTORY_DEPTH` eras.
         /// If stakers hasn't been set or has been removed then empty exposure is returned.
-        pub ErasStakersClipped get(fn eras_stakers_clipped):
-        double_map hasher(twox_64_concat) EraIndex, hasher(twox_64_concat) T::AccountId
+        pub ErasStakersPaged get(fn eras_stakers_paged):
+        double_map hasher(twox_64_concat) EraIndex, hasher(twox_64_concat) (T::AccountId, PageIndex)
         => Exposure<T::AccountId, BalanceOf<T>>;
+
+        /// The number of pages of the validator's paged exposure at era.
+        ///
+        /// Is it removed after `HISTORY_DEPTH` eras.
+        pub ErasStakersPageCount get(fn eras_stakers_page_count):
+        double_map hasher(twox_64_concat) EraIndex, hasher(twox_64_concat) T::AccountId
+        => PageIndex;
             
         /// Similar to `ErasStakers`, this holds the preferences of validators.
         ///
@@ -672,6 +861,33 @@ decl_storage! {
 
         /// Force Selection
         ForceSelection get(fn force_selection): bool = false;
+
+        /// The announced commission increase of validators, applied at the given era.
+        PendingCommissions get(fn pending_commissions):
+            map hasher(twox_64_concat) T::AccountId => Option<(ValidatorPrefs, EraIndex)>;
+
+        /// The last effective prefs of chilled validators, which limit the commission when they validate again.
+        LastValidatorPrefs get(fn last_validator_prefs):
+            map hasher(twox_64_concat) T::AccountId => Option<ValidatorPrefs>;
+
+        /// Storage version of the pallet.
+        ///
+        /// This is set to v2.0.0 for new networks.
+        StorageVersion build(|_: &GenesisConfig<T>| Releases::V2_0_0): Releases;
+
+        /// The way to elect validators from the candidates at the end of each era.
+        ElectionMode get(fn election_mode) config(): ElectionMode;
+
+        /// The weight given to the newest workload when smoothing the workloads of each stash,
+        /// an `alpha` of `2 / (N + 1)` roughly averages the workloads over the last N report slots.
+        WorkloadSmoothingFactor get(fn workload_smoothing_factor) config(): Perbill = Perbill::one();
+
+        /// The exponential moving average of the workload of each stash.
+        WorkloadEma get(fn workload_ema):
+            map hasher(twox_64_concat) T::AccountId => Option<u128>;
+
+        /// The exponential moving average of the total workload.
+        TotalWorkloadEma get(fn total_workload_ema): Option<u128>;
     }
     add_extra_genesis {
         config(stakers):
@@ -744,14 +960,32 @@ decl_event!(
         Withdrawn(AccountId, Balance),
         /// An account has called `validate` and set guarantee fee.
         ValidateSuccess(AccountId, ValidatorPrefs),
+        /// A validator has announced a commission increase, which will be applied at the era.
+        CommissionChangeAnnounced(AccountId, ValidatorPrefs, EraIndex),
+        /// An announced commission increase of the validator has been applied.
+        CommissionChangeApplied(AccountId, ValidatorPrefs),
         /// An account has called `guarantee` and vote for one validator.
         GuaranteeSuccess(AccountId, AccountId, Balance),
         /// An account has called `cut_guarantee` and cut vote for one validator.
         CutGuaranteeSuccess(AccountId, AccountId, Balance),
+        /// A guarantee has been evicted since the validator exceeds the maximum guarantors.
+        /// [guarantor, validator, votes]
+        GuaranteeEvicted(AccountId, AccountId, Balance),
+        /// An account has called `guarantee_with_preference` and vote for a list of validators.
+        GuaranteePreferenceSuccess(AccountId, Balance),
         /// An account has been chilled from its stash
         ChillSuccess(AccountId, AccountId),
         /// Update the identities success. The stake limit of each identity would be updated.
         UpdateStakeLimitSuccess(u32),
+        /// The election mode has been updated.
+        SetElectionModeSuccess(ElectionMode),
+        /// The workload smoothing factor has been updated.
+        SetWorkloadSmoothingFactorSuccess(Perbill),
+        /// The inflation curve has been updated.
+        /// [first_year_rewards, reward_decrease_ratio, min_reward_ratio, extra_reward_start_year]
+        SetInflationCurveSuccess(Balance, Perbill, Perbill, u64),
+        /// The authoring reward ratio tiers have been updated.
+        SetAuthoringRewardRatioTiersSuccess(Vec<(u32, Perbill)>),
     }
 );
 
@@ -786,12 +1020,23 @@ decl_error! {
         InvalidEraToReward,
         /// Claimed reward twice.
         AlreadyClaimed,
+        /// The exposure page does not exist.
+        InvalidPage,
+        /// The workload smoothing factor can not be zero.
+        InvalidSmoothingFactor,
+        /// The first year rewards and the reward decrease ratio can not be zero.
+        InvalidInflationCurve,
+        /// The authoring reward ratio tiers should start from zero validators and be strictly
+        /// increasing, with at most `MAX_AUTHORING_REWARD_RATIO_TIERS` tiers.
+        InvalidAuthoringRewardRatioTiers,
         /// Don't have enough balance to recharge the staking pot
         InsufficientCurrency,
         /// Can not rebond without unlocking chunks.
         NoUnlockChunk,
         /// Staking locks need to be the maximum locks
         InsufficientFrozenBond,
+        /// The commission increase exceeds the maximum increase per change.
+        CommissionIncreaseTooLarge,
     }
 }
 
@@ -816,6 +1061,9 @@ decl_module! {
         /// their reward. This used to limit the i/o cost for the guarantor payout.
         const MaxGuarantorRewardedPerValidator: u32 = T::MaxGuarantorRewardedPerValidator::get();
 
+        /// The maximum number of guarantors backing one validator in an era.
+        const MaxGuarantorsPerValidator: u32 = T::MaxGuarantorsPerValidator::get();
+
         /// The staking's module id, used for deriving its sovereign account ID.
         const ModuleId: ModuleId = T::ModuleId::get();
 
@@ -827,10 +1075,24 @@ decl_module! {
 
         const UncheckedFrozenBondFund: BalanceOf<T> = T::UncheckedFrozenBondFund::get();
 
+        /// The maximum commission increase of a validator in one change.
+        const MaxCommissionIncreasePerChange: Perbill = T::MaxCommissionIncreasePerChange::get();
+
+        /// Number of full eras that a commission increase must be announced before it's applied.
+        const CommissionIncreaseDelay: EraIndex = T::CommissionIncreaseDelay::get();
+
         type Error = Error<T>;
 
         fn deposit_event() = default;
 
+        fn on_runtime_upgrade() -> Weight {
+            if StorageVersion::get() == Releases::V1_0_0 {
+                Self::migrate_to_paged_exposures()
+            } else {
+                0
+            }
+        }
+
         fn on_finalize() {
             // Set the start of the first era.
             if let Some(mut active_era) = Self::active_era() {
@@ -907,7 +1169,7 @@ decl_module! {
                 total: value,
                 active: value,
                 unlocking: vec![],
-                claimed_rewards: (last_reward_era..current_era).collect(),
+                claimed_rewards: (last_reward_era..current_era).map(|era| (era, 0)).collect(),
             };
             Self::update_ledger(&controller, &item);
         }
@@ -998,6 +1260,10 @@ decl_module! {
                 ensure!(guarantee.total < ledger.active, Error::<T>::AllGuaranteed);
                 value = value.min(ledger.active - guarantee.total);
             }
+            if let Some(preference) = Self::guarantee_preferences(&ledger.stash) {
+                ensure!(preference.total < ledger.active, Error::<T>::AllGuaranteed);
+                value = value.min(ledger.active - preference.total);
+            }
 
             // 2. Ensure value < ledger.active
             value = value.min(ledger.active);
@@ -1112,7 +1378,9 @@ decl_module! {
 
         /// Declare the desire to validate for the origin controller.
         ///
-        /// Effects will be felt at the beginning of the next era.
+        /// Effects will be felt at the beginning of the next era. A lower `fee` means a higher
+        /// commission, which cannot be increased more than `MaxCommissionIncreasePerChange` at once
+        /// and will only be applied after `CommissionIncreaseDelay` full eras.
         ///
         /// The dispatch origin for this call must be _Signed_ by the controller, not the stash.
         ///
@@ -1122,22 +1390,50 @@ decl_module! {
         /// - Writes are limited to the `origin` account key.
         /// -----------
         /// DB Weight:
-        /// - Read: Ledger, StakeLimit
-        /// - Write: Guarantors, Validators
+        /// - Read: Ledger, Validators, ErasValidatorPrefs, Current Era
+        /// - Write: Guarantors, Validators, PendingCommissions, LastValidatorPrefs
         /// # </weight>
         #[weight = T::WeightInfo::validate()]
         fn validate(origin, prefs: ValidatorPrefs) {
             let controller = ensure_signed(origin)?;
             let ledger = Self::ledger(&controller).ok_or(Error::<T>::NotController)?;
             let v_stash = &ledger.stash;
+
+            // 1. Get the effective prefs, which is the current one, the last one before chilled or the one in the ongoing era
+            let maybe_effective_prefs = if <Validators<T>>::contains_key(v_stash) {
+                Some(Self::validators(v_stash))
+            } else if let Some(last_prefs) = Self::last_validator_prefs(v_stash) {
+                Some(last_prefs)
+            } else {
+                Self::active_era()
+                    .filter(|active_era| <ErasValidatorPrefs<T>>::contains_key(&active_era.index, v_stash))
+                    .map(|active_era| Self::eras_validator_prefs(&active_era.index, v_stash))
+            };
+
+            // 2. Commission increase should be limited and announced
+            if let Some(effective_prefs) = maybe_effective_prefs.filter(|p| p.fee > prefs.fee) {
+                ensure!(
+                    effective_prefs.fee.saturating_sub(prefs.fee) <= T::MaxCommissionIncreasePerChange::get(),
+                    Error::<T>::CommissionIncreaseTooLarge
+                );
+                let apply_at = Self::current_era().unwrap_or(0)
+                    .saturating_add(T::CommissionIncreaseDelay::get())
+                    .saturating_add(1);
+                <Guarantors<T>>::remove(v_stash);
+                <GuaranteePreferences<T>>::remove(v_stash);
+                <LastValidatorPrefs<T>>::remove(v_stash);
+                <Validators<T>>::insert(v_stash, &effective_prefs);
+                <PendingCommissions<T>>::insert(v_stash, (prefs.clone(), apply_at));
+                Self::deposit_event(RawEvent::CommissionChangeAnnounced(v_stash.clone(), prefs, apply_at));
+                return Ok(());
+            }
+
+            // 3. Commission decrease or new validator takes effect directly
             <Guarantors<T>>::remove(v_stash);
+            <GuaranteePreferences<T>>::remove(v_stash);
+            <PendingCommissions<T>>::remove(v_stash);
+            <LastValidatorPrefs<T>>::remove(v_stash);
             <Validators<T>>::insert(v_stash, &prefs);
-            // Set the validator pref to 100% for the ongoing era as the punishment
-            if let Some(active_era) = Self::active_era() {
-                if <ErasValidatorPrefs<T>>::get(&active_era.index, &v_stash).fee > prefs.fee {
-                    <ErasValidatorPrefs<T>>::insert(&active_era.index, &v_stash, ValidatorPrefs { fee: Perbill::one() });
-                }
-            }
             Self::deposit_event(RawEvent::ValidateSuccess(controller, prefs));
         }
 
@@ -1176,11 +1472,61 @@ decl_module! {
             ensure!(guarantee.is_some(), Error::<T>::ExceedGuaranteeLimit);
             let guarantee = guarantee.unwrap();
 
-            <Validators<T>>::remove(g_stash);
+            Self::remove_validator(g_stash);
+            <GuaranteePreferences<T>>::remove(g_stash);
             <Guarantors<T>>::insert(g_stash, guarantee);
             Self::deposit_event(RawEvent::GuaranteeSuccess(controller, v_stash, votes));
         }
 
+        /// Declare the desire to guarantee a list of preferred `targets` with `total` votes for the origin controller.
+        /// This would replace the former guarantees.
+        ///
+        /// At the end of each era, the votes would be spread across the targets in order,
+        /// each target takes the votes until its stake limit is filled up.
+        ///
+        /// The dispatch origin for this call must be _Signed_ by the controller, not the stash.
+        ///
+        /// # <weight>
+        /// - The transaction's complexity is proportional to the size of `targets`,
+        /// which is bounded by `MAX_GUARANTEE`.
+        /// ---------
+        /// DB Weight:
+        /// - Reads: Ledger, Validators, Current Era
+        /// - Writes: Validators, Guarantors, GuaranteePreferences, PendingCommissions, LastValidatorPrefs
+        /// # </weight>
+        #[weight = T::WeightInfo::guarantee()]
+        fn guarantee_with_preference(origin, targets: Vec<<T::Lookup as StaticLookup>::Source>, total: BalanceOf<T>) {
+            // 1. Get ledger
+            let controller = ensure_signed(origin)?;
+            let ledger = Self::ledger(&controller).ok_or(Error::<T>::NotController)?;
+            let g_stash = &ledger.stash;
+
+            // 2. Targets should be legal and unique
+            ensure!(!targets.is_empty(), Error::<T>::InvalidTarget);
+            ensure!(targets.len() <= MAX_GUARANTEE, Error::<T>::ExceedGuaranteeLimit);
+            let mut v_stashes: Vec<T::AccountId> = vec![];
+            for target in targets {
+                let v_stash = T::Lookup::lookup(target)?;
+                ensure!(<Validators<T>>::contains_key(&v_stash), Error::<T>::InvalidTarget);
+                ensure!(!v_stashes.contains(&v_stash), Error::<T>::InvalidTarget);
+                v_stashes.push(v_stash);
+            }
+
+            // 3. Votes value should greater than the dust
+            ensure!(total > T::Currency::minimum_balance(), Error::<T>::InsufficientValue);
+            let total = total.min(ledger.active);
+
+            // 4. Replace the former guarantees
+            Self::remove_validator(g_stash);
+            <Guarantors<T>>::remove(g_stash);
+            <GuaranteePreferences<T>>::insert(g_stash, GuaranteePreference {
+                targets: v_stashes,
+                total,
+                submitted_in: Self::current_era().unwrap_or(0),
+            });
+            Self::deposit_event(RawEvent::GuaranteePreferenceSuccess(controller, total));
+        }
+
         /// Declare the desire to cut guarantee for the origin controller.
         ///
         /// Effects will be felt at the beginning of the next era.
@@ -1275,19 +1621,21 @@ decl_module! {
             }
         }
 
-        /// Pay out all the stakers behind a single validator for a single era.
+        /// Pay out one page of the stakers behind a single validator for a single era.
         ///
-        /// - `validator_stash` is the stash account of the validator. Their guarantors, up to
-        ///   `T::MaxGuarantorRewardedPerValidator`, will also receive their rewards.
+        /// - `validator_stash` is the stash account of the validator. Their guarantors in the
+        ///   `page`, up to `T::MaxGuarantorRewardedPerValidator`, will also receive their rewards.
         /// - `era` may be any era between `[current_era - history_depth; current_era]`.
+        /// - `page` may be any page less than `ErasStakersPageCount`, each page can only be
+        ///   claimed once.
         ///
         /// The origin of this call must be _Signed_. Any account can call this function, even if
         /// it is not one of the stakers.
         /// TODO: Add weight for this one
         #[weight = 120 * WEIGHT_PER_MICROS]
-        fn reward_stakers(origin, validator_stash: T::AccountId, era: EraIndex) -> DispatchResult {
+        fn reward_stakers(origin, validator_stash: T::AccountId, era: EraIndex, page: PageIndex) -> DispatchResult {
             ensure_signed(origin)?;
-            Self::do_reward_stakers(validator_stash, era)
+            Self::do_reward_stakers(validator_stash, era, page)
         }
 
         // ----- Root Calls ------
@@ -1471,6 +1819,57 @@ decl_module! {
             T::Currency::remove_lock(STAKING_ID, &stash);
         }
 
+        /// Update the inflation curve.
+        ///
+        /// Can be called by the `T::TokenomicsOrigin`.
+        #[weight = T::DbWeight::get().writes(4)]
+        fn set_inflation_curve(
+            origin,
+            #[compact] first_year_rewards: BalanceOf<T>,
+            reward_decrease_ratio: Perbill,
+            min_reward_ratio: Perbill,
+            extra_reward_start_year: u64
+        ) {
+            T::TokenomicsOrigin::try_origin(origin)
+                .map(|_| ())
+                .or_else(ensure_root)?;
+            ensure!(
+                !first_year_rewards.is_zero() && !reward_decrease_ratio.is_zero(),
+                Error::<T>::InvalidInflationCurve
+            );
+
+            <FirstYearRewards<T>>::put(first_year_rewards);
+            RewardDecreaseRatio::put(reward_decrease_ratio);
+            MinRewardRatio::put(min_reward_ratio);
+            ExtraRewardStartYear::put(extra_reward_start_year);
+            Self::deposit_event(RawEvent::SetInflationCurveSuccess(
+                first_year_rewards,
+                reward_decrease_ratio,
+                min_reward_ratio,
+                extra_reward_start_year
+            ));
+        }
+
+        /// Update the authoring reward ratio tiers, each tier is the minimal number of
+        /// validators and the ratio of total rewards for block authoring.
+        ///
+        /// Can be called by the `T::TokenomicsOrigin`.
+        #[weight = T::DbWeight::get().writes(1)]
+        fn set_authoring_reward_ratio_tiers(origin, tiers: Vec<(u32, Perbill)>) {
+            T::TokenomicsOrigin::try_origin(origin)
+                .map(|_| ())
+                .or_else(ensure_root)?;
+            ensure!(
+                tiers.len() <= MAX_AUTHORING_REWARD_RATIO_TIERS &&
+                tiers.first().map_or(false, |(min_validators, _)| min_validators.is_zero()) &&
+                tiers.windows(2).all(|w| w[0].0 < w[1].0),
+                Error::<T>::InvalidAuthoringRewardRatioTiers
+            );
+
+            AuthoringRewardRatioTiers::put(tiers.clone());
+            Self::deposit_event(RawEvent::SetAuthoringRewardRatioTiersSuccess(tiers));
+        }
+
         // TODO: Remove it after the main net reward start
         #[weight = 1000]
         fn set_start_reward_era(origin, start_reward_era: EraIndex) {
@@ -1495,12 +1894,154 @@ decl_module! {
             ensure_root(origin)?;
             ForceSelection::put(enable);
         }
+
+        /// Set the way to elect validators at the end of each era.
+        ///
+        /// The dispatch origin must be Root.
+        #[weight = 1000]
+        fn set_election_mode(origin, mode: ElectionMode) {
+            ensure_root(origin)?;
+            <ElectionMode>::put(mode);
+            Self::deposit_event(RawEvent::SetElectionModeSuccess(mode));
+        }
+
+        /// Set the weight given to the newest workload when calculating the stake limit.
+        ///
+        /// The dispatch origin must be Root.
+        #[weight = 1000]
+        fn set_workload_smoothing_factor(origin, factor: Perbill) {
+            ensure_root(origin)?;
+            ensure!(!factor.is_zero(), Error::<T>::InvalidSmoothingFactor);
+            <WorkloadSmoothingFactor>::put(factor);
+            Self::deposit_event(RawEvent::SetWorkloadSmoothingFactorSuccess(factor));
+        }
     }
 }
 
 impl<T: Config> Module<T> {
     // PUBLIC IMMUTABLES
 
+    /// The slashing status of a stash, including the pending unapplied slashes, the slashing
+    /// span history and the guarantees at risk. Used by the runtime api.
+    pub fn slashing_report(stash: &T::AccountId) -> SlashingReport<T::AccountId, BalanceOf<T>> {
+        // 1. Collect the pending slashes touch the stash and all the offenders
+        let mut pending_slashes = vec![];
+        let mut offenders = vec![];
+        if let Some(earliest_era) = <Self as Store>::EarliestUnappliedSlash::get() {
+            let active_era = Self::active_era().map(|e| e.index).unwrap_or(Zero::zero());
+            // Slashes reported in `era` are applied at the beginning of `era + SlashDeferDuration + 1`
+            let apply_delay = T::SlashDeferDuration::get().saturating_add(1);
+            for era in earliest_era..=active_era {
+                for slash in <Self as Store>::UnappliedSlashes::get(&era) {
+                    let value = if &slash.validator == stash {
+                        Some(slash.own)
+                    } else {
+                        slash.others.iter().find(|(who, _)| who == stash).map(|(_, value)| *value)
+                    };
+                    if let Some(value) = value {
+                        pending_slashes.push(PendingSlash {
+                            apply_at: era.saturating_add(apply_delay),
+                            validator: slash.validator.clone(),
+                            value
+                        });
+                    }
+                    offenders.push(slash.validator);
+                }
+            }
+        }
+
+        // 2. Get the current guarantees whose targets are going to be slashed
+        let mut guarantees_at_risk: Vec<IndividualExposure<T::AccountId, BalanceOf<T>>> = Self::guarantors(stash).map(|guarantee| {
+            guarantee.targets.into_iter()
+                .filter(|target| offenders.contains(&target.who))
+                .collect()
+        }).unwrap_or_default();
+
+        // 3. Get the exposures of the planned era on the rest offenders, which contain the votes
+        // spread from the guarantee preference
+        if let Some(current_era) = Self::current_era() {
+            offenders.sort();
+            offenders.dedup();
+            for offender in offenders.iter() {
+                if guarantees_at_risk.iter().any(|g| &g.who == offender) {
+                    continue;
+                }
+                for page in 0..Self::eras_stakers_page_count(current_era, offender) {
+                    let exposure = Self::eras_stakers_paged(current_era, (offender.clone(), page));
+                    if let Some(ie) = exposure.others.iter().find(|ie| &ie.who == stash) {
+                        guarantees_at_risk.push(IndividualExposure {
+                            who: offender.clone(),
+                            value: ie.value
+                        });
+                        break;
+                    }
+                }
+            }
+        }
+
+        SlashingReport {
+            pending_slashes,
+            spans: slashing::span_history::<T>(stash),
+            last_nonzero_slash: <Self as Store>::SlashingSpans::get(stash)
+                .map(|spans| spans.last_nonzero_slash())
+                .unwrap_or(Zero::zero()),
+            guarantees_at_risk
+        }
+    }
+
+    /// Project the rewards of the next `eras` eras from the active era, based on the current
+    /// total issuance, effective staking ratio, elected validators and market staking pot.
+    /// The benefit fee reductions are not deducted. Used by the runtime api.
+    pub fn reward_projection(eras: EraIndex) -> RewardProjection<BalanceOf<T>> {
+        let active_era = Self::active_era().map(|e| e.index).unwrap_or(Zero::zero());
+        let year_in_eras = Self::year_in_eras();
+        let total_issuance = TryInto::<u128>::try_into(T::Currency::total_issuance())
+            .ok()
+            .unwrap();
+        let authoring_ratio = Self::get_authoring_and_staking_reward_ratio(Self::current_elected().len() as u32);
+
+        // The market staking pot would be split into the following `duration` eras at the end of the active era
+        let duration = T::MarketStakingPotDuration::get();
+        let pot_payout_per_era = Perbill::from_rational_approximation(1, duration) * T::MarketStakingPot::staking_pot_balance();
+
+        let mut projection = RewardProjection { year_in_eras, ..Default::default() };
+        for era in active_era..active_era.saturating_add(eras) {
+            // 1. Inflation and extra rewards
+            let (inflation_with_extra, extra) = Self::inflation_rewards_in_era(era);
+            let extra: BalanceOf<T> = extra.saturated_into();
+            let inflation = BalanceOf::<T>::saturated_from(inflation_with_extra).saturating_sub(extra);
+
+            // 2. Market rewards
+            let mut market = Self::eras_market_payout(era).unwrap_or_default();
+            if era.saturating_sub(active_era) < duration {
+                market = market.saturating_add(pot_payout_per_era);
+            }
+
+            // 3. Split the total rewards into authoring and staking
+            let total = inflation.saturating_add(extra).saturating_add(market);
+            let authoring = authoring_ratio * total;
+            projection.eras.push(EraRewardProjection {
+                era,
+                inflation,
+                extra,
+                market,
+                authoring,
+                staking: total.saturating_sub(authoring),
+                total,
+            });
+
+            // 4. Record the yearly rewards of each reward year
+            if era >= Self::start_reward_era() {
+                let year_num = era.saturating_sub(Self::start_reward_era()) as u64 / year_in_eras;
+                if projection.yearly_rewards.last().map_or(true, |(year, _)| *year != year_num) {
+                    let (rewards_this_year, _) = Self::rewards_in_year(year_num, total_issuance);
+                    projection.yearly_rewards.push((year_num, rewards_this_year.saturated_into()));
+                }
+            }
+        }
+        projection
+    }
+
     /// The total balance that can be slashed from a stash account as of right now.
     pub fn slashable_balance_of(stash: &T::AccountId) -> BalanceOf<T> {
         Self::bonded(stash)
@@ -1527,6 +2068,41 @@ impl<T: Config> Module<T> {
         }
     }
 
+    /// Fold the newest workloads into the exponential moving averages and return the smoothed
+    /// workloads. Stashes missing from `workload_map` are treated as reporting zero workload.
+    fn smooth_workloads(
+        workload_map: BTreeMap<T::AccountId, u128>,
+        total_workload: u128
+    ) -> (BTreeMap<T::AccountId, u128>, u128) {
+        let alpha = Self::workload_smoothing_factor();
+        let ema = |prev: Option<u128>, current: u128| match prev {
+            Some(prev) => (alpha * current).saturating_add((Perbill::one() - alpha) * prev),
+            None => current,
+        };
+
+        let mut smoothed_map: BTreeMap<T::AccountId, u128> = <WorkloadEma<T>>::iter()
+            .map(|(who, prev)| {
+                let current = workload_map.get(&who).cloned().unwrap_or_default();
+                (who, ema(Some(prev), current))
+            })
+            .collect();
+        for (who, current) in workload_map {
+            smoothed_map.entry(who).or_insert(current);
+        }
+        for (who, workload) in smoothed_map.iter() {
+            if workload.is_zero() {
+                <WorkloadEma<T>>::remove(who);
+            } else {
+                <WorkloadEma<T>>::insert(who, workload);
+            }
+        }
+        smoothed_map.retain(|_, workload| !workload.is_zero());
+
+        let smoothed_total = ema(Self::total_workload_ema(), total_workload);
+        <TotalWorkloadEma>::put(smoothed_total);
+        (smoothed_map, smoothed_total)
+    }
+
     pub fn update_stage_one_stake_limit(workload_map: BTreeMap<T::AccountId, u128>) -> u64 {
         // In stage one, state limit / own workload is fixed to T::SPowerRatio
         let mut validators_count = 0;
@@ -1781,8 +2357,18 @@ impl<T: Config> Module<T> {
     /// Chill a stash account.
     fn chill_stash(stash: &T::AccountId) {
         <StakeLimit<T>>::remove(stash);
-        <Validators<T>>::remove(stash);
+        Self::remove_validator(stash);
         <Guarantors<T>>::remove(stash);
+        <GuaranteePreferences<T>>::remove(stash);
+    }
+
+    /// Remove a validator and keep its last effective prefs.
+    fn remove_validator(stash: &T::AccountId) {
+        if <Validators<T>>::contains_key(stash) {
+            <LastValidatorPrefs<T>>::insert(stash, Self::validators(stash));
+        }
+        <Validators<T>>::remove(stash);
+        <PendingCommissions<T>>::remove(stash);
     }
 
     /// Actually make a payment to a staker. This uses the currency's reward function
@@ -1791,9 +2377,20 @@ impl<T: Config> Module<T> {
         let dest = Self::payee(stash);
         match dest {
             RewardDestination::Controller => Self::bonded(stash).and_then(|controller| {
-                T::Currency::deposit_into_existing(&controller, amount).ok()
+                let r = T::Currency::deposit_into_existing(&controller, amount).ok();
+                // Add the payout into benefit funds if the payee opts in
+                if r.is_some() {
+                    T::BenefitInterface::maybe_compound_reward(&controller, amount);
+                }
+                r
             }),
-            RewardDestination::Stash => T::Currency::deposit_into_existing(stash, amount).ok(),
+            RewardDestination::Stash => {
+                let r = T::Currency::deposit_into_existing(stash, amount).ok();
+                if r.is_some() {
+                    T::BenefitInterface::maybe_compound_reward(stash, amount);
+                }
+                r
+            },
             RewardDestination::Staked => Self::bonded(stash)
                 .and_then(|c| Self::ledger(&c).map(|l| (c, l)))
                 .and_then(|(controller, mut l)| {
@@ -1804,7 +2401,9 @@ impl<T: Config> Module<T> {
                     r
                 }),
             RewardDestination::Account(dest_account) => {
-                Some(T::Currency::deposit_creating(&dest_account, amount))
+                let r = T::Currency::deposit_creating(&dest_account, amount);
+                T::BenefitInterface::maybe_compound_reward(&dest_account, amount);
+                Some(r)
             }
         }
     }
@@ -1815,6 +2414,7 @@ impl<T: Config> Module<T> {
     fn do_reward_stakers(
         validator_stash: T::AccountId,
         era: EraIndex,
+        page: PageIndex,
     ) -> DispatchResult {
         // 1. Validate input data
         let current_era = CurrentEra::get().ok_or(Error::<T>::InvalidEraToReward)?;
@@ -1830,13 +2430,17 @@ impl<T: Config> Module<T> {
         let controller = Self::bonded(&validator_stash).ok_or(Error::<T>::NotStash)?;
         let mut ledger = <Ledger<T>>::get(&controller).ok_or_else(|| Error::<T>::NotController)?;
 
-        ledger.claimed_rewards.retain(|&x| x >= current_era.saturating_sub(history_depth));
-        match ledger.claimed_rewards.binary_search(&era) {
+        // The first page is always claimable for the authoring reward
+        let page_count = Self::eras_stakers_page_count(&era, &validator_stash).max(1);
+        ensure!(page < page_count, Error::<T>::InvalidPage);
+
+        ledger.claimed_rewards.retain(|&(x, _)| x >= current_era.saturating_sub(history_depth));
+        match ledger.claimed_rewards.binary_search(&(era, page)) {
             Ok(_) => Err(Error::<T>::AlreadyClaimed)?,
-            Err(pos) => ledger.claimed_rewards.insert(pos, era),
+            Err(pos) => ledger.claimed_rewards.insert(pos, (era, page)),
         }
         /* Input data seems good, no errors allowed after this point */
-        let exposure = <ErasStakersClipped<T>>::get(&era, &ledger.stash);
+        let exposure = <ErasStakersPaged<T>>::get(&era, (ledger.stash.clone(), page));
         <Ledger<T>>::insert(&controller, &ledger);
 
         // 2. Pay authoring reward
@@ -1869,8 +2473,15 @@ impl<T: Config> Module<T> {
                 Self::deposit_event(RawEvent::Reward(i.who.clone(), imbalance.peek()));
             };
         }
-        // 6. Pay staking reward to validator
-        validator_imbalance.maybe_subsume(Self::make_payout(&ledger.stash, total_reward - guarantee_rewards));
+        // 6. Pay staking reward to validator, the total reward is split by the votes of each page
+        let page_reward = if page_count == 1 {
+            total_reward
+        } else {
+            let page_votes = exposure.others.iter().fold(Zero::zero(), |acc: BalanceOf<T>, i| acc.saturating_add(i.value));
+            let others_votes = exposure.total.saturating_sub(exposure.own);
+            Perbill::from_rational_approximation(page_votes, others_votes) * total_reward
+        };
+        validator_imbalance.maybe_subsume(Self::make_payout(&ledger.stash, page_reward.saturating_sub(guarantee_rewards)));
         Self::deposit_event(RawEvent::Reward(ledger.stash, validator_imbalance.peek()));
         Ok(())
     }
@@ -2066,7 +2677,8 @@ impl<T: Config> Module<T> {
         /// Clear all era information for given era.
     fn clear_era_information(era_index: EraIndex) {
         <ErasStakers<T>>::remove_prefix(era_index);
-        <ErasStakersClipped<T>>::remove_prefix(era_index);
+        <ErasStakersPaged<T>>::remove_prefix(era_index);
+        <ErasStakersPageCount<T>>::remove_prefix(era_index);
         <ErasValidatorPrefs<T>>::remove_prefix(era_index);
         <ErasStakingPayout<T>>::remove(era_index);
         <ErasMarketPayout<T>>::remove(era_index);
@@ -2077,35 +2689,59 @@ impl<T: Config> Module<T> {
     }
 
     fn total_rewards_in_era(active_era: EraIndex) -> BalanceOf<T> {
-        // 1. Has not start rewarding yet
-        if active_era < Self::start_reward_era() { return Zero::zero(); }
-        let mut maybe_rewards_this_year = FIRST_YEAR_REWARDS ;
-        let total_issuance = TryInto::<u128>::try_into(T::Currency::total_issuance())
-            .ok()
-            .unwrap();
+        let (reward_this_era, _) = Self::inflation_rewards_in_era(active_era);
+        reward_this_era.try_into().ok().unwrap()
+    }
+
+    /// The number of eras in a Julian year.
+    fn year_in_eras() -> u64 {
         // Milliseconds per year for the Julian year (365.25 days).
         const MILLISECONDS_PER_YEAR: u64 = 1000 * 3600 * 24 * 36525 / 100;
         // 1 Julian year = (365.25d * 24h * 3600s * 1000ms) / (millisecs_in_era = block_time * blocks_num_in_era)
-        let year_in_eras = MILLISECONDS_PER_YEAR / MILLISECS_PER_BLOCK / (EPOCH_DURATION_IN_BLOCKS * T::SessionsPerEra::get()) as u64;
-        let year_num = active_era.saturating_sub(Self::start_reward_era()) as u64 / year_in_eras;
+        MILLISECONDS_PER_YEAR / MILLISECS_PER_BLOCK / (EPOCH_DURATION_IN_BLOCKS * T::SessionsPerEra::get()) as u64
+    }
+
+    /// Return the rewards of the given reward year, and the extra part of it.
+    ///
+    /// NOTE: The ratios are applied with `mul_floor` to keep the former `x * 88 / 100` rounding,
+    /// `Perbill * x` rounds to the nearest and would change the rewards from the 11th year on.
+    /// The min rewards is now `floor(total_issuance * 28 / 1000)` rather than
+    /// `total_issuance / 1000 * 28`, which can only be higher by less than 28 units.
+    fn rewards_in_year(year_num: u64, total_issuance: u128) -> (u128, u128) {
+        let mut maybe_rewards_this_year = TryInto::<u128>::try_into(Self::first_year_rewards())
+            .ok()
+            .unwrap();
+        let reward_decrease_ratio = Self::reward_decrease_ratio();
+        let min_rewards_this_year = Self::min_reward_ratio().mul_floor(total_issuance);
         for _ in 0..year_num {
-            maybe_rewards_this_year = maybe_rewards_this_year * REWARD_DECREASE_RATIO.0 / REWARD_DECREASE_RATIO.1;
+            maybe_rewards_this_year = reward_decrease_ratio.mul_floor(maybe_rewards_this_year);
 
-            // If reward inflation <= 2.8%, stop reduce
-            let min_rewards_this_year = total_issuance / MIN_REWARD_RATIO.1 * MIN_REWARD_RATIO.0;
+            // If reward inflation <= min reward ratio(2.8% by default), stop reduce
             if maybe_rewards_this_year <= min_rewards_this_year {
                 maybe_rewards_this_year = min_rewards_this_year;
                 break;
             }
         }
 
-        if year_num >= EXTRA_REWARD_START_YEAR {
-            maybe_rewards_this_year = maybe_rewards_this_year.saturating_add(Self::supply_extra_rewards_due_to_low_effective_staking_ratio(total_issuance));
+        let mut extra_rewards_this_year = 0;
+        if year_num >= Self::extra_reward_start_year() {
+            extra_rewards_this_year = Self::supply_extra_rewards_due_to_low_effective_staking_ratio(total_issuance);
         }
+        (maybe_rewards_this_year.saturating_add(extra_rewards_this_year), extra_rewards_this_year)
+    }
 
-        let reward_this_era = maybe_rewards_this_year / year_in_eras as u128;
+    /// Return the inflation rewards of the given era, and the extra part of it.
+    fn inflation_rewards_in_era(active_era: EraIndex) -> (u128, u128) {
+        // 1. Has not start rewarding yet
+        if active_era < Self::start_reward_era() { return (0, 0); }
+        let total_issuance = TryInto::<u128>::try_into(T::Currency::total_issuance())
+            .ok()
+            .unwrap();
+        let year_in_eras = Self::year_in_eras();
+        let year_num = active_era.saturating_sub(Self::start_reward_era()) as u64 / year_in_eras;
+        let (rewards_this_year, extra_rewards_this_year) = Self::rewards_in_year(year_num, total_issuance);
 
-        reward_this_era.try_into().ok().unwrap()
+        (rewards_this_year / year_in_eras as u128, extra_rewards_this_year / year_in_eras as u128)
     }
 
     fn supply_extra_rewards_due_to_low_effective_staking_ratio(total_issuance: u128) -> u128 {
@@ -2159,6 +2795,9 @@ impl<T: Config> Module<T> {
     ///
     /// This should only be called at the end of an era.
     fn select_and_update_validators(current_era: EraIndex) -> Option<Vec<T::AccountId>> {
+        // 0. Apply the announced commission increase
+        Self::apply_pending_commissions(current_era);
+
         // I. Ensure minimum validator count
         let validator_count = <Validators<T>>::iter().count();
         let minimum_validator_count = Self::minimum_validator_count().max(1) as usize;
@@ -2191,17 +2830,14 @@ impl<T: Config> Module<T> {
                 (v_stash, Vec::<IndividualExposure<T::AccountId, BalanceOf<T>>>::new())
             ).collect();
         for (guarantor, guarantee) in <Guarantors<T>>::iter() {
-            let Guarantee { total: _, submitted_in: _, targets, suppressed: _ } = guarantee;
-
-            // // Filter out guarantee targets which were guaranteed before the most recent
-            // // slashing span.
-            // targets.retain(|ie| {
-            //     <Self as Store>::SlashingSpans::get(&ie.who).map_or(
-            //         true,
-            //         |spans| submitted_in >= spans.last_nonzero_slash(),
-            //     )
-            // });
-            
+            let Guarantee { total: _, submitted_in, mut targets, suppressed: _ } = guarantee;
+
+            // Filter out guarantee targets which were guaranteed before the most recent
+            // slashing span.
+            if T::FilterSlashedGuarantees::get() {
+                targets.retain(|ie| Self::is_guarantee_unslashed(&ie.who, submitted_in));
+            }
+
             for target in targets {
                 if let Some(g) = vg_graph.get_mut(&target.who) {
                      g.push(IndividualExposure {
@@ -2211,6 +2847,12 @@ impl<T: Config> Module<T> {
                 }
             }
         }
+        Self::evict_exceeded_guarantees(&mut vg_graph);
+        Self::spread_guarantee_preferences(&mut vg_graph);
+        if Self::election_mode() == ElectionMode::Balanced {
+            let balance_weight = Self::balance_vg_graph(&mut vg_graph);
+            <frame_system::Module<T>>::register_extra_weight_unchecked(balance_weight, DispatchClass::Mandatory);
+        }
 
         // III. This part will cover
         // 1. Get `ErasStakers` with `stake_limit` and `vg_graph`
@@ -2266,13 +2908,7 @@ impl<T: Config> Module<T> {
             // 4. Update snapshots
             <ErasStakers<T>>::insert(&current_era, &v_stash, new_exposure.clone());
             let exposure_total = new_exposure.total;
-            let mut exposure_clipped = new_exposure;
-            let clipped_max_len = T::MaxGuarantorRewardedPerValidator::get() as usize;
-            if exposure_clipped.others.len() > clipped_max_len {
-                exposure_clipped.others.sort_by(|a, b| a.value.cmp(&b.value).reverse());
-                exposure_clipped.others.truncate(clipped_max_len);
-            }
-            <ErasStakersClipped<T>>::insert(&current_era, &v_stash, exposure_clipped);
+            Self::store_exposure_pages(current_era, v_stash, new_exposure);
 
             <ErasValidatorPrefs<T>>::insert(&current_era, &v_stash, Self::validators(&v_stash).clone());
             if let Some(maybe_total_stakes) = eras_total_stakes.checked_add(&exposure_total) {
@@ -2321,6 +2957,265 @@ impl<T: Config> Module<T> {
         Some(elected_stashes)
     }
 
+    /// Move the clipped exposures into the first page and the claimed eras into the claimed
+    /// first pages, the rest pages never exist before.
+    fn migrate_to_paged_exposures() -> Weight {
+        #[derive(Encode, Decode)]
+        struct OldStakingLedger<AccountId, Balance: HasCompact> {
+            stash: AccountId,
+            #[codec(compact)]
+            total: Balance,
+            #[codec(compact)]
+            active: Balance,
+            unlocking: Vec<UnlockChunk<Balance>>,
+            claimed_rewards: Vec<EraIndex>,
+        }
+
+        let mut count: u64 = 0;
+        <Ledger<T>>::translate::<OldStakingLedger<T::AccountId, BalanceOf<T>>, _>(|_, old| {
+            count += 1;
+            Some(StakingLedger {
+                stash: old.stash,
+                total: old.total,
+                active: old.active,
+                unlocking: old.unlocking,
+                claimed_rewards: old.claimed_rewards.into_iter().map(|era| (era, 0)).collect(),
+            })
+        });
+
+        // The key is `twox_64_concat(era) ++ twox_64_concat(stash)`
+        for (key, exposure) in StorageIterator::<Exposure<T::AccountId, BalanceOf<T>>>::new(
+            b"Staking", b"ErasStakersClipped"
+        ).drain() {
+            count += 1;
+            if key.len() < 20 {
+                continue;
+            }
+            if let (Ok(era), Ok(v_stash)) = (EraIndex::decode(&mut &key[8..12]), T::AccountId::decode(&mut &key[20..])) {
+                <ErasStakersPaged<T>>::insert(&era, (v_stash.clone(), 0), exposure);
+                <ErasStakersPageCount<T>>::insert(&era, &v_stash, 1);
+            }
+        }
+
+        StorageVersion::put(Releases::V2_0_0);
+        T::DbWeight::get().reads_writes(count + 1, count * 2 + 1)
+    }
+
+    /// Split the exposure into pages with at most `MaxGuarantorRewardedPerValidator` guarantors,
+    /// the biggest stakers are put into the first pages.
+    fn store_exposure_pages(
+        current_era: EraIndex,
+        v_stash: &T::AccountId,
+        mut exposure: Exposure<T::AccountId, BalanceOf<T>>
+    ) {
+        let page_size = (T::MaxGuarantorRewardedPerValidator::get() as usize).max(1);
+        if exposure.others.len() > page_size {
+            exposure.others.sort_by(|a, b| a.value.cmp(&b.value).reverse());
+        }
+        let mut page_count: PageIndex = 0;
+        // Always have the first page, even there is no guarantor
+        let pages = if exposure.others.is_empty() {
+            vec![vec![]]
+        } else {
+            exposure.others.chunks(page_size).map(|page| page.to_vec()).collect::<Vec<_>>()
+        };
+        for others in pages {
+            <ErasStakersPaged<T>>::insert(&current_era, (v_stash.clone(), page_count), Exposure {
+                total: exposure.total,
+                own: exposure.own,
+                others
+            });
+            page_count += 1;
+        }
+        <ErasStakersPageCount<T>>::insert(&current_era, v_stash, page_count);
+    }
+
+    /// Apply the announced commission increases which are due at `current_era`.
+    fn apply_pending_commissions(current_era: EraIndex) {
+        let due_commissions = <PendingCommissions<T>>::iter()
+            .filter(|(_, (_, apply_at))| *apply_at <= current_era)
+            .collect::<Vec<_>>();
+        for (v_stash, (prefs, _)) in due_commissions {
+            <PendingCommissions<T>>::remove(&v_stash);
+            if <Validators<T>>::contains_key(&v_stash) {
+                <Validators<T>>::insert(&v_stash, &prefs);
+                Self::deposit_event(RawEvent::CommissionChangeApplied(v_stash, prefs));
+            }
+        }
+    }
+
+    /// Evict the smallest guarantees of validators which have more guarantors than
+    /// `MaxGuarantorsPerValidator`, the evicted votes are returned to their guarantors.
+    fn evict_exceeded_guarantees(
+        vg_graph: &mut BTreeMap<T::AccountId, Vec<IndividualExposure<T::AccountId, BalanceOf<T>>>>
+    ) {
+        let max_guarantors = T::MaxGuarantorsPerValidator::get() as usize;
+        for (v_stash, voters) in vg_graph.iter_mut() {
+            if voters.len() <= max_guarantors {
+                continue;
+            }
+            // Keep the biggest guarantees
+            voters.sort_by(|a, b| b.value.cmp(&a.value));
+            for evicted in voters.split_off(max_guarantors) {
+                if let Some(guarantee) = Self::decrease_guarantee(v_stash, &evicted.who, evicted.value) {
+                    <Guarantors<T>>::insert(&evicted.who, guarantee);
+                }
+                Self::deposit_event(RawEvent::GuaranteeEvicted(evicted.who, v_stash.clone(), evicted.value));
+            }
+        }
+    }
+
+    /// Spread the votes of guarantee preferences into the V/G graph.
+    /// The votes fill the remaining stake limit headroom of the preferred validators in order,
+    /// the votes which cannot be filled into any preferred validators are not counted.
+    fn spread_guarantee_preferences(
+        vg_graph: &mut BTreeMap<T::AccountId, Vec<IndividualExposure<T::AccountId, BalanceOf<T>>>>
+    ) {
+        let preferences = <GuaranteePreferences<T>>::iter().collect::<Vec<_>>();
+        if preferences.is_empty() {
+            return;
+        }
+
+        // 1. Get the used stakes of each validator, own stakes and the explicit guarantees
+        let mut headrooms: BTreeMap<T::AccountId, BalanceOf<T>> = BTreeMap::new();
+        for (v_stash, voters) in vg_graph.iter() {
+            let own_stake = Self::bonded(v_stash)
+                .and_then(|controller| Self::ledger(&controller))
+                .map(|ledger| ledger.active)
+                .unwrap_or(Zero::zero());
+            let used_stakes = voters.iter().fold(own_stake, |acc, ie| acc.saturating_add(ie.value));
+            let stake_limit = Self::stake_limit(v_stash).unwrap_or(Zero::zero());
+            headrooms.insert(v_stash.clone(), stake_limit.saturating_sub(used_stakes));
+        }
+
+        // 2. Fill the headroom in order
+        let filter_slashed = T::FilterSlashedGuarantees::get();
+        let max_guarantors = T::MaxGuarantorsPerValidator::get() as usize;
+        for (guarantor, preference) in preferences {
+            let mut remains = preference.total;
+            for target in preference.targets {
+                if remains.is_zero() {
+                    break;
+                }
+                if filter_slashed && !Self::is_guarantee_unslashed(&target, preference.submitted_in) {
+                    continue;
+                }
+                if let (Some(g), Some(headroom)) = (vg_graph.get_mut(&target), headrooms.get_mut(&target)) {
+                    let votes = remains.min(*headroom);
+                    if votes.is_zero() || g.len() >= max_guarantors {
+                        continue;
+                    }
+                    g.push(IndividualExposure {
+                        who: guarantor.clone(),
+                        value: votes
+                    });
+                    *headroom -= votes;
+                    remains -= votes;
+                }
+            }
+        }
+    }
+
+    /// Rebalance the V/G graph with the stake limit of each validator as its capacity.
+    /// Each guarantor's votes on all of its targets become a budget, then validators are
+    /// filled one by one in a sequential phragmen-like way: the validator which can reach
+    /// the highest support is filled first, its voters spend their remaining budget
+    /// proportionally. The votes which cannot be filled into any validators are not counted.
+    ///
+    /// Only the top `MAX_BALANCED_VALIDATORS` validators by their initial support are balanced,
+    /// the rest keep their votes as they are. Each validator has at most
+    /// `MaxGuarantorsPerValidator` voters, so TC is O(V * V * G) with the bounded V and G.
+    fn balance_vg_graph(
+        vg_graph: &mut BTreeMap<T::AccountId, Vec<IndividualExposure<T::AccountId, BalanceOf<T>>>>
+    ) -> Weight {
+        // 1. Get the capacity, the voters and the initial support of each validator
+        let max_guarantors = T::MaxGuarantorsPerValidator::get() as usize;
+        let mut candidates: Vec<(T::AccountId, BalanceOf<T>, Vec<T::AccountId>, BalanceOf<T>)> = vec![];
+        for (v_stash, voters) in vg_graph.iter() {
+            let own_stake = Self::bonded(v_stash)
+                .and_then(|controller| Self::ledger(&controller))
+                .map(|ledger| ledger.active)
+                .unwrap_or(Zero::zero());
+            let stake_limit = Self::stake_limit(v_stash).unwrap_or(Zero::zero());
+            let capacity = stake_limit.saturating_sub(own_stake);
+            let mut voters_of = vec![];
+            for voter in voters.iter() {
+                if !voters_of.contains(&voter.who) && voters_of.len() < max_guarantors {
+                    voters_of.push(voter.who.clone());
+                }
+            }
+            let votes = voters.iter().fold(Zero::zero(), |acc: BalanceOf<T>, ie| acc.saturating_add(ie.value));
+            candidates.push((v_stash.clone(), capacity, voters_of, capacity.min(votes)));
+        }
+
+        // 2. Keep the top validators, the sort is stable so the ties are kept in the stash order
+        candidates.sort_by(|a, b| b.3.cmp(&a.3));
+        candidates.truncate(MAX_BALANCED_VALIDATORS);
+        let balanced_count = candidates.len() as u32;
+
+        // 3. Get the budget of each guarantor from its votes on the balanced validators
+        let mut budgets: BTreeMap<T::AccountId, BalanceOf<T>> = BTreeMap::new();
+        let mut balanced_graph: BTreeMap<T::AccountId, Vec<IndividualExposure<T::AccountId, BalanceOf<T>>>> = BTreeMap::new();
+        for (v_stash, _, voters_of, _) in candidates.iter() {
+            if let Some(voters) = vg_graph.remove(v_stash) {
+                for voter in voters.into_iter().filter(|ie| voters_of.contains(&ie.who)) {
+                    let budget = budgets.entry(voter.who).or_insert(Zero::zero());
+                    *budget = budget.saturating_add(voter.value);
+                }
+            }
+            balanced_graph.insert(v_stash.clone(), vec![]);
+        }
+
+        // 4. Fill the validator with the highest support in each round
+        let total_budget_of = |voters: &Vec<T::AccountId>, budgets: &BTreeMap<T::AccountId, BalanceOf<T>>| {
+            voters.iter().fold(Zero::zero(), |acc: BalanceOf<T>, who|
+                acc.saturating_add(budgets.get(who).cloned().unwrap_or(Zero::zero()))
+            )
+        };
+        while !candidates.is_empty() {
+            let mut best: Option<(usize, BalanceOf<T>)> = None;
+            for (index, (_, capacity, voters, _)) in candidates.iter().enumerate() {
+                let support = (*capacity).min(total_budget_of(voters, &budgets));
+                if best.map_or(true, |(_, best_support)| support > best_support) {
+                    best = Some((index, support));
+                }
+            }
+            let (index, support) = best.expect("candidates is not empty; qed");
+            if support.is_zero() {
+                break;
+            }
+            let (v_stash, _, voters, _) = candidates.remove(index);
+            let total_budget = total_budget_of(&voters, &budgets);
+            let ratio = Perbill::from_rational_approximation(support, total_budget);
+            let exposures = balanced_graph.get_mut(&v_stash).expect("initialized above; qed");
+            for who in voters {
+                if let Some(budget) = budgets.get_mut(&who) {
+                    let votes = (ratio * *budget).min(*budget);
+                    if votes.is_zero() {
+                        continue;
+                    }
+                    *budget -= votes;
+                    exposures.push(IndividualExposure {
+                        who,
+                        value: votes
+                    });
+                }
+            }
+        }
+        vg_graph.append(&mut balanced_graph);
+
+        T::WeightInfo::balance_vg_graph(balanced_count, max_guarantors as u32)
+    }
+
+    /// Whether the guarantee to `v_stash` submitted in `submitted_in` era is not earlier
+    /// than the most recent slash of `v_stash`.
+    fn is_guarantee_unslashed(v_stash: &T::AccountId, submitted_in: EraIndex) -> bool {
+        <Self as Store>::SlashingSpans::get(v_stash).map_or(
+            true,
+            |spans| submitted_in >= spans.last_nonzero_slash(),
+        )
+    }
+
     /// Remove all associated data of a stash account from the staking system.
     ///
     /// Assumes storage is upgraded before calling.
@@ -2336,8 +3231,12 @@ impl<T: Config> Module<T> {
 
         <Payee<T>>::remove(stash);
         <Validators<T>>::remove(stash);
+        <PendingCommissions<T>>::remove(stash);
+        <LastValidatorPrefs<T>>::remove(stash);
         <Guarantors<T>>::remove(stash);
+        <GuaranteePreferences<T>>::remove(stash);
         <StakeLimit<T>>::remove(stash);
+        <WorkloadEma<T>>::remove(stash);
 
         // TODO: this may update with `num_slashing_spans`?
         slashing::clear_stash_metadata::<T>(stash);
@@ -2382,7 +3281,7 @@ impl<T: Config> Module<T> {
         mut validators_stakes: Vec<(T::AccountId, u128)>,
         to_elect: usize) -> Vec<T::AccountId> {
         // Select new validators by top-down their total `valid` stakes
-        // then randomly choose some of them from the top validators
+        // then choose some of them from the top validators by the election mode
 
         let candidate_to_elect = validators_stakes.len().min(to_elect * 2);
         // sort by 'valid' stakes
@@ -2394,50 +3293,64 @@ impl<T: Config> Module<T> {
         .map(|(who, stakes)| (who.clone(), *stakes))
         .collect::<Vec<(T::AccountId, u128)>>();
 
-        // TODO: enable it back when the network is stable
-        // // shuffle it
-        // Self::shuffle_candidates(&mut candidate_stashes);
-
         // choose elected_stashes number of validators
-        let elected_stashes = candidate_stashes[0..to_elect]
-        .iter()
-        .map(|(who, _stakes)| who.clone())
-        .collect::<Vec<T::AccountId>>();
+        match Self::election_mode() {
+            ElectionMode::TopDown | ElectionMode::Balanced => candidate_stashes[0..to_elect]
+                .iter()
+                .map(|(who, _stakes)| who.clone())
+                .collect::<Vec<T::AccountId>>(),
+            ElectionMode::RandomWeighted => {
+                // 👼 bless the randomness
+                let seed = T::Randomness::random(b"candidates_selection");
+                let seed = sp_io::hashing::blake2_256(seed.as_ref());
+                Self::weighted_random_select(candidate_stashes, to_elect, seed)
+            }
+        }
+    }
+
+    /// Draw `to_elect` candidates without replacement, each draw picks a candidate with
+    /// the probability of its stakes over the total stakes of the remaining candidates.
+    /// The result is fully determined by the `seed`.
+    fn weighted_random_select(
+        mut candidates_stakes: Vec<(T::AccountId, u128)>,
+        to_elect: usize,
+        seed: [u8; 32]) -> Vec<T::AccountId> {
+        let mut elected_stashes = Vec::with_capacity(to_elect);
+        for round in 0..to_elect.min(candidates_stakes.len()) as u32 {
+            let random_bytes = sp_io::hashing::blake2_256(&(seed, round).encode());
+            let mut random_u128 = [0u8; 16];
+            random_u128.copy_from_slice(&random_bytes[0..16]);
+            let random = u128::from_le_bytes(random_u128);
+
+            let total_stakes = candidates_stakes.iter()
+                .fold(0u128, |acc, (_, stakes)| acc.saturating_add(*stakes));
+            let index = if total_stakes.is_zero() {
+                // Nobody has stakes, fallback to the uniform draw
+                (random % candidates_stakes.len() as u128) as usize
+            } else {
+                let mut target = random % total_stakes;
+                candidates_stakes.iter().position(|(_, stakes)| {
+                    if target < *stakes {
+                        true
+                    } else {
+                        target -= *stakes;
+                        false
+                    }
+                }).unwrap_or(candidates_stakes.len() - 1)
+            };
+            elected_stashes.push(candidates_stakes.remove(index).0);
+        }
         elected_stashes
     }
 
     pub fn get_authoring_and_staking_reward_ratio(num_of_validators: u32) -> Perbill {
-        match num_of_validators {
-            0 ..= 500 => Perbill::from_percent(20),
-            501 ..= 1000 => Perbill::from_percent(25),
-            1001 ..= 2500 => Perbill::from_percent(30),
-            2501 ..= 5000 => Perbill::from_percent(40),
-            5001 ..= u32::MAX => Perbill::from_percent(50),
-        }
-    }
-
-    // fn shuffle_candidates(candidates_stakes: &mut Vec<(T::AccountId, u128)>) {
-    //     // 1. Construct random seed, 👼 bless the randomness
-    //     // seed = [ block_hash, phrase ]
-    //     let phrase = b"candidates_shuffle";
-    //     let bn = <frame_system::Module<T>>::block_number();
-    //     let bh: T::Hash = <frame_system::Module<T>>::block_hash(bn);
-    //     let seed = [
-    //         &bh.as_ref()[..],
-    //         &phrase.encode()[..]
-    //     ].concat();
-    //
-    //     // we'll need a random seed here.
-    //     let seed = T::Randomness::random(seed.as_slice());
-    //     // seed needs to be guaranteed to be 32 bytes.
-    //     let seed = <[u8; 32]>::decode(&mut TrailingZeroInput::new(seed.as_ref()))
-    //         .expect("input is padded with zeroes; qed");
-    //     let mut rng = ChaChaRng::from_seed(seed);
-    //     for i in (0..candidates_stakes.len()).rev() {
-    //         let random_index = (rng.next_u32() % (i as u32 + 1)) as usize;
-    //         candidates_stakes.swap(random_index, i);
-    //     }
-    // }
+        Self::authoring_reward_ratio_tiers()
+            .into_iter()
+            .rev()
+            .find(|(min_validators, _)| *min_validators <= num_of_validators)
+            .map(|(_, ratio)| ratio)
+            .unwrap_or_default()
+    }
 }
 
 /// In this implementation `new_session(session)` must be called before `end_session(session-1)`
@@ -2487,11 +3400,15 @@ impl<T: Config> swork::Works<T::AccountId> for Module<T> {
         let mut add_db_reads_writes = |reads, writes| {
             consumed_weight += T::DbWeight::get().reads_writes(reads, writes);
         };
-        // 1. Calculate total stake limit
-        let total_stake_limit = Self::calculate_total_stake_limit();
         let group_counts = workload_map.len() as u32;
+        // 1. Smooth the workloads, so a missed report won't drop the stake limit immediately
+        let (workload_map, total_workload) = Self::smooth_workloads(workload_map, total_workload);
+        let smoothed_count = workload_map.len() as u64;
+        add_db_reads_writes(smoothed_count + 2, smoothed_count + 1);
+        // 2. Calculate total stake limit
+        let total_stake_limit = Self::calculate_total_stake_limit();
         add_db_reads_writes(3, 0);
-        // 2. total_workload * SPowerRatio < total_stake_limit => stage one
+        // 3. total_workload * SPowerRatio < total_stake_limit => stage one
         let validators_count: u64 = if total_workload.saturating_mul(T::SPowerRatio::get()) < total_stake_limit {
             Self::update_stage_one_stake_limit(workload_map)
         } else {
@@ -56,6 +56,8 @@ thread_local! {
     static SESSION: RefCell<(Vec<AccountId>, HashSet<AccountId>)> = RefCell::new(Default::default());
     static EXISTENTIAL_DEPOSIT: RefCell<u128> = RefCell::new(0);
     static SLASH_DEFER_DURATION: RefCell<EraIndex> = RefCell::new(0);
+    static FILTER_SLASHED_GUARANTEES: RefCell<bool> = RefCell::new(false);
+    static MAX_GUARANTORS_PER_VALIDATOR: RefCell<u32> = RefCell::new(64);
     static OWN_WORKLOAD: RefCell<u128> = RefCell::new(0);
     static TOTAL_WORKLOAD: RefCell<u128> = RefCell::new(0);
     static DSM_STAKING_PAYOUT: RefCell<Balance> = RefCell::new(0);
@@ -113,6 +115,20 @@ impl Get<EraIndex> for SlashDeferDuration {
     }
 }
 
+pub struct FilterSlashedGuarantees;
+impl Get<bool> for FilterSlashedGuarantees {
+    fn get() -> bool {
+        FILTER_SLASHED_GUARANTEES.with(|v| *v.borrow())
+    }
+}
+
+pub struct MaxGuarantorsPerValidator;
+impl Get<u32> for MaxGuarantorsPerValidator {
+    fn get() -> u32 {
+        MAX_GUARANTORS_PER_VALIDATOR.with(|v| *v.borrow())
+    }
+}
+
 /// Author of block is always 11
 pub struct Author11;
 impl FindAuthor<u128> for Author11 {
@@ -230,8 +246,20 @@ impl<AID> MarketInterface<AID, BalanceOf<Test>> for TestStaking {
         BalanceOf::<Test>::from(DSM_STAKING_PAYOUT.with(|v| *v.borrow()))
     }
 
+    fn staking_pot_balance() -> BalanceOf<Test> {
+        BalanceOf::<Test>::from(DSM_STAKING_PAYOUT.with(|v| *v.borrow()))
+    }
+
     fn update_files_spower(_changed_files: &Vec<(MerkleRoot, u64, Vec<(AID, AID, SworkerAnchor, Option<primitives::BlockNumber>)>)>) {
     }
+
+    fn spot_check_nonce(_anchor: &SworkerAnchor, _cid: &MerkleRoot, _report_slot: primitives::ReportSlot) -> Option<primitives::SpotCheckNonce> {
+        None
+    }
+
+    fn pass_spot_check(_anchor: &SworkerAnchor, _cid: &MerkleRoot, _report_slot: primitives::ReportSlot) -> bool {
+        false
+    }
 }
 
 pub struct TestBenefitInterface;
@@ -251,6 +279,10 @@ impl<AID> BenefitInterface<AID, BalanceOf<Test>, NegativeImbalanceOf<Test>> for
         return true;
     }
 
+    fn maybe_compound_reward(_: &AID, _: BalanceOf<Test>) -> BalanceOf<Test> {
+        Zero::zero()
+    }
+
     fn get_collateral_and_reward(_: &AID) -> (BalanceOf<Test>, BalanceOf<Test>) {
         (Zero::zero(), Zero::zero())
     }
@@ -263,6 +295,7 @@ impl<AID> BenefitInterface<AID, BalanceOf<Test>, NegativeImbalanceOf<Test>> for
 parameter_types! {
     pub const PunishmentSlots: u32 = 1;
     pub const MaxGroupSize: u32 = 100;
+    pub const SpowerOperatorBond: Balance = 0;
 }
 
 impl swork::Config for Test {
@@ -272,6 +305,7 @@ impl swork::Config for Test {
     type Works = TestStaking;
     type MarketInterface = TestStaking;
     type MaxGroupSize = MaxGroupSize;
+    type SpowerOperatorBond = SpowerOperatorBond;
     type BenefitInterface = TestBenefitInterface;
     type WeightInfo = swork::weight::WeightInfo<Test>;
 }
@@ -284,6 +318,8 @@ parameter_types! {
     pub const SPowerRatio: u128 = 2_500;
     pub const MarketStakingPotDuration: u32 = 5;
     pub const UncheckedFrozenBondFund: Balance = 10;
+    pub const MaxCommissionIncreasePerChange: Perbill = Perbill::from_percent(20);
+    pub const CommissionIncreaseDelay: EraIndex = 1;
 }
 
 impl Config for Test {
@@ -299,14 +335,19 @@ impl Config for Test {
     type SessionsPerEra = SessionsPerEra;
     type BondingDuration = BondingDuration;
     type MaxGuarantorRewardedPerValidator = MaxGuarantorRewardedPerValidator;
+    type MaxGuarantorsPerValidator = MaxGuarantorsPerValidator;
     type SlashDeferDuration = SlashDeferDuration;
     type SlashCancelOrigin = frame_system::EnsureRoot<Self::AccountId>;
+    type TokenomicsOrigin = frame_system::EnsureRoot<Self::AccountId>;
+    type FilterSlashedGuarantees = FilterSlashedGuarantees;
     type SessionInterface = Self;
     type SPowerRatio = SPowerRatio;
     type MarketStakingPot = TestStaking;
     type MarketStakingPotDuration = MarketStakingPotDuration;
     type BenefitInterface = TestBenefitInterface;
     type UncheckedFrozenBondFund = UncheckedFrozenBondFund;
+    type MaxCommissionIncreasePerChange = MaxCommissionIncreasePerChange;
+    type CommissionIncreaseDelay = CommissionIncreaseDelay;
     type WeightInfo = weight::WeightInfo;
 }
 
@@ -335,6 +376,8 @@ pub struct ExtBuilder {
     validator_count: u32,
     minimum_validator_count: u32,
     slash_defer_duration: EraIndex,
+    filter_slashed_guarantees: bool,
+    max_guarantors_per_validator: u32,
     fair: bool,
     num_validators: Option<u32>,
     invulnerables: Vec<u128>,
@@ -354,6 +397,8 @@ impl Default for ExtBuilder {
             validator_count: 2,
             minimum_validator_count: 0,
             slash_defer_duration: 0,
+            filter_slashed_guarantees: false,
+            max_guarantors_per_validator: 64,
             fair: true,
             num_validators: None,
             invulnerables: vec![],
@@ -399,6 +444,14 @@ impl ExtBuilder {
         self.slash_defer_duration = eras;
         self
     }
+    pub fn filter_slashed_guarantees(mut self, enable: bool) -> Self {
+        self.filter_slashed_guarantees = enable;
+        self
+    }
+    pub fn max_guarantors_per_validator(mut self, max: u32) -> Self {
+        self.max_guarantors_per_validator = max;
+        self
+    }
     pub fn fair(mut self, is_fair: bool) -> Self {
         self.fair = is_fair;
         self
@@ -426,6 +479,8 @@ impl ExtBuilder {
     pub fn set_associated_consts(&self) {
         EXISTENTIAL_DEPOSIT.with(|v| *v.borrow_mut() = self.existential_deposit);
         SLASH_DEFER_DURATION.with(|v| *v.borrow_mut() = self.slash_defer_duration);
+        FILTER_SLASHED_GUARANTEES.with(|v| *v.borrow_mut() = self.filter_slashed_guarantees);
+        MAX_GUARANTORS_PER_VALIDATOR.with(|v| *v.borrow_mut() = self.max_guarantors_per_validator);
         OWN_WORKLOAD.with(|v| *v.borrow_mut() = self.own_workload);
         TOTAL_WORKLOAD.with(|v| *v.borrow_mut() = self.total_workload);
         DSM_STAKING_PAYOUT.with(|v| *v.borrow_mut() = self.dsm_staking_payout);
@@ -732,10 +787,11 @@ pub fn start_era_with_new_workloads(era_index: EraIndex, with_reward: bool, own_
 }
 
 pub fn payout_all_stakers(era_index: EraIndex) {
-    Staking::reward_stakers(Origin::signed(10), 11, era_index).unwrap_or_default();
-    Staking::reward_stakers(Origin::signed(10), 21, era_index).unwrap_or_default();
-    Staking::reward_stakers(Origin::signed(10), 31, era_index).unwrap_or_default();
-    Staking::reward_stakers(Origin::signed(10), 41, era_index).unwrap_or_default();
+    for validator in vec![11, 21, 31, 41] {
+        for page in 0..Staking::eras_stakers_page_count(era_index, validator).max(1) {
+            Staking::reward_stakers(Origin::signed(10), validator, era_index, page).unwrap_or_default();
+        }
+    }
 }
 
 fn init_swork_setup() {
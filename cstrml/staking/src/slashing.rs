@@ -51,7 +51,7 @@
 
 use super::{
     Config, Module, Store, BalanceOf, Exposure, Perbill, SessionInterface,
-    NegativeImbalanceOf, UnappliedSlash,
+    NegativeImbalanceOf, UnappliedSlash, SlashingSpanInfo,
 };
 use sp_runtime::{traits::{Zero, Saturating}, RuntimeDebug};
 use frame_support::{
@@ -144,10 +144,10 @@ impl SlashingSpans {
         sp_std::iter::once(last).chain(prior)
     }
 
-	// /// Yields the era index where the most recent non-zero slash occurred.
-	// pub fn last_nonzero_slash(&self) -> EraIndex {
-	// 	self.last_nonzero_slash
-	// }
+    /// Yields the era index where the most recent non-zero slash occurred.
+    pub fn last_nonzero_slash(&self) -> EraIndex {
+        self.last_nonzero_slash
+    }
 
     // prune the slashing spans against a window, whose start era index is given.
     //
@@ -189,6 +189,22 @@ impl<Balance> SpanRecord<Balance> {
     }
 }
 
+/// The slashing spans of a stash and the amount slashed in each of them, most recent first.
+pub(crate) fn span_history<T: Config>(stash: &T::AccountId) -> Vec<SlashingSpanInfo<BalanceOf<T>>> {
+    <Module<T> as Store>::SlashingSpans::get(stash).map(|spans| {
+        spans.iter().map(|span| {
+            let record = <Module<T> as Store>::SpanSlash::get(&(stash.clone(), span.index));
+            SlashingSpanInfo {
+                index: span.index,
+                start: span.start,
+                length: span.length,
+                slashed: record.slashed,
+                paid_out: record.paid_out,
+            }
+        }).collect()
+    }).unwrap_or_default()
+}
+
 /// Parameters for performing a slash.
 #[derive(Clone)]
 pub(crate) struct SlashParams<'a, T: 'a + Config> {
@@ -209,7 +209,7 @@ fn change_controller_works() {
 }
 
 #[test]
-fn validate_punishment_should_work() {
+fn validate_commission_increase_should_be_limited() {
     ExtBuilder::default().build().execute_with(|| {
         for i in 1..10 {
             let _ = Balances::make_free_balance_be(&i, 3000);
@@ -225,30 +225,61 @@ fn validate_punishment_should_work() {
         assert_ok!(set_payee(4, RewardDestination::Controller));
 
         Staking::upsert_stake_limit(&5, 3000);
-        assert_eq!(<ErasValidatorPrefs<Test>>::contains_key(5, 5), false);
+        // New validator takes any prefs directly
         assert_ok!(Staking::validate(Origin::signed(4), ValidatorPrefs { fee: Perbill::from_percent(50)}));
-        // Insert a useless eras validator prefs
-        assert_eq!(<ErasValidatorPrefs<Test>>::contains_key(5, 5), true);
-        assert_eq!(Staking::eras_validator_prefs(5, 5).fee, Perbill::from_percent(100));
-        assert_eq!(<ErasValidatorPrefs<Test>>::contains_key(6, 5), false);
+        assert_eq!(Staking::validators(5).fee, Perbill::from_percent(50));
+        assert_eq!(<ErasValidatorPrefs<Test>>::contains_key(5, 5), false);
 
         start_era(6, false);
         assert_eq!(Staking::eras_validator_prefs(6, 5).fee, Perbill::from_percent(50));
 
-        // Increase the prefs won't create punishment
+        // Decrease the commission takes effect directly
         assert_ok!(Staking::validate(Origin::signed(4), ValidatorPrefs { fee: Perbill::from_percent(60)}));
+        assert_eq!(Staking::validators(5).fee, Perbill::from_percent(60));
         assert_eq!(Staking::eras_validator_prefs(6, 5).fee, Perbill::from_percent(50));
 
-        // Decrease the prefs would have punishment
-        assert_ok!(Staking::validate(Origin::signed(4), ValidatorPrefs { fee: Perbill::from_percent(30)}));
-        assert_eq!(Staking::eras_validator_prefs(6, 5).fee, Perbill::from_percent(100));
+        // Increase the commission too much should be rejected
+        assert_noop!(
+            Staking::validate(Origin::signed(4), ValidatorPrefs { fee: Perbill::from_percent(30)}),
+            Error::<Test>::CommissionIncreaseTooLarge
+        );
 
-        assert_ok!(Staking::validate(Origin::signed(4), ValidatorPrefs { fee: Perbill::from_percent(20)}));
-        assert_eq!(Staking::eras_validator_prefs(6, 5).fee, Perbill::from_percent(100));
+        // Increase the commission should be announced and applied later
+        assert_ok!(Staking::validate(Origin::signed(4), ValidatorPrefs { fee: Perbill::from_percent(45)}));
+        assert_eq!(Staking::validators(5).fee, Perbill::from_percent(60));
+        assert_eq!(Staking::pending_commissions(5), Some((ValidatorPrefs { fee: Perbill::from_percent(45)}, 8)));
+        assert_eq!(Staking::eras_validator_prefs(6, 5).fee, Perbill::from_percent(50));
 
         start_era(7, false);
-        // Next era would be ok again
-        assert_eq!(Staking::eras_validator_prefs(7, 5).fee, Perbill::from_percent(20));
+        assert_eq!(Staking::eras_validator_prefs(7, 5).fee, Perbill::from_percent(60));
+
+        start_era(8, false);
+        assert_eq!(Staking::eras_validator_prefs(8, 5).fee, Perbill::from_percent(45));
+        assert_eq!(Staking::validators(5).fee, Perbill::from_percent(45));
+        assert_eq!(Staking::pending_commissions(5), None);
+
+        // Chill and validate again cannot bypass the limit
+        assert_ok!(Staking::chill(Origin::signed(4)));
+        assert_eq!(Staking::last_validator_prefs(5), Some(ValidatorPrefs { fee: Perbill::from_percent(45)}));
+        assert_noop!(
+            Staking::validate(Origin::signed(4), ValidatorPrefs { fee: Perbill::zero()}),
+            Error::<Test>::CommissionIncreaseTooLarge
+        );
+
+        // Even after the chilled era is over
+        start_era(9, false);
+        start_era(10, false);
+        assert_eq!(<ErasValidatorPrefs<Test>>::contains_key(10, 5), false);
+        assert_noop!(
+            Staking::validate(Origin::signed(4), ValidatorPrefs { fee: Perbill::zero()}),
+            Error::<Test>::CommissionIncreaseTooLarge
+        );
+
+        // Validate again should announce the increase from the last prefs
+        assert_ok!(Staking::validate(Origin::signed(4), ValidatorPrefs { fee: Perbill::from_percent(30)}));
+        assert_eq!(Staking::validators(5).fee, Perbill::from_percent(45));
+        assert_eq!(Staking::last_validator_prefs(5), None);
+        assert_eq!(Staking::pending_commissions(5), Some((ValidatorPrefs { fee: Perbill::from_percent(30)}, 12)));
     })
 }
 
@@ -299,9 +330,9 @@ fn rewards_should_work() {
                     others: vec![IndividualExposure { who: 2, value: 500 }],
                 },
             );
-            <ErasStakersClipped<Test>>::insert(
+            <ErasStakersPaged<Test>>::insert(
                 0,
-                &11,
+                (11, 0),
                 Exposure {
                     own: 500,
                     total: 1000,
@@ -348,8 +379,8 @@ fn rewards_should_work() {
             Session::on_initialize(System::block_number());
             assert_eq!(Staking::current_era().unwrap_or(0), 1);
             assert_eq!(Session::current_index(), 3);
-            Staking::reward_stakers(Origin::signed(10), 11, 0).unwrap();
-            Staking::reward_stakers(Origin::signed(20), 21, 0).unwrap();
+            Staking::reward_stakers(Origin::signed(10), 11, 0, 0).unwrap();
+            Staking::reward_stakers(Origin::signed(20), 21, 0, 0).unwrap();
             // 11 validator has 2/3 of the total rewards and half half for it and its guarantor
             assert_eq_error_rate!(
                 Balances::total_balance(&2) / 1000000,
@@ -1537,6 +1568,50 @@ fn extra_reward_should_work() {
         })
 }
 
+#[test]
+fn reward_projection_should_work() {
+    ExtBuilder::default()
+        .guarantee(false)
+        .start_reward_era(10000)
+        .dsm_staking_payout(500)
+        .build()
+        .execute_with(|| {
+            ActiveEra::put(ActiveEraInfo { index: 27530, start: None });
+            <ErasMarketPayout<Test>>::insert(27531, 50);
+            <ErasMarketPayout<Test>>::insert(27535, 70);
+
+            let projection = Staking::reward_projection(6);
+            assert_eq!(projection.year_in_eras, 17532);
+            assert_eq!(projection.eras.len(), 6);
+            // The market staking pot is split into the following 5 eras
+            let markets: Vec<Balance> = projection.eras.iter().map(|e| e.market).collect();
+            assert_eq!(markets, vec![100, 150, 100, 100, 100, 70]);
+            for era_projection in projection.eras.iter() {
+                assert_eq!(era_projection.extra, 0);
+                assert_eq!(era_projection.inflation, Staking::total_rewards_in_era(era_projection.era));
+                assert_eq!(era_projection.total, era_projection.inflation + era_projection.market);
+                assert_eq!(era_projection.authoring, Perbill::from_percent(20) * era_projection.total);
+                assert_eq!(era_projection.authoring + era_projection.staking, era_projection.total);
+            }
+            // Era 27532 is the beginning of the second reward year
+            assert_eq!(projection.eras[1].inflation, 285192790326260);
+            assert_eq!(projection.eras[2].inflation, 250969655487109);
+            assert_eq!(projection.yearly_rewards, vec![
+                (0, 5_000_000 * CRUS),
+                (1, 4_400_000 * CRUS)
+            ]);
+
+            // Nothing before the start reward era, except the market rewards
+            ActiveEra::put(ActiveEraInfo { index: 9998, start: None });
+            let projection = Staking::reward_projection(3);
+            assert_eq!(projection.eras[0].inflation, 0);
+            assert_eq!(projection.eras[1].inflation, 0);
+            assert_eq!(projection.eras[0].total, 100);
+            assert_eq!(projection.eras[2].inflation, Staking::total_rewards_in_era(10000));
+            assert_eq!(projection.yearly_rewards, vec![(0, 5_000_000 * CRUS)]);
+        })
+}
+
 #[test]
 fn validator_payment_prefs_work() {
     // Test that validator preferences are correctly honored
@@ -1566,9 +1641,9 @@ fn validator_payment_prefs_work() {
                 others: vec![IndividualExposure { who: 2, value: 500 }],
             },
         );
-        <ErasStakersClipped<Test>>::insert(
+        <ErasStakersPaged<Test>>::insert(
             0,
-            &11,
+            (11, 0),
             Exposure {
                 own: 500, // equal division indicates that the reward will be equally divided among validator and guarantor.
                 total: 1000,
@@ -1598,7 +1673,7 @@ fn validator_payment_prefs_work() {
 
 
         start_era(1, true);
-        Staking::reward_stakers(Origin::signed(10), 11, 0).unwrap();
+        Staking::reward_stakers(Origin::signed(10), 11, 0, 0).unwrap();
 
         let shared_cut = total_staking_payout_0 * 500 / 2001;
         // Validator's payee is Staked account, 11, reward will be paid here.
@@ -2752,7 +2827,7 @@ fn bond_with_little_staked_value_bounded_by_total_stakes() {
             assert_eq_uvec!(validator_controllers(), vec![20, 10, 2]);
             assert_eq!(Staking::eras_total_stakes(1), 2002);
             payout_all_stakers(0);
-            Staking::reward_stakers(Origin::signed(10), 1, 0).unwrap();
+            Staking::reward_stakers(Origin::signed(10), 1, 0, 0).unwrap();
             // Old ones are rewarded, round to 0.000001 CRU
             assert_eq!(
                 Balances::free_balance(&10) / 1000000,
@@ -2769,7 +2844,7 @@ fn bond_with_little_staked_value_bounded_by_total_stakes() {
             assert_eq_uvec!(validator_controllers(), vec![20, 10, 2]);
             assert_eq!(Staking::eras_total_stakes(2) / 1000000, (2002 + total_staking_payout_0 * 1000 / 2001 + total_authoring_payout / 3) / 1000000);
             payout_all_stakers(1);
-            Staking::reward_stakers(Origin::signed(10), 1, 1).unwrap();
+            Staking::reward_stakers(Origin::signed(10), 1, 1, 0).unwrap();
             // round to 0.000001 CRU
             assert_eq!(
                 Balances::free_balance(&2) / 1000000,
@@ -2846,7 +2921,7 @@ fn reward_with_no_stake_limit() {
             assert_eq_uvec!(validator_controllers(), vec![20, 10, 8]);
             assert_eq!(Staking::eras_total_stakes(1), 2000);
             payout_all_stakers(0);
-            Staking::reward_stakers(Origin::signed(10), 7, 0).unwrap();
+            Staking::reward_stakers(Origin::signed(10), 7, 0, 0).unwrap();
             assert_eq!(Balances::free_balance(&8), init_balance_8);
 
             reward_all_elected();
@@ -2854,7 +2929,7 @@ fn reward_with_no_stake_limit() {
 
             assert_eq_uvec!(validator_controllers(), vec![20, 10, 8]);
             payout_all_stakers(1);
-            Staking::reward_stakers(Origin::signed(10), 7, 1).unwrap();
+            Staking::reward_stakers(Origin::signed(10), 7, 1, 0).unwrap();
 
             // 8 should get authoring reward
             assert_eq!(
@@ -2948,9 +3023,9 @@ fn reward_validator_slashing_validator_doesnt_overflow() {
             },
         );
 
-        <ErasStakersClipped<Test>>::insert(
+        <ErasStakersPaged<Test>>::insert(
             0,
-            &11,
+            (11, 0),
             Exposure {
                 total: stake,
                 own: stake,
@@ -2964,7 +3039,7 @@ fn reward_validator_slashing_validator_doesnt_overflow() {
         );
 
         // Check reward
-        let _ = Staking::reward_stakers(Origin::signed(10), 11, 0);
+        let _ = Staking::reward_stakers(Origin::signed(10), 11, 0, 0);
         assert_eq!(Balances::total_balance(&11), stake * 2);
 
         // Set staker
@@ -2992,9 +3067,9 @@ fn reward_validator_slashing_validator_doesnt_overflow() {
             },
         );
 
-        <ErasStakersClipped<Test>>::insert(
+        <ErasStakersPaged<Test>>::insert(
             0,
-            &11,
+            (11, 0),
             Exposure {
                 total: stake,
                 own: 1,
@@ -3747,6 +3822,66 @@ fn deferred_slashes_are_deferred() {
         })
 }
 
+#[test]
+fn slashing_report_should_work() {
+    ExtBuilder::default()
+        .slash_defer_duration(2)
+        .filter_slashed_guarantees(true)
+        .build()
+        .execute_with(|| {
+            start_era(1, false);
+            assert_eq!(Staking::slashing_report(&101), SlashingReport::default());
+
+            let exposure = Staking::eras_stakers(1, &11);
+            let guaranteed_value = exposure.others.iter().find(|o| o.who == 101).unwrap().value;
+
+            on_offence_now(
+                &[OffenceDetails {
+                    offender: (11, Staking::eras_stakers(1, &11)),
+                    reporters: vec![],
+                }],
+                &[Perbill::from_percent(10)],
+            );
+
+            // 1. Validator side
+            let report = Staking::slashing_report(&11);
+            assert_eq!(report.pending_slashes, vec![PendingSlash {
+                apply_at: 4,
+                validator: 11,
+                value: 100
+            }]);
+            assert_eq!(report.last_nonzero_slash, 1);
+            // The slashed span is ended, a new span starts from the next era
+            assert_eq!(report.spans.len(), 2);
+            assert_eq!((report.spans[0].index, report.spans[0].start, report.spans[0].length), (1, 2, None));
+            assert_eq!(report.spans[1].slashed, 100);
+
+            // 2. Guarantor side
+            let report = Staking::slashing_report(&101);
+            assert_eq!(report.pending_slashes, vec![PendingSlash {
+                apply_at: 4,
+                validator: 11,
+                value: guaranteed_value / 10
+            }]);
+            assert_eq!(report.guarantees_at_risk, vec![IndividualExposure {
+                who: 11,
+                value: 250
+            }]);
+
+            // 3. The guarantee made before the slash should be filtered out
+            assert_ok!(Staking::validate(Origin::signed(10), ValidatorPrefs::default()));
+            start_era(2, false);
+            assert!(Staking::eras_stakers(2, &11).others.iter().all(|o| o.who != 101));
+            assert!(Staking::eras_stakers(2, &21).others.iter().any(|o| o.who == 101));
+
+            // 4. Applied slashes are not pending anymore
+            start_era(4, false);
+            assert_eq!(Balances::free_balance(&11), 900);
+            assert!(Staking::slashing_report(&101).pending_slashes.is_empty());
+            assert!(Staking::slashing_report(&101).guarantees_at_risk.is_empty());
+        })
+}
+
 #[test]
 fn remove_deferred() {
     ExtBuilder::default()
@@ -3954,7 +4089,7 @@ fn update_stakers_should_work_new_era() {
 }
 
 #[test]
-fn eras_stakers_clipped_should_work_new_era() {
+fn eras_stakers_paged_should_work_new_era() {
     ExtBuilder::default()
     .guarantee(false)
     .fair(false) // to give 20 more staked value
@@ -4068,7 +4203,7 @@ fn eras_stakers_clipped_should_work_new_era() {
             }
         );
         assert_eq!(
-            Staking::eras_stakers_clipped(1, &5),
+            Staking::eras_stakers_paged(1, (5, 0)),
             Exposure {
                 total: 3400,
                 own: 1000,
@@ -4090,6 +4225,87 @@ fn eras_stakers_clipped_should_work_new_era() {
                 }]
             }
         );
+        assert_eq!(
+            Staking::eras_stakers_paged(1, (5, 1)),
+            Exposure {
+                total: 3400,
+                own: 1000,
+                others: vec![IndividualExposure {
+                    who: 119,
+                    value: 400
+                }]
+            }
+        );
+        assert_eq!(Staking::eras_stakers_page_count(1, &5), 2);
+
+        // Each page should be paid separately
+        <Module<Test>>::reward_by_ids(vec![(5, 1)]);
+        start_era(2, true);
+        let balance_118 = Balances::free_balance(&118);
+        assert_ok!(Staking::reward_stakers(Origin::signed(10), 5, 1, 0));
+        assert_noop!(
+            Staking::reward_stakers(Origin::signed(10), 5, 1, 0),
+            Error::<Test>::AlreadyClaimed
+        );
+        assert_noop!(
+            Staking::reward_stakers(Origin::signed(10), 5, 1, 2),
+            Error::<Test>::InvalidPage
+        );
+        assert_eq!(Balances::free_balance(&118), balance_118);
+        assert_ok!(Staking::reward_stakers(Origin::signed(10), 5, 1, 1));
+        assert!(Balances::free_balance(&118) > balance_118);
+        assert_eq!(Staking::ledger(&4).unwrap().claimed_rewards, vec![(1, 0), (1, 1)]);
+    });
+}
+
+#[test]
+fn exceeded_guarantees_should_be_evicted() {
+    ExtBuilder::default()
+    .guarantee(false)
+    .max_guarantors_per_validator(4)
+    .build()
+    .execute_with(|| {
+        for i in 110..120 {
+            let _ = Balances::make_free_balance_be(&i, 3000);
+        }
+        for i in 1..10 {
+            let _ = Balances::make_free_balance_be(&i, 3000);
+        }
+
+        start_session(1, false);
+        assert_ok!(Staking::bond(
+            Origin::signed(5),
+            4,
+            1000
+        ));
+        Staking::upsert_stake_limit(&5, 4000);
+        assert_ok!(Staking::validate(Origin::signed(4), ValidatorPrefs::default()));
+
+        // 5 guarantors back 5, the smallest one would be evicted
+        for (stash, votes) in vec![(111, 500), (113, 500), (115, 300), (117, 500), (119, 500)] {
+            assert_ok!(Staking::bond(
+                Origin::signed(stash),
+                stash - 1,
+                1000
+            ));
+            assert_ok!(Staking::guarantee(Origin::signed(stash - 1), (5, votes)));
+        }
+
+        start_era(1, false);
+        let exposure = Staking::eras_stakers(1, &5);
+        assert_eq!(exposure.total, 3000);
+        assert_eq!(exposure.others.len(), 4);
+        assert!(exposure.others.iter().all(|ie| ie.who != 115));
+        assert_eq!(Staking::eras_stakers_paged(1, (5, 0)), exposure);
+
+        // The evicted votes are returned to the guarantor
+        assert_eq!(Staking::guarantors(&115), Some(Guarantee {
+            targets: vec![],
+            total: 0,
+            submitted_in: 0,
+            suppressed: false
+        }));
+        assert_ok!(Staking::guarantee(Origin::signed(114), (11, 1000)));
     });
 }
 
@@ -4576,6 +4792,236 @@ fn new_era_with_stake_limit_should_work() {
         });
 }
 
+#[test]
+fn guarantee_with_preference_should_work() {
+    ExtBuilder::default()
+        .guarantee(false)
+        .slash_defer_duration(2)
+        .own_workload(2)
+        .total_workload(100000000)
+        .validator_count(8)
+        .build()
+        .execute_with(|| {
+            // put some money in account that we'll use.
+            for i in 1..10 {
+                let _ = Balances::deposit_creating(&i, 5000);
+            }
+            start_era(4, false);
+
+            // Add guarantor with explicit guarantee
+            assert_ok!(Staking::bond(
+                Origin::signed(3),
+                4,
+                2000
+            ));
+            assert_ok!(Staking::guarantee(Origin::signed(4), (11, 1000)));
+
+            // Add guarantor with preference
+            assert_ok!(Staking::bond(
+                Origin::signed(1),
+                2,
+                2000
+            ));
+            assert_noop!(
+                Staking::guarantee_with_preference(Origin::signed(2), vec![11, 11], 2000),
+                Error::<Test>::InvalidTarget
+            );
+            assert_noop!(
+                Staking::guarantee_with_preference(Origin::signed(2), vec![11, 41], 2000),
+                Error::<Test>::InvalidTarget
+            );
+            assert_ok!(Staking::guarantee(Origin::signed(2), (21, 1000)));
+            assert_ok!(Staking::guarantee_with_preference(Origin::signed(2), vec![11, 21, 31], 3000));
+            // Preference replaces the explicit guarantee and is limited by the bonded value
+            assert_eq!(Staking::guarantors(&1), None);
+            assert_eq!(Staking::guarantee_preferences(&1), Some(GuaranteePreference {
+                targets: vec![11, 21, 31],
+                total: 2000,
+                submitted_in: 4
+            }));
+            // Guaranteed stakes cannot be unbonded
+            assert_noop!(Staking::unbond(Origin::signed(2), 1000), Error::<Test>::AllGuaranteed);
+
+            start_era_with_new_workloads(5, false, 1, 200000000);
+            assert_eq!(Staking::stake_limit(&11), Some(2500));
+            assert_eq!(Staking::stake_limit(&21), Some(2500));
+            assert_eq!(Staking::stake_limit(&31), Some(2500));
+
+            // 11 is filled up by 3 and 1, 21 is filled up by 1, the rest goes to 31
+            assert_eq!(
+                Staking::eras_stakers(5, 11),
+                Exposure {
+                    total: 2500,
+                    own: 1000,
+                    others: vec![IndividualExposure {
+                        who: 3,
+                        value: 1000
+                    }, IndividualExposure {
+                        who: 1,
+                        value: 500
+                    }]
+                }
+            );
+            assert_eq!(
+                Staking::eras_stakers(5, 21),
+                Exposure {
+                    total: 2500,
+                    own: 2000,
+                    others: vec![IndividualExposure {
+                        who: 1,
+                        value: 500
+                    }]
+                }
+            );
+            assert_eq!(
+                Staking::eras_stakers(5, 31),
+                Exposure {
+                    total: 1001,
+                    own: 1,
+                    others: vec![IndividualExposure {
+                        who: 1,
+                        value: 1000
+                    }]
+                }
+            );
+
+            // The spread votes are at risk when the preferred validator is going to be slashed
+            on_offence_now(
+                &[OffenceDetails {
+                    offender: (31, Staking::eras_stakers(5, &31)),
+                    reporters: vec![],
+                }],
+                &[Perbill::from_percent(10)],
+            );
+            assert_eq!(Staking::slashing_report(&1).guarantees_at_risk, vec![IndividualExposure {
+                who: 31,
+                value: 1000
+            }]);
+
+            // Chill should remove the preference
+            assert_ok!(Staking::chill(Origin::signed(2)));
+            assert_eq!(Staking::guarantee_preferences(&1), None);
+        });
+}
+
+#[test]
+fn balanced_election_should_work() {
+    ExtBuilder::default()
+        .guarantee(false)
+        .own_workload(2)
+        .total_workload(100000000)
+        .validator_count(8)
+        .build()
+        .execute_with(|| {
+            // put some money in account that we'll use.
+            for i in 1..10 {
+                let _ = Balances::deposit_creating(&i, 5000);
+            }
+            start_era(4, false);
+
+            assert_ok!(Staking::bond(
+                Origin::signed(3),
+                4,
+                2000
+            ));
+            assert_ok!(Staking::guarantee(Origin::signed(4), (11, 1000)));
+            assert_ok!(Staking::guarantee(Origin::signed(4), (31, 1000)));
+            assert_ok!(Staking::bond(
+                Origin::signed(1),
+                2,
+                2000
+            ));
+            assert_ok!(Staking::guarantee(Origin::signed(2), (11, 2000)));
+
+            // 1. Top down would clip all the votes on 11
+            start_era_with_new_workloads(5, false, 1, 200000000);
+            assert_eq!(Staking::stake_limit(&11), Some(2500));
+            assert_eq!(Staking::stake_limit(&31), Some(2500));
+            assert_eq!(Staking::eras_stakers(5, 11).total, 2500);
+            assert_eq!(Staking::eras_stakers(5, 11).own, 625);
+            assert_eq!(Staking::eras_stakers(5, 31).total, 1001);
+            assert_eq!(Staking::eras_total_stakes(5), 5501);
+
+            // 2. Balanced should move the votes of 3 into 31, and fill 11 with 1
+            assert_ok!(Staking::set_election_mode(Origin::root(), ElectionMode::Balanced));
+            start_era_with_new_workloads(6, false, 1, 200000000);
+            assert_eq!(
+                Staking::eras_stakers(6, 11),
+                Exposure {
+                    total: 2500,
+                    own: 1000,
+                    others: vec![IndividualExposure {
+                        who: 1,
+                        value: 1500
+                    }]
+                }
+            );
+            assert_eq!(
+                Staking::eras_stakers(6, 21),
+                Exposure {
+                    total: 2000,
+                    own: 2000,
+                    others: vec![]
+                }
+            );
+            assert_eq!(
+                Staking::eras_stakers(6, 31),
+                Exposure {
+                    total: 2001,
+                    own: 1,
+                    others: vec![IndividualExposure {
+                        who: 3,
+                        value: 2000
+                    }]
+                }
+            );
+            assert_eq!(Staking::eras_total_stakes(6), 6501);
+            assert_eq_uvec!(Staking::current_elected(), vec![11, 21, 31]);
+        });
+}
+
+#[test]
+fn balance_vg_graph_should_be_bounded() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            let max_guarantors = <Test as Config>::MaxGuarantorsPerValidator::get();
+            let mut vg_graph: BTreeMap<AccountId, Vec<IndividualExposure<AccountId, Balance>>> = BTreeMap::new();
+            for i in 0..200u128 {
+                Staking::upsert_stake_limit(&(1000 + i), 1_000_000);
+                let voters = (0..max_guarantors as u128 + 10).map(|j| IndividualExposure {
+                    who: 100_000 + i + j,
+                    value: 10 + i
+                }).collect::<Vec<_>>();
+                vg_graph.insert(1000 + i, voters);
+            }
+            let origin_graph = vg_graph.clone();
+
+            // 1. Only the top validators are balanced, each of them with bounded voters
+            let weight = Staking::balance_vg_graph(&mut vg_graph);
+            assert_eq!(weight, <Test as Config>::WeightInfo::balance_vg_graph(MAX_BALANCED_VALIDATORS as u32, max_guarantors));
+            assert_eq!(vg_graph.len(), 200);
+
+            // 2. The validators with the lowest supports keep their votes
+            let unbalanced_count = 200 - MAX_BALANCED_VALIDATORS as u128;
+            for i in 0..unbalanced_count {
+                assert_eq!(vg_graph.get(&(1000 + i)), origin_graph.get(&(1000 + i)));
+            }
+
+            // 3. The balanced votes never exceed the votes on the balanced validators
+            let sum_of = |graph: &BTreeMap<AccountId, Vec<IndividualExposure<AccountId, Balance>>>| {
+                (unbalanced_count..200).fold(0, |acc, i|
+                    graph.get(&(1000 + i)).unwrap().iter().fold(acc, |acc, ie| acc + ie.value)
+                )
+            };
+            assert!(sum_of(&vg_graph) > 0);
+            assert!(sum_of(&vg_graph) <= sum_of(&origin_graph));
+            for i in unbalanced_count..200 {
+                assert!(vg_graph.get(&(1000 + i)).unwrap().len() <= max_guarantors as usize);
+            }
+        });
+}
+
 #[test]
 fn chill_stash_should_work() {
     ExtBuilder::default()
@@ -4769,7 +5215,7 @@ fn double_claim_rewards_should_fail() {
                 (init_balance_31 + total_authoring_payout / 3 + total_staking_payout_0 / 2001) / 1000000
             );
             assert_noop!(
-                Staking::reward_stakers(Origin::signed(10), 11, 0),
+                Staking::reward_stakers(Origin::signed(10), 11, 0, 0),
                 DispatchError::Module {
                     index: 3,
                     error: 13,
@@ -4777,7 +5223,7 @@ fn double_claim_rewards_should_fail() {
                 }
             );
             assert_noop!(
-                Staking::reward_stakers(Origin::signed(10), 21, 0),
+                Staking::reward_stakers(Origin::signed(10), 21, 0, 0),
                 DispatchError::Module {
                     index: 3,
                     error: 13,
@@ -4785,7 +5231,7 @@ fn double_claim_rewards_should_fail() {
                 }
             );
             assert_noop!(
-                Staking::reward_stakers(Origin::signed(10), 31, 0),
+                Staking::reward_stakers(Origin::signed(10), 31, 0, 0),
                 DispatchError::Module {
                     index: 3,
                     error: 13,
@@ -4812,14 +5258,14 @@ fn era_clean_should_work() {
             assert!(<ErasStakingPayout<Test>>::contains_key(0));
             assert!(<ErasTotalStakes<Test>>::contains_key(0));
             assert!(<ErasStakers<Test>>::contains_key(0, 21));
-            assert!(<ErasStakersClipped<Test>>::contains_key(0, 21));
+            assert!(<ErasStakersPaged<Test>>::contains_key(0, (21, 0)));
             assert!(<ErasValidatorPrefs<Test>>::contains_key(0, 21));
             start_era(85, true);
             assert!(!<ErasStakingPayout<Test>>::contains_key(0));
             assert!(!<ErasAuthoringPayout<Test>>::contains_key(0, 21));
             assert!(!<ErasTotalStakes<Test>>::contains_key(0));
             assert!(!<ErasStakers<Test>>::contains_key(0, 21));
-            assert!(!<ErasStakersClipped<Test>>::contains_key(0, 21));
+            assert!(!<ErasStakersPaged<Test>>::contains_key(0, (21, 0)));
             assert!(!<ErasValidatorPrefs<Test>>::contains_key(0, 21));
         });
 }
@@ -4847,7 +5293,7 @@ fn payout_to_any_account_works() {
         let total_payout = staking_rewards_in_era(Staking::current_era().unwrap_or(0));
         assert!(total_payout > 100); // Test is meaningfull if reward something
         start_era(2, true);
-        assert_ok!(<Module<Test>>::reward_stakers(Origin::signed(1337), 111, 1));
+        assert_ok!(<Module<Test>>::reward_stakers(Origin::signed(1337), 111, 1, 0));
 
         // Payment is successful
         assert!(Balances::free_balance(42) > 0);
@@ -4993,6 +5439,67 @@ fn update_stage_two_stake_limit_according_to_mpow_should_work() {
         });
 }
 
+#[test]
+fn stake_limit_should_follow_smoothed_workloads() {
+    ExtBuilder::default()
+        .guarantee(false)
+        .own_workload(u128::max_value())
+        .build()
+        .execute_with(|| {
+            for i in 1..5 {
+                let _ = Balances::deposit_creating(&i, 5000);
+            }
+            assert_ok!(Staking::bond(Origin::signed(1), 2, 1000));
+            assert_ok!(Staking::bond(Origin::signed(3), 4, 1000));
+            assert_ok!(Staking::validate(Origin::signed(2), ValidatorPrefs::default()));
+            assert_ok!(Staking::validate(Origin::signed(4), ValidatorPrefs::default()));
+
+            // Smoothing factor should be set by root and can not be zero
+            assert_noop!(
+                Staking::set_workload_smoothing_factor(Origin::signed(1), Perbill::from_percent(50)),
+                BadOrigin
+            );
+            assert_noop!(
+                Staking::set_workload_smoothing_factor(Origin::root(), Perbill::zero()),
+                Error::<Test>::InvalidSmoothingFactor
+            );
+            assert_ok!(Staking::set_workload_smoothing_factor(Origin::root(), Perbill::from_percent(50)));
+            assert_eq!(Staking::workload_smoothing_factor(), Perbill::from_percent(50));
+
+            // 1. The first report is taken as it is
+            let mut workload_map = BTreeMap::new();
+            workload_map.insert(1, 4);
+            workload_map.insert(3, 2);
+            Staking::report_works(workload_map.clone(), 6);
+            assert_eq!(Staking::workload_ema(&1), Some(4));
+            assert_eq!(Staking::workload_ema(&3), Some(2));
+            assert_eq!(Staking::total_workload_ema(), Some(6));
+            assert_eq!(Staking::stake_limit(&1).unwrap_or_default(), 10000);
+            assert_eq!(Staking::stake_limit(&3).unwrap_or_default(), 5000);
+
+            // 2. Missing one report only halves the stake limit
+            let mut missed_map = BTreeMap::new();
+            missed_map.insert(3, 2);
+            Staking::report_works(missed_map, 2);
+            assert_eq!(Staking::workload_ema(&1), Some(2));
+            assert_eq!(Staking::total_workload_ema(), Some(4));
+            assert_eq!(Staking::stake_limit(&1).unwrap_or_default(), 5000);
+            assert_eq!(Staking::stake_limit(&3).unwrap_or_default(), 5000);
+
+            // 3. Recover gradually
+            Staking::report_works(workload_map, 6);
+            assert_eq!(Staking::workload_ema(&1), Some(3));
+            assert_eq!(Staking::stake_limit(&1).unwrap_or_default(), 7500);
+            assert_eq!(Staking::stake_limit(&3).unwrap_or_default(), 5000);
+            assert_eq!(Staking::workload_ema(&7), None);
+
+            // 4. Killed stash should not keep its workload
+            assert_ok!(Staking::kill_stash(&1));
+            assert_eq!(Staking::workload_ema(&1), None);
+            assert_eq!(Staking::workload_ema(&3), Some(2));
+        });
+}
+
 #[test]
 fn check_inverse_function_work() {
     ExtBuilder::default()
@@ -5070,6 +5577,96 @@ fn change_authoring_reward_ratio_according_to_validator_count_should_work() {
         });
 }
 
+#[test]
+fn set_tokenomics_should_work() {
+    ExtBuilder::default()
+        .guarantee(false)
+        .start_reward_era(10000)
+        .build()
+        .execute_with(|| {
+            let tiers = vec![(0, Perbill::from_percent(10)), (100, Perbill::from_percent(60))];
+            assert_noop!(
+                Staking::set_authoring_reward_ratio_tiers(Origin::signed(11), tiers.clone()),
+                BadOrigin
+            );
+            assert_noop!(
+                Staking::set_inflation_curve(Origin::signed(11), 1_000_000 * CRUS, Perbill::from_percent(50), Perbill::zero(), 100),
+                BadOrigin
+            );
+
+            // 1. Tiers should start from zero and be strictly increasing
+            for invalid_tiers in vec![
+                vec![],
+                vec![(1, Perbill::from_percent(10))],
+                vec![(0, Perbill::from_percent(10)), (10, Perbill::from_percent(20)), (10, Perbill::from_percent(30))],
+                (0..17).map(|i| (i, Perbill::from_percent(i))).collect(),
+            ] {
+                assert_noop!(
+                    Staking::set_authoring_reward_ratio_tiers(Origin::root(), invalid_tiers),
+                    Error::<Test>::InvalidAuthoringRewardRatioTiers
+                );
+            }
+            assert_ok!(Staking::set_authoring_reward_ratio_tiers(Origin::root(), tiers.clone()));
+            assert_eq!(Staking::authoring_reward_ratio_tiers(), tiers);
+            assert_eq!(Staking::get_authoring_and_staking_reward_ratio(99), Perbill::from_percent(10));
+            assert_eq!(Staking::get_authoring_and_staking_reward_ratio(100), Perbill::from_percent(60));
+            assert_eq!(Staking::get_authoring_and_staking_reward_ratio(u32::max_value()), Perbill::from_percent(60));
+
+            // 2. Inflation curve should have non-zero first year rewards and decrease ratio
+            assert_eq!(Staking::total_rewards_in_era(10000), 5_000_000 * CRUS / 17532);
+            assert_noop!(
+                Staking::set_inflation_curve(Origin::root(), 0, Perbill::from_percent(50), Perbill::zero(), 100),
+                Error::<Test>::InvalidInflationCurve
+            );
+            assert_noop!(
+                Staking::set_inflation_curve(Origin::root(), 1_000_000 * CRUS, Perbill::zero(), Perbill::zero(), 100),
+                Error::<Test>::InvalidInflationCurve
+            );
+            assert_ok!(Staking::set_inflation_curve(Origin::root(), 1_000_000 * CRUS, Perbill::from_percent(50), Perbill::zero(), 100));
+            assert_eq!(Staking::total_rewards_in_era(10000), 1_000_000 * CRUS / 17532);
+            assert_eq!(Staking::total_rewards_in_era(27532), 500_000 * CRUS / 17532);
+            assert_eq!(Staking::total_rewards_in_era(45064), 250_000 * CRUS / 17532);
+        });
+}
+
+#[test]
+fn rewards_in_year_should_keep_former_rounding() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            // Former rewards with `x * 88 / 100`
+            let former_rewards: Vec<u128> = vec![
+                5000000000000000000,
+                4400000000000000000,
+                3872000000000000000,
+                3407360000000000000,
+                2998476800000000000,
+                2638659584000000000,
+                2322020433920000000,
+                2043377981849600000,
+                1798172624027648000,
+                1582391909144330240,
+                1392504880047010611,
+                1225404294441369337, // `Perbill * x` gives 1225404294441369338
+                1078355779108405016,
+                948953085615396414,
+                835078715341548844,
+                734869269500562982,
+            ];
+            for (year, rewards) in former_rewards.into_iter().enumerate() {
+                let (total_rewards, extra_rewards) = Staking::rewards_in_year(year as u64, 1000);
+                assert_eq!(total_rewards - extra_rewards, rewards);
+            }
+
+            // Stop reducing at 2.8% of the total issuance
+            let total_issuance: u128 = 100_000_000 * CRUS;
+            let (total_rewards, extra_rewards) = Staking::rewards_in_year(4, total_issuance);
+            assert_eq!(total_rewards - extra_rewards, 2998476800000000000);
+            let (total_rewards, extra_rewards) = Staking::rewards_in_year(5, total_issuance);
+            assert_eq!(total_rewards - extra_rewards, total_issuance / 1000 * 28);
+        });
+}
+
 #[test]
 fn reward_should_be_locked_with_other_lock() {
     ExtBuilder::default()
@@ -5115,7 +5712,7 @@ fn reward_should_be_locked_with_other_lock() {
             <Module<Test>>::reward_by_ids(vec![(131, 1)]);
             // Compute total payout now for whole duration as other parameter won't change
             start_era(2, true);
-            assert_ok!(<Module<Test>>::reward_stakers(Origin::signed(1337), 131, 1));
+            assert_ok!(<Module<Test>>::reward_stakers(Origin::signed(1337), 131, 1, 0));
 
             assert_eq!(Balances::locks(&131)[0].amount, 5000);
 
@@ -5128,7 +5725,7 @@ fn reward_should_be_locked_with_other_lock() {
                     total: 209019550997461,
                     active: 209019550997461,
                     unlocking: vec![],
-                    claimed_rewards:vec![1],
+                    claimed_rewards: vec![(1, 0)],
                 })
             );
 
@@ -5143,13 +5740,13 @@ fn reward_should_be_locked_with_other_lock() {
                         value: 209019550997461,
                         era: 5
                     }],
-                    claimed_rewards: vec![1]
+                    claimed_rewards: vec![(1, 0)]
                 })
             );
             assert_eq!(Balances::locks(&131)[1].amount, 209019550997461);
             <Module<Test>>::reward_by_ids(vec![(131, 1)]);
             start_era(5, true);
-            assert_ok!(<Module<Test>>::reward_stakers(Origin::signed(1337), 131, 2));
+            assert_ok!(<Module<Test>>::reward_stakers(Origin::signed(1337), 131, 2, 0));
 
             assert_eq!(
                 Staking::ledger(&132),
@@ -5161,7 +5758,7 @@ fn reward_should_be_locked_with_other_lock() {
                         value: 209019550997461,
                         era: 5
                     }],
-                    claimed_rewards: vec![1, 2]
+                    claimed_rewards: vec![(1, 0), (2, 0)]
                 })
             );
             assert_eq!(Balances::locks(&131)[1].amount, 209019550997461 + 209019550992471);
@@ -5173,7 +5770,7 @@ fn reward_should_be_locked_with_other_lock() {
                     total: 209019550992471,
                     active: 209019550992471,
                     unlocking: vec![],
-                    claimed_rewards: vec![1, 2]
+                    claimed_rewards: vec![(1, 0), (2, 0)]
                 })
             );
             assert_eq!(Balances::locks(&131)[1].amount, 209019550992471);
@@ -5190,7 +5787,7 @@ fn reward_should_be_locked_with_other_lock() {
                         value: 5000,
                         era: 8
                     }],
-                    claimed_rewards: vec![1, 2]
+                    claimed_rewards: vec![(1, 0), (2, 0)]
                 })
             );
             assert_eq!(Balances::locks(&131)[1].amount, 5000);
@@ -5220,7 +5817,7 @@ fn reward_should_be_locked_with_other_lock() {
                         value: 3000,
                         era: 11
                     }],
-                    claimed_rewards: vec![1, 2]
+                    claimed_rewards: vec![(1, 0), (2, 0)]
                 })
             );
             assert_eq!(Balances::locks(&131)[1].amount, 4000);
@@ -5248,7 +5845,7 @@ fn reward_should_be_locked_with_other_lock() {
                         value: 3000,
                         era: 11
                     }],
-                    claimed_rewards: vec![1, 2]
+                    claimed_rewards: vec![(1, 0), (2, 0)]
                 })
             );
             assert_eq!(Balances::locks(&131)[1].amount, 4000);
@@ -5472,3 +6069,140 @@ fn force_selection_should_work() {
             assert_eq!(Staking::current_elected().len(), 4);
         });
 }
+
+#[test]
+fn random_weighted_election_should_work() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            let candidates: Vec<(AccountId, u128)> =
+                vec![(11, 1000), (21, 2000), (31, 500), (41, 3000), (51, 1), (61, 0)];
+
+            // Default mode should elect the top ones
+            assert_eq!(Staking::election_mode(), ElectionMode::TopDown);
+            assert_eq!(Staking::do_election(candidates.clone(), 2), vec![41, 21]);
+
+            assert_noop!(
+                Staking::set_election_mode(Origin::signed(10), ElectionMode::RandomWeighted),
+                BadOrigin
+            );
+            assert_ok!(Staking::set_election_mode(Origin::root(), ElectionMode::RandomWeighted));
+            assert_eq!(Staking::election_mode(), ElectionMode::RandomWeighted);
+
+            // Elected validators should come from the top 2 * to_elect candidates
+            let elected = Staking::do_election(candidates.clone(), 2);
+            assert_eq!(elected.len(), 2);
+            assert_ne!(elected[0], elected[1]);
+            assert!(elected.iter().all(|v| vec![41, 21, 11, 31].contains(v)));
+
+            // Same seed, same result
+            assert_eq!(Staking::do_election(candidates.clone(), 2), elected);
+
+            // Candidates without stakes should never be drawn before the others
+            let elected = Staking::do_election(candidates.clone(), 3);
+            assert_eq!(elected.len(), 3);
+            assert!(!elected.contains(&61));
+
+            // Candidates without stakes still have a chance
+            let elected = Staking::do_election(vec![(51, 0), (61, 0)], 2);
+            assert_eq!(elected.len(), 2);
+        });
+}
+
+#[test]
+fn random_weighted_selection_should_follow_stakes() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            let candidates: Vec<(AccountId, u128)> =
+                vec![(11, 1000), (21, 2000), (31, 3000), (41, 4000), (51, 0)];
+            let rounds: u32 = 10000;
+            let mut counts: BTreeMap<AccountId, u32> = BTreeMap::new();
+            for i in 0..rounds {
+                let seed = sp_io::hashing::blake2_256(&i.encode());
+                let elected = Staking::weighted_random_select(candidates.clone(), 1, seed);
+                *counts.entry(elected[0]).or_default() += 1;
+            }
+            // Frequencies should match the stake weights within 2%
+            assert_eq_error_rate!(counts.get(&11).cloned().unwrap_or_default(), 1000, 200);
+            assert_eq_error_rate!(counts.get(&21).cloned().unwrap_or_default(), 2000, 200);
+            assert_eq_error_rate!(counts.get(&31).cloned().unwrap_or_default(), 3000, 200);
+            assert_eq_error_rate!(counts.get(&41).cloned().unwrap_or_default(), 4000, 200);
+            assert_eq!(counts.get(&51), None);
+
+            // Different seeds give different orders, but always the same for a seed
+            let seed = sp_io::hashing::blake2_256(b"seed");
+            assert_eq!(
+                Staking::weighted_random_select(candidates.clone(), 4, seed),
+                Staking::weighted_random_select(candidates.clone(), 4, seed)
+            );
+        });
+}
+
+#[test]
+fn migrate_to_paged_exposures_should_work() {
+    use frame_support::{
+        storage::migration::put_storage_value,
+        traits::OnRuntimeUpgrade,
+        Blake2_128Concat, StorageHasher, Twox64Concat,
+    };
+
+    #[derive(Encode, Decode)]
+    struct OldStakingLedger<AccountId, Balance: HasCompact> {
+        stash: AccountId,
+        #[codec(compact)]
+        total: Balance,
+        #[codec(compact)]
+        active: Balance,
+        unlocking: Vec<UnlockChunk<Balance>>,
+        claimed_rewards: Vec<EraIndex>,
+    }
+
+    ExtBuilder::default().build().execute_with(|| {
+        // 1. Seed the old ledger and clipped exposures
+        StorageVersion::put(Releases::V1_0_0);
+        put_storage_value(b"Staking", b"Ledger", &Blake2_128Concat::hash(&(100 as AccountId).encode()), OldStakingLedger {
+            stash: 101 as AccountId,
+            total: 1000 as Balance,
+            active: 900 as Balance,
+            unlocking: vec![UnlockChunk { value: 100, era: 3 }],
+            claimed_rewards: vec![1, 2],
+        });
+        let exposure = Exposure {
+            total: 1500 as Balance,
+            own: 1000,
+            others: vec![IndividualExposure { who: 201 as AccountId, value: 500 }],
+        };
+        let mut key = Twox64Concat::hash(&2u32.encode());
+        key.extend(Twox64Concat::hash(&(101 as AccountId).encode()));
+        put_storage_value(b"Staking", b"ErasStakersClipped", &key, exposure.clone());
+
+        // 2. Migrate
+        assert!(<Staking as OnRuntimeUpgrade>::on_runtime_upgrade() > 0);
+
+        // 3. The claimed eras become the claimed first pages
+        assert_eq!(Staking::ledger(&100), Some(StakingLedger {
+            stash: 101,
+            total: 1000,
+            active: 900,
+            unlocking: vec![UnlockChunk { value: 100, era: 3 }],
+            claimed_rewards: vec![(1, 0), (2, 0)],
+        }));
+
+        // 4. The clipped exposure becomes the first and only page
+        assert_eq!(Staking::eras_stakers_paged(2, (101, 0)), exposure);
+        assert_eq!(Staking::eras_stakers_page_count(2, 101), 1);
+        let mut prefix = Twox64Concat::hash(&2u32.encode());
+        prefix.extend(Twox64Concat::hash(&(101 as AccountId).encode()));
+        assert_eq!(
+            frame_support::storage::migration::get_storage_value::<Exposure<AccountId, Balance>>(b"Staking", b"ErasStakersClipped", &prefix),
+            None
+        );
+        assert_eq!(StorageVersion::get(), Releases::V2_0_0);
+
+        // 5. Migrate again takes no effect
+        <Staking as OnRuntimeUpgrade>::on_runtime_upgrade();
+        assert_eq!(Staking::ledger(&100).unwrap().claimed_rewards, vec![(1, 0), (2, 0)]);
+        assert_eq!(Staking::eras_stakers_page_count(2, 101), 1);
+    });
+}
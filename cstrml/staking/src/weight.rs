@@ -86,4 +86,10 @@ impl crate::WeightInfo for WeightInfo {
 			.saturating_add(DbWeight::get().reads((1 as Weight).saturating_mul(m as Weight)))
 			.saturating_add(DbWeight::get().writes((270 as Weight).saturating_mul(v as Weight)))
 	}
+	fn balance_vg_graph(v: u32, g: u32, ) -> Weight {
+		(0 as Weight)
+			.saturating_add((100_000 as Weight).saturating_mul(v as Weight).saturating_mul(v as Weight).saturating_mul(g as Weight))
+			.saturating_add((1_000_000 as Weight).saturating_mul(v as Weight).saturating_mul(g as Weight))
+			.saturating_add(DbWeight::get().reads((3 as Weight).saturating_mul(v as Weight)))
+	}
 }
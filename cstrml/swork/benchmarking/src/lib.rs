@@ -6,7 +6,7 @@
 use frame_system::{self as system, RawOrigin};
 use frame_benchmarking::{benchmarks, account};
 use frame_support::traits::Currency;
-use frame_support::storage::StorageMap;
+use frame_support::storage::{StorageMap, StorageDoubleMap};
 use sp_runtime::traits::{StaticLookup, Zero};
 use codec::Decode;
 use market::{FileInfoV2, Replica};
@@ -133,6 +133,39 @@ fn legal_work_report_with_deleted_files() -> ReportWorksInfo {
     }
 }
 
+fn legal_work_report_with_spot_check_proofs() -> (ReportWorksInfo, (ReportSlot, MerkleRoot, MerkleRoot, SworkerSignature), SpotCheckNonce) {
+    let curr_pk = vec![208,128,227,104,10,4,101,69,230,106,22,8,35,241,132,222,243,83,12,135,11,70,36,156,200,191,144,219,90,218,103,38,120,123,24,186,123,202,15,31,85,148,173,163,248,56,78,159,245,246,170,103,41,125,238,74,247,112,52,174,155,152,140,92];
+    let prev_pk: Vec<u8> = vec![];
+    let block_number = 600;
+    let block_hash = vec![0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0];
+    let free: u64 = 0;
+    let spower: u64 = 0;
+    let added_files: Vec<(Vec<u8>, u64, u64)> = vec![];
+    let deleted_files: Vec<(Vec<u8>, u64, u64)> = vec![];
+    let files_root: Vec<u8> = vec![17];
+    let srd_root: Vec<u8> = vec![0];
+    let sig: Vec<u8> = vec![80,9,130,236,221,20,205,138,123,191,91,57,220,77,252,96,67,127,28,190,152,52,94,219,78,118,232,143,245,54,54,187,121,8,57,49,119,177,128,208,137,168,156,45,224,172,222,59,89,36,223,83,83,254,238,197,50,154,230,102,180,57,221,66];
+
+    // Answer the spot check of slot 300 with nonce [7; 32]
+    let cid = "QmdwgqZy1MZBfWPi7GcxVsYgJEtmvHg6rsLzbCej3tf3oF".as_bytes().to_vec();
+    let chunk_hash: Vec<u8> = vec![92,167,220,157,92,75,135,240,183,191,186,179,196,247,211,242,78,14,43,43,110,28,138,157,214,246,192,47,243,161,178,196];
+    let proof_sig: Vec<u8> = vec![23,125,89,198,122,235,100,144,216,235,88,21,223,151,7,191,188,175,105,205,227,106,205,194,250,205,26,91,27,177,69,92,224,81,188,91,221,147,89,185,34,161,64,203,4,201,98,166,186,234,221,11,32,136,39,28,48,112,159,223,58,152,229,67];
+
+    (ReportWorksInfo {
+        curr_pk,
+        prev_pk,
+        block_number,
+        block_hash,
+        free,
+        spower,
+        srd_root,
+        files_root,
+        added_files,
+        deleted_files,
+        sig
+    }, (300, cid, chunk_hash, proof_sig), [7u8; 32])
+}
+
 fn add_market_files<T: Config>(files: Vec<(MerkleRoot, u64, u64)>, _user: T::AccountId, pub_key: Vec<u8>) {
     for (file, file_size, _) in files.clone().iter() {
         let mut replicas = BTreeMap::<T::AccountId, Replica<T::AccountId>>::new();
@@ -212,7 +245,8 @@ benchmarks! {
             wr.deleted_files,
             wr.srd_root,
             wr.files_root,
-            wr.sig
+            wr.sig,
+            vec![]
         ).expect("Something wrong during reporting works");
     } verify {
         assert_eq!(swork::Module::<T>::free(), wr.free as u128);
@@ -251,7 +285,8 @@ benchmarks! {
             wr.deleted_files,
             wr.srd_root,
             wr.files_root,
-            wr.sig
+            wr.sig,
+            vec![]
         ).expect("Something wrong during reporting works");
     } verify {
         assert_eq!(swork::Module::<T>::free(), wr.free as u128);
@@ -290,7 +325,8 @@ benchmarks! {
             wr.deleted_files,
             wr.srd_root,
             wr.files_root,
-            wr.sig
+            wr.sig,
+            vec![]
         ).expect("Something wrong during reporting works");
 
         let wr = legal_work_report_with_deleted_files();
@@ -312,7 +348,8 @@ benchmarks! {
             wr.deleted_files,
             wr.srd_root,
             wr.files_root,
-            wr.sig
+            wr.sig,
+            vec![]
         ).expect("Something wrong during reporting works");
     } verify {
         assert_eq!(swork::Module::<T>::free(), wr.free as u128);
@@ -320,6 +357,48 @@ benchmarks! {
         assert_eq!(swork::Module::<T>::reported_in_slot(&wr.curr_pk, wr.block_number), true);
     }
 
+    report_works_with_spot_check_proofs {
+        let p in 1 .. 100;
+        let code: Vec<u8> = vec![120,27,83,125,61,206,243,157,236,123,139,206,111,223,205,3,45,141,132,102,64,233,181,89,139,74,159,98,113,136,169,8];
+        swork::Module::<T>::set_code(RawOrigin::Root.into(), code.clone(), EXPIRE_BLOCK_NUMBER.into()).expect("failed to insert code");
+
+        // Prepare legal work report and spot check proofs
+        let user: Vec<u8> = vec![212,53,147,199,21,253,211,28,97,20,26,189,4,169,159,214,130,44,133,88,133,76,205,227,154,86,132,231,165,109,162,125]; // Alice
+        let caller = T::AccountId::decode(&mut &user[..]).unwrap_or_default();
+        let (wr, proof, nonce) = legal_work_report_with_spot_check_proofs();
+        let (report_slot, cid, _, _) = proof.clone();
+
+        // Set block number, system hash and pk in swork at 600
+        swork::Module::<T>::insert_pk_info(wr.curr_pk.clone(), code.clone());
+        system::Module::<T>::set_block_number(603u32.into());
+        let fake_bh:T::Hash = T::Hash::decode(&mut &wr.block_hash[..]).unwrap_or_default();
+        let target_block_number:T::BlockNumber = 600u32.into();
+        <system::BlockHash<T>>::insert(target_block_number, fake_bh);
+
+        // Prepare the pending spot check in market, every proof verifies the sig
+        <market::SpotChecks<T>>::insert(report_slot, (cid.clone(), wr.curr_pk.clone()), (caller.clone(), nonce));
+        let proofs = vec![proof; p as usize];
+    }: {
+        swork::Module::<T>::report_works(
+            RawOrigin::Signed(caller.clone()).into(),
+            wr.curr_pk.clone(),
+            wr.prev_pk,
+            wr.block_number,
+            wr.block_hash,
+            wr.free,
+            wr.spower,
+            wr.added_files,
+            wr.deleted_files,
+            wr.srd_root,
+            wr.files_root,
+            wr.sig,
+            proofs
+        ).expect("Something wrong during reporting works");
+    } verify {
+        assert_eq!(market::Module::<T>::spot_checks(report_slot, (cid, wr.curr_pk.clone())), None);
+        assert_eq!(swork::Module::<T>::reported_in_slot(&wr.curr_pk, wr.block_number), true);
+    }
+
     create_group {
         let owner: T::AccountId = account("owner", 0, SEED);
     }: {
@@ -361,7 +440,8 @@ benchmarks! {
             wr.deleted_files,
             wr.srd_root,
             wr.files_root,
-            wr.sig
+            wr.sig,
+            vec![]
         ).expect("Something wrong during reporting works");
         swork::Module::<T>::add_member_into_allowlist(RawOrigin::Signed(owner.clone()).into(), T::Lookup::unlookup(member.clone())).expect("Something wrong during adding into allowlist");
     }: {
@@ -406,6 +486,13 @@ mod tests {
         });
     }
 
+    #[test]
+    fn report_works_with_spot_check_proofs() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_ok!(test_benchmark_report_works_with_spot_check_proofs::<Test>());
+        });
+    }
+
     #[test]
     fn create_group() {
         ExtBuilder::default().build().execute_with(|| {
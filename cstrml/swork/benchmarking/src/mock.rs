@@ -122,6 +122,7 @@ impl market::Config for Test {
     type MaximumFileSize = MaximumFileSize;
     type WeightInfo = market::weight::WeightInfo<Test>;
     type RenewRewardRatio = RenewRewardRatio;
+    type Randomness = TestRandomness;
 }
 
 pub struct TestWorksInterface;
@@ -147,6 +148,10 @@ impl<AID> BenefitInterface<AID, BalanceOf<Test>, NegativeImbalanceOf<Test>> for
         return true;
     }
 
+    fn maybe_compound_reward(_: &AID, _: BalanceOf<Test>) -> BalanceOf<Test> {
+        Zero::zero()
+    }
+
     fn get_collateral_and_reward(_: &AID) -> (BalanceOf<Test>, BalanceOf<Test>) {
         (Zero::zero(), Zero::zero())
     }
@@ -159,6 +164,7 @@ impl<AID> BenefitInterface<AID, BalanceOf<Test>, NegativeImbalanceOf<Test>> for
 parameter_types! {
     pub const PunishmentSlots: u32 = 1;
     pub const MaxGroupSize: u32 = 100;
+    pub const SpowerOperatorBond: Balance = 0;
 }
 
 impl swork::Config for Test {
@@ -168,6 +174,7 @@ impl swork::Config for Test {
     type Works = TestWorksInterface;
     type MarketInterface = Market;
     type MaxGroupSize = MaxGroupSize;
+    type SpowerOperatorBond = SpowerOperatorBond;
     type BenefitInterface = TestBenefitInterface;
     type WeightInfo = swork::weight::WeightInfo<Test>;
 }
@@ -8,7 +8,7 @@ use codec::{Decode, Encode};
 use frame_support::{
     decl_event, decl_module, decl_storage, decl_error, ensure,
     dispatch::{DispatchResult, DispatchResultWithPostInfo},
-    storage::{IterableStorageMap, generator::StorageMap, unhashed},
+    storage::{IterableStorageMap, generator::StorageMap, unhashed, migration},
     traits::{Currency, ReservableCurrency, Get},
     ReversibleStorageHasher,
     weights::{
@@ -17,7 +17,8 @@ use frame_support::{
 };
 use sp_core::sr25519;
 pub use frame_support::storage::PrefixIterator;
-use sp_runtime::traits::{StaticLookup, Zero};
+use sp_runtime::{DispatchError, traits::{StaticLookup, Zero}};
+use sp_io::hashing::blake2_256;
 use sp_std::{str, convert::TryInto, prelude::*, collections::btree_set::BTreeSet};
 use frame_system::{self as system, ensure_root, ensure_signed};
 
@@ -27,7 +28,7 @@ use serde::{Deserialize, Serialize};
 // Crust primitives and runtime modules
 use primitives::{
     constants::swork::*,
-    MerkleRoot, SworkerPubKey, SworkerSignature,
+    MerkleRoot, SworkerPubKey, SworkerSignature, Hash, SpotCheckNonce,
     ReportSlot, BlockNumber, IASSig,
     ISVBody, SworkerCert, SworkerCode, SworkerAnchor,
     traits::{MarketInterface, SworkerInterface, BenefitInterface}
@@ -54,6 +55,9 @@ const IDENTITY_UPDATE_LENGTH: usize = 500; // Loop 500 identities per block
 const SRD_LIMIT: u64 = 2_251_799_813_685_248; // 2 PB <-> 2 * 1024 * 1024 * 1024 * 1024 * 1024.
 const FILES_LIMIT: u64 = 9_007_199_254_740_992; // 8 PB <-> 8 * 1024 * 1024 * 1024 * 1024 * 1024.
 const FILES_COUNT_LIMIT: usize = 300; // TODO: 300 files for now(will be deleted after completed wr reporting mechanism).
+const SPOT_CHECK_PROOFS_LIMIT: usize = 100; // Same as the max spot check sample size of one slot in market.
+const SPOWER_BATCH_ROUND_LIFETIME: BlockNumber = 14_400; // Unapproved spower batches expire in 1 day.
+const REPLICAS_COMMITMENTS_PRUNE_LIMIT: u64 = 300; // Prune 300 processed replicas commitments per update at most.
 const NEW_IDENTITY: ReportSlot = 1;
 const NO_PUNISHMENT: ReportSlot = 0;
 
@@ -70,7 +74,7 @@ macro_rules! log {
 pub trait WeightInfo {
     fn set_code() -> Weight;
     fn register() -> Weight;
-    fn report_works(added: u32, deleted: u32) -> Weight;
+    fn report_works(added: u32, deleted: u32, spot_check_proofs: u32) -> Weight;
     fn update_spower(changed_sworkers_count: u32, changed_files_count: u32) -> Weight;
     fn create_group() -> Weight;
     fn join_group() -> Weight;
@@ -183,6 +187,32 @@ impl<T: Config> SworkerInterface<T::AccountId> for Module<T> {
     // Update the last processed block of work reports 
     fn update_last_processed_block_of_work_reports(last_processed_block: BlockNumber) {
         LastProcessedBlockWorkReports::put(last_processed_block);
+        // The replicas queued until the last processed block have been replayed
+        if let Some(commitment) = Self::replicas_commitments(last_processed_block) {
+            ProcessedReplicasCommitment::put(commitment);
+        }
+        Self::prune_replicas_commitments(last_processed_block);
+    }
+
+    // Check whether the replicas to update match the replicas queued by report works until the last processed block
+    fn check_replicas_commitment(
+        last_processed_block: BlockNumber,
+        file_infos_map: &Vec<(MerkleRoot, u64, Vec<(T::AccountId, T::AccountId, SworkerAnchor, ReportSlot, BlockNumber, BlockNumber, bool)>)>
+    ) -> bool {
+        let mut batch_commitment = Self::processed_replicas_commitment();
+        let mut is_empty = true;
+        for (cid, file_size, replicas) in file_infos_map.iter() {
+            for (reporter, owner, anchor, report_slot, report_block, valid_at, is_added) in replicas.iter() {
+                batch_commitment = Self::chain_replica_commitment(&batch_commitment, cid, *file_size, reporter, owner, anchor, *report_slot, *report_block, *valid_at, *is_added);
+                is_empty = false;
+            }
+        }
+
+        match Self::replicas_commitments(last_processed_block) {
+            Some(queued_commitment) => batch_commitment == queued_commitment,
+            // Nothing to replay, the queued replicas (if any) must be replayed in a later batch
+            None => is_empty
+        }
     }
     
     // Update changed spower of sworkers
@@ -212,6 +242,50 @@ impl<T: Config> SworkerInterface<T::AccountId> for Module<T> {
         }
     }
 
+    // Attest a spower batch, the batch would only be applied when enough spower operators attested the same one.
+    // Each batch is bound to a round of its kind and batch block, one operator can only attest one batch in a round,
+    // otherwise the bond of the operator would be slashed for equivocation. The batch block of each kind should keep
+    // increasing, so an applied batch can never be attested and applied again.
+    fn attest_spower_batch(who: &T::AccountId, kind: &[u8], batch_block: BlockNumber, batch_hash: Hash) -> Result<bool, DispatchError> {
+        // 1. Check if the caller is a spower operator
+        ensure!(<SpowerOperators<T>>::contains_key(who), Error::<T>::IllegalSpowerOperator);
+
+        // 2. Check replay
+        if let Some((applied_block, _)) = Self::applied_spower_batches(kind.to_vec()) {
+            ensure!(batch_block > applied_block, Error::<T>::SpowerBatchAlreadyApplied);
+        }
+
+        // 3. Check equivocation
+        let round: Hash = (kind, batch_block).using_encoded(blake2_256).into();
+        if let Some(attested_hash) = Self::spower_batch_attestations(&round, who) {
+            ensure!(attested_hash != batch_hash, Error::<T>::SpowerBatchAlreadyAttested);
+            Self::slash_spower_operator(who, batch_block);
+            return Ok(false);
+        }
+        <SpowerBatchAttestations<T>>::insert(&round, who, batch_hash);
+        if !SpowerBatchRounds::contains_key(&round) {
+            let expire_at = Self::get_current_block_number().saturating_add(SPOWER_BATCH_ROUND_LIFETIME);
+            SpowerBatchRounds::insert(&round, expire_at);
+            SpowerBatchRoundsToExpire::mutate(expire_at, |rounds| rounds.push(round));
+        }
+
+        // 4. Vote for the batch, the votes from removed operators are not counted
+        let mut votes = Self::spower_batch_votes(&round, &batch_hash);
+        votes.retain(|operator| <SpowerOperators<T>>::contains_key(operator));
+        votes.push(who.clone());
+        let votes_count = votes.len() as u32;
+        Self::deposit_event(RawEvent::AttestSpowerBatchSuccess(who.clone(), batch_hash, votes_count));
+
+        // 5. Approve the batch if it reaches the threshold
+        if votes_count >= Self::spower_attest_threshold() {
+            AppliedSpowerBatches::insert(kind.to_vec(), (batch_block, batch_hash));
+            Self::clear_spower_batch_round(&round);
+            return Ok(true);
+        }
+        <SpowerBatchVotes<T>>::insert(&round, &batch_hash, votes);
+        Ok(false)
+    }
+
 }
 
 /// The module's configuration trait.
@@ -232,6 +306,9 @@ pub trait Config: system::Config {
     /// Interface for interacting with a market module.
     type MarketInterface: MarketInterface<Self::AccountId, BalanceOf<Self>>;
 
+    /// The bond reserved from each spower operator, which would be slashed for equivocation.
+    type SpowerOperatorBond: Get<BalanceOf<Self>>;
+
     /// Max number of members in one group
     type MaxGroupSize: Get<u32>;
 
@@ -275,11 +352,58 @@ decl_storage! {
         pub WorkReports get(fn work_reports):
             map hasher(twox_64_concat) SworkerAnchor => Option<WorkReport>;
 
+        /// The replicas queued by report works are chained as h = blake2_256((h, cid, file_size, reporter, owner, anchor, report_slot, report_block, valid_at, is_added)),
+        /// mapping from the block number to the chain head after the replicas queued in this block, which haven't been processed yet.
+        /// The crust-spower service should replay the replicas in the queued order, from the processed chain head to the head of the last processed block.
+        pub ReplicasCommitments get(fn replicas_commitments):
+            map hasher(twox_64_concat) BlockNumber => Option<Hash>;
+
+        /// The head of the replicas commitment chain, including all queued replicas
+        pub LatestReplicasCommitment get(fn latest_replicas_commitment): Hash;
+
+        /// The head of the replicas commitment chain, until the last processed block
+        pub ProcessedReplicasCommitment get(fn processed_replicas_commitment): Hash;
+
+        /// The blocks with replicas commitments in queued order, used to prune the processed ones
+        pub ReplicasCommitmentBlocks get(fn replicas_commitment_blocks):
+            map hasher(twox_64_concat) u64 => Option<BlockNumber>;
+
+        /// The (first, next) index range of ReplicasCommitmentBlocks
+        pub ReplicasCommitmentBlocksRange get(fn replicas_commitment_blocks_range): (u64, u64);
+
         /// The last procssed block for the WorkReportsToProcess data, which is used by the crust-spower service for fresh new start
         pub LastProcessedBlockWorkReports get (fn last_processed_block_work_reports): BlockNumber = 0;
 
-        /// The crust-spower service account
-        pub SpowerSuperior get(fn spower_superior): Option<T::AccountId>;
+        /// The crust-spower service operators, mapping from the operator to its reserved bond
+        pub SpowerOperators get(fn spower_operators):
+            map hasher(blake2_128_concat) T::AccountId => Option<BalanceOf<T>>;
+
+        /// The count of spower operators
+        pub SpowerOperatorsCount get(fn spower_operators_count): u32 = 0;
+
+        /// The number of spower operators who should attest the same batch before it's applied
+        pub SpowerAttestThreshold get(fn spower_attest_threshold): u32 = 1;
+
+        /// The batch hash attested by each spower operator in each round,
+        /// the round is the hash of the batch kind and the batch block
+        pub SpowerBatchAttestations get(fn spower_batch_attestations):
+            double_map hasher(identity) Hash, hasher(blake2_128_concat) T::AccountId => Option<Hash>;
+
+        /// The spower operators who attested the batch in each round
+        pub SpowerBatchVotes get(fn spower_batch_votes):
+            double_map hasher(identity) Hash, hasher(identity) Hash => Vec<T::AccountId>;
+
+        /// The unapproved rounds, mapping from the round to the block it expires at
+        pub SpowerBatchRounds get(fn spower_batch_rounds):
+            map hasher(identity) Hash => Option<BlockNumber>;
+
+        /// The unapproved rounds which expire at the block
+        pub SpowerBatchRoundsToExpire get(fn spower_batch_rounds_to_expire):
+            map hasher(twox_64_concat) BlockNumber => Vec<Hash>;
+
+        /// The last applied batch of each kind, mapping from the batch kind to its batch block and batch hash
+        pub AppliedSpowerBatches get(fn applied_spower_batches):
+            map hasher(blake2_128_concat) Vec<u8> => Option<(BlockNumber, Hash)>;
 
         /// The last spower update block, which is set during updateSpower call by Crust-Spower service
         pub LastSpowerUpdateBlock get(fn last_spower_update_block): BlockNumber = 0;
@@ -374,10 +498,24 @@ decl_error! {
         CodeNotExpired,
         /// Tee signature is not valid
         InvalidTeeSignature,
-        /// The spower superior account is not set. Please call the set_spower_superior extrinsic first.
-        SpowerSuperiorNotSet,
-        /// The caller account is not the spower superior account. Please check the caller account again.
-        IllegalSpowerSuperior
+        /// The caller account is not a spower operator. Please check the caller account again.
+        IllegalSpowerOperator,
+        /// The account is already a spower operator.
+        SpowerOperatorAlreadyExist,
+        /// The spower operator doesn't have enough currency to reserve the bond.
+        InsufficientSpowerOperatorBond,
+        /// The spower operator already attested this batch.
+        SpowerBatchAlreadyAttested,
+        /// The attest threshold should be larger than 0 and no more than the spower operators count.
+        InvalidSpowerAttestThreshold,
+        /// Exceed the limit of spot check proofs count in one work report.
+        ExceedSpotCheckProofsLimit,
+        /// The spot check proof is not answering a past report slot.
+        InvalidSpotCheckSlot,
+        /// Illegal spot check proof signature
+        IllegalSpotCheckProofSig,
+        /// The batch block is not larger than the one of the last applied batch.
+        SpowerBatchAlreadyApplied
     }
 }
 
@@ -395,6 +533,14 @@ decl_module! {
         // this is needed only if you are using events in your module
         fn deposit_event() = default;
 
+        fn on_runtime_upgrade() -> Weight {
+            // The spower superior has been replaced by the spower operators
+            if migration::take_storage_value::<T::AccountId>(b"Swork", b"SpowerSuperior", &[]).is_some() {
+                T::DbWeight::get().reads_writes(1, 1)
+            } else {
+                T::DbWeight::get().reads(1)
+            }
+        }
 
         /// Called when a block is initialized. Will call update_identities to update stake limit
         fn on_initialize(now: T::BlockNumber) -> Weight {
@@ -443,6 +589,7 @@ decl_module! {
                     add_db_reads_writes(0, 2, 0);
                 }
             }
+            add_db_reads_writes(0, 0, Self::expire_spower_batch_rounds(now));
             consumed_weight
         }
 
@@ -588,6 +735,14 @@ decl_module! {
         /// The dispatch origin for this call must be _Signed_ by the reporter account.
         ///
         /// Emits `WorksReportSuccess` if new work report has been reported
+        /// Emits `SpotCheckProofsSuccess` if any spot check has been answered
+        ///
+        /// Arguments:
+        /// spot_check_proofs:
+        ///     Vec<(report_slot, cid, chunk_hash, sig)>, answering the replica spot checks drawn by market
+        ///     in the past slots. The chunk_hash is the hash of the file chunk selected by the spot check nonce,
+        ///     the sig is signed by the current pk over curr_pk ++ report_slot ++ cid ++ nonce ++ chunk_hash.
+        ///     The proofs of the spot checks which are not pending anymore are ignored.
         ///
         /// # <weight>
         /// - Independent of the arguments. Moderate complexity.
@@ -596,10 +751,12 @@ decl_module! {
         ///
         /// ------------------
         /// DB Weight:
-        /// - Read: Identities, ReportedInSlot, Code, market.Merchant, market.SOrder
-        /// - Write: WorkReport, ReportedInSlot, market.SOrder
+        /// - Read: Identities, ReportedInSlot, Code, market.Merchant, market.SOrder, market.SpotChecks,
+        ///   LatestReplicasCommitment, ReplicasCommitments, ReplicasCommitmentBlocksRange
+        /// - Write: WorkReport, ReportedInSlot, market.SOrder, market.SpotChecks,
+        ///   LatestReplicasCommitment, ReplicasCommitments, ReplicasCommitmentBlocks, ReplicasCommitmentBlocksRange
         /// # </weight>
-        #[weight = T::WeightInfo::report_works(added_files.len() as u32, deleted_files.len() as u32)]
+        #[weight = T::WeightInfo::report_works(added_files.len() as u32, deleted_files.len() as u32, spot_check_proofs.len() as u32)]
         pub fn report_works(
             origin,
             curr_pk: SworkerPubKey,
@@ -612,13 +769,15 @@ decl_module! {
             deleted_files: Vec<(MerkleRoot, u64, u64)>,
             reported_srd_root: MerkleRoot,
             reported_files_root: MerkleRoot,
-            sig: SworkerSignature
+            sig: SworkerSignature,
+            spot_check_proofs: Vec<(ReportSlot, MerkleRoot, MerkleRoot, SworkerSignature)>
         ) -> DispatchResultWithPostInfo {
             let reporter = ensure_signed(origin)?;
             let mut prev_pk = curr_pk.clone();
 
             // 1. Basic check
             ensure!(reported_srd_size < SRD_LIMIT && reported_files_size < FILES_LIMIT && added_files.len() <= FILES_COUNT_LIMIT && deleted_files.len() <= FILES_COUNT_LIMIT, Error::<T>::IllegalWorkReport);
+            ensure!(spot_check_proofs.len() <= SPOT_CHECK_PROOFS_LIMIT, Error::<T>::ExceedSpotCheckProofsLimit);
 
             // 2. Ensure reporter is registered
             ensure!(PubKeys::contains_key(&curr_pk), Error::<T>::IllegalReporter);
@@ -704,9 +863,24 @@ decl_module! {
                 );
             }
 
-            // 10. Finish register
+            // 10. Check the spot check proofs, only the pending spot checks need to verify the sig.
+            // It must be the anchor which the replicas are reported with, a new anchor has no spot checks.
+            let proofs_anchor = Self::pub_keys(&prev_pk).anchor.unwrap_or_else(|| curr_pk.clone());
+            let mut passed_spot_checks: Vec<(ReportSlot, MerkleRoot)> = vec![];
+            for (report_slot, cid, chunk_hash, proof_sig) in spot_check_proofs.iter() {
+                ensure!(*report_slot < slot, Error::<T>::InvalidSpotCheckSlot);
+                if let Some(nonce) = T::MarketInterface::spot_check_nonce(&proofs_anchor, cid, *report_slot) {
+                    ensure!(
+                        Self::spot_check_proof_sig_check(&curr_pk, *report_slot, cid, &nonce, chunk_hash, proof_sig),
+                        Error::<T>::IllegalSpotCheckProofSig
+                    );
+                    passed_spot_checks.push((*report_slot, cid.clone()));
+                }
+            }
+
+            // 11. Finish register
             if is_ab_upgrade {
-                // 10.1 Transfer A's status to B and delete old A's storage status
+                // 11.1 Transfer A's status to B and delete old A's storage status
                 let prev_pk_info = Self::pub_keys(&prev_pk);
                 PubKeys::mutate(&curr_pk, |curr_pk_info| {
                     curr_pk_info.anchor = prev_pk_info.anchor;
@@ -716,14 +890,14 @@ decl_module! {
             } else if is_first_report {
                 let mut pk_info = Self::pub_keys(&curr_pk);
                 match Self::identities(&reporter) {
-                    // 10.2 re-register scenario
+                    // 11.2 re-register scenario
                     Some(mut identity) => {
                         Self::chill_anchor(&identity.anchor);
                         identity.anchor = curr_pk.clone();
                         identity.punishment_deadline = NEW_IDENTITY;
                         <Identities<T>>::insert(&reporter, identity);
                     },
-                    // 10.3 first register scenario
+                    // 11.3 first register scenario
                     None => {
                         let identity = Identity {
                             anchor: curr_pk.clone(),
@@ -737,7 +911,7 @@ decl_module! {
                 PubKeys::insert(&curr_pk, pk_info);
             }
 
-            // 11. 🏋🏻 ‍️Merge work report and update corresponding storages, contains:
+            // 12. 🏋🏻 ‍️Merge work report and update corresponding storages, contains:
             // a. Upsert work report
             // b. Judge if it is resuming reporting(recover all sOrders)
             // c. Update sOrders according to `added_files` and `deleted_files`
@@ -756,7 +930,7 @@ decl_module! {
                 slot,
             );
 
-            // 12. Emit QueueWorkReportSuccess event to be processed by the crust-spower off-chain service
+            // 13. Emit QueueWorkReportSuccess event to be processed by the crust-spower off-chain service
             let id = Self::identities(&reporter).unwrap_or_default();
             let owner = if let Some(group) = id.group { group.clone() } else { reporter.clone() };
             if added_files.len() > 0 || deleted_files.len() > 0 {                
@@ -768,14 +942,30 @@ decl_module! {
                     LastProcessedBlockWorkReports::put(curr_bn - 1);
                 }
 
+                // Commit to the queued replicas, the crust-spower service should replay exactly the same replicas
+                for (cid, file_size, valid_at) in added_files.iter() {
+                    Self::queue_replica_commitment(curr_bn, cid, *file_size, &reporter, &owner, &anchor, slot, curr_bn, *valid_at as BlockNumber, true);
+                }
+                for (cid, file_size, valid_at) in deleted_files.iter() {
+                    Self::queue_replica_commitment(curr_bn, cid, *file_size, &reporter, &owner, &anchor, slot, curr_bn, *valid_at as BlockNumber, false);
+                }
+
                 // Emit the QueueWorkReportSuccess event
                 Self::deposit_event(RawEvent::QueueWorkReportSuccess(anchor, reporter.clone(), owner.clone()));
             }
             
-            // 13. Emit work report event   
+            // 14. Pass the answered spot checks
+            if !passed_spot_checks.is_empty() {
+                for (report_slot, cid) in passed_spot_checks.iter() {
+                    T::MarketInterface::pass_spot_check(&anchor, cid, *report_slot);
+                }
+                Self::deposit_event(RawEvent::SpotCheckProofsSuccess(reporter.clone(), passed_spot_checks.len() as u32));
+            }
+
+            // 15. Emit work report event   
             Self::deposit_event(RawEvent::WorksReportSuccess(reporter.clone(), curr_pk.clone()));
 
-            // 14. Try to free count limitation
+            // 16. Try to free count limitation
             if T::BenefitInterface::maybe_free_count(&owner) {
                return Ok(Pays::No.into());
             }
@@ -783,38 +973,83 @@ decl_module! {
             Ok(Pays::Yes.into())
         }
 
-        /// Set the crust-spower service superior account
+        /// Register a crust-spower service operator, the operator bond would be reserved
         #[weight = 1000]
-        pub fn set_spower_superior(origin, superior: T::AccountId) -> DispatchResult {
+        pub fn register_spower_operator(origin, operator: T::AccountId) -> DispatchResult {
             ensure_root(origin)?;
+            ensure!(!<SpowerOperators<T>>::contains_key(&operator), Error::<T>::SpowerOperatorAlreadyExist);
 
-            SpowerSuperior::<T>::put(superior.clone());
+            let bond = T::SpowerOperatorBond::get();
+            T::Currency::reserve(&operator, bond).map_err(|_| Error::<T>::InsufficientSpowerOperatorBond)?;
+            <SpowerOperators<T>>::insert(&operator, bond);
+            SpowerOperatorsCount::mutate(|count| *count = count.saturating_add(1));
 
-            Self::deposit_event(RawEvent::SetSpowerSuperiorSuccess(superior));
+            Self::deposit_event(RawEvent::RegisterSpowerOperatorSuccess(operator));
             Ok(())
         }
 
-        /// Update sworker spower, which is called by Crust-Spower service
+        /// Remove a crust-spower service operator and unreserve its bond
+        #[weight = 1000]
+        pub fn remove_spower_operator(origin, operator: T::AccountId) -> DispatchResult {
+            ensure_root(origin)?;
+            let bond = Self::spower_operators(&operator).ok_or(Error::<T>::IllegalSpowerOperator)?;
+
+            T::Currency::unreserve(&operator, bond);
+            <SpowerOperators<T>>::remove(&operator);
+            Self::decrease_spower_operators_count();
+
+            Self::deposit_event(RawEvent::RemoveSpowerOperatorSuccess(operator));
+            Ok(())
+        }
+
+        /// Set the number of spower operators who should attest the same batch
+        #[weight = 1000]
+        pub fn set_spower_attest_threshold(origin, threshold: u32) -> DispatchResult {
+            ensure_root(origin)?;
+            ensure!(!threshold.is_zero() && threshold <= Self::spower_operators_count(), Error::<T>::InvalidSpowerAttestThreshold);
+
+            SpowerAttestThreshold::put(threshold);
+
+            Self::deposit_event(RawEvent::SetSpowerAttestThresholdSuccess(threshold));
+            Ok(())
+        }
+
+        /// Update sworker spower, which is called by Crust-Spower service operators.
+        /// It's only applied when enough operators submit the same data for the same batch block.
         /// Arguments:
         /// changed_spowers: 
         ///     Vec<(SworkerAnchor, changed_spower_value)>
         /// changed_files:
         ///     Specify the files changed spower data, the data structure is
         ///     Vec<(cid, spower, Vec<(owner, who, anchor, created_at)>)>
+        /// batch_block:
+        ///     The block this batch is built at, should be larger than the one of the last applied batch
         #[weight = T::WeightInfo::update_spower(changed_spowers.len() as u32, changed_files.len() as u32)]
         pub fn update_spower(
             origin,
             changed_spowers: Vec<(SworkerAnchor, i64)>,
             changed_files: Vec<(MerkleRoot, u64, Vec<(T::AccountId, T::AccountId, SworkerAnchor, Option<BlockNumber>)>)>,
+            batch_block: BlockNumber
         ) -> DispatchResultWithPostInfo {
             let caller = ensure_signed(origin)?;
-            let maybe_superior = Self::spower_superior();
 
-            // 1. Check if superior is set and the caller is superior
-            ensure!(maybe_superior.is_some(), Error::<T>::SpowerSuperiorNotSet);
-            ensure!(Some(&caller) == maybe_superior.as_ref(), Error::<T>::IllegalSpowerSuperior);
+            // 1. Normalise the batch, operators may submit the same data in different orders.
+            // The sorts are stable, so the duplicated entries keep their applying order.
+            let mut changed_spowers = changed_spowers;
+            changed_spowers.sort_by(|a, b| a.0.cmp(&b.0));
+            let mut changed_files = changed_files;
+            changed_files.sort_by(|a, b| a.0.cmp(&b.0));
+            for (_, _, changed_replicas) in changed_files.iter_mut() {
+                changed_replicas.sort_by(|a, b| a.0.cmp(&b.0));
+            }
+
+            // 2. Attest the batch, only apply it when enough spower operators attest the same one
+            let batch_hash: Hash = (b"update_spower", &changed_spowers, &changed_files).using_encoded(blake2_256).into();
+            if !Self::attest_spower_batch(&caller, b"update_spower", batch_block, batch_hash)? {
+                return Ok(Pays::No.into());
+            }
 
-            // 2. Update sworker spower
+            // 3. Update sworker spower
             for (anchor, changed_spower) in changed_spowers.iter() {
                 WorkReports::mutate_exists(anchor, |maybe_wr| match *maybe_wr {
                     Some(WorkReport { ref mut spower, .. }) => {
@@ -828,13 +1063,13 @@ decl_module! {
                 });
             }
 
-            // 3. Update the file new spower in pallet_market
+            // 4. Update the file new spower in pallet_market
             T::MarketInterface::update_files_spower(&changed_files);
 
-            // 4. Set the LastSpowerUpdateBlock value, which is the latest block in updated_blocks
+            // 5. Set the LastSpowerUpdateBlock value, which is the latest block in updated_blocks
             LastSpowerUpdateBlock::put(Self::get_current_block_number());
 
-            // 5. Emit the event
+            // 6. Emit the event
             Self::deposit_event(RawEvent::UpdateSpowerSuccess(caller, 
                 <system::Module<T>>::block_number(), 
                 changed_spowers.len() as u32, 
@@ -1417,6 +1652,26 @@ impl<T: Config> Module<T> {
         utils::verify_p256_sig(curr_pk, &data, sig)
     }
 
+    fn spot_check_proof_sig_check(
+        curr_pk: &SworkerPubKey,
+        report_slot: ReportSlot,
+        cid: &MerkleRoot,
+        nonce: &SpotCheckNonce,
+        chunk_hash: &MerkleRoot,
+        sig: &SworkerSignature
+    ) -> bool {
+        let report_slot_bytes = utils::encode_u64_to_string_to_bytes(report_slot);
+        let data: Vec<u8> = [
+            &curr_pk[..],
+            &report_slot_bytes[..],
+            &cid[..],
+            &nonce[..],
+            &chunk_hash[..]
+        ].concat();
+
+        utils::verify_p256_sig(curr_pk, &data, sig)
+    }
+
     fn register_payload_sig_check(
         account_id: &Vec<u8>,
         code: &Vec<u8>,
@@ -1481,6 +1736,103 @@ impl<T: Config> Module<T> {
         Ok(())
     }
 
+    /// Chain the commitment of one replica queued by report works to the head
+    pub fn chain_replica_commitment(
+        head: &Hash,
+        cid: &MerkleRoot,
+        file_size: u64,
+        reporter: &T::AccountId,
+        owner: &T::AccountId,
+        anchor: &SworkerAnchor,
+        report_slot: ReportSlot,
+        report_block: BlockNumber,
+        valid_at: BlockNumber,
+        is_added: bool
+    ) -> Hash {
+        (head, cid, file_size, reporter, owner, anchor, report_slot, report_block, valid_at, is_added).using_encoded(blake2_256).into()
+    }
+
+    /// Chain the commitment of one replica queued in the block
+    pub fn queue_replica_commitment(
+        bn: BlockNumber,
+        cid: &MerkleRoot,
+        file_size: u64,
+        reporter: &T::AccountId,
+        owner: &T::AccountId,
+        anchor: &SworkerAnchor,
+        report_slot: ReportSlot,
+        report_block: BlockNumber,
+        valid_at: BlockNumber,
+        is_added: bool
+    ) {
+        let commitment = Self::chain_replica_commitment(&Self::latest_replicas_commitment(), cid, file_size, reporter, owner, anchor, report_slot, report_block, valid_at, is_added);
+        LatestReplicasCommitment::put(commitment);
+        if !ReplicasCommitments::contains_key(bn) {
+            let (first, next) = Self::replicas_commitment_blocks_range();
+            ReplicasCommitmentBlocks::insert(next, bn);
+            ReplicasCommitmentBlocksRange::put((first, next + 1));
+        }
+        ReplicasCommitments::insert(bn, commitment);
+    }
+
+    /// Prune the replicas commitments until the last processed block, the rest would be pruned in the next update
+    fn prune_replicas_commitments(last_processed_block: BlockNumber) {
+        let (mut first, next) = Self::replicas_commitment_blocks_range();
+        let mut pruned_count: u64 = 0;
+        while first < next && pruned_count < REPLICAS_COMMITMENTS_PRUNE_LIMIT {
+            let bn = Self::replicas_commitment_blocks(first).unwrap_or_default();
+            if bn > last_processed_block {
+                break;
+            }
+            ReplicasCommitmentBlocks::remove(first);
+            ReplicasCommitments::remove(bn);
+            first += 1;
+            pruned_count += 1;
+        }
+        ReplicasCommitmentBlocksRange::put((first, next));
+    }
+
+    fn clear_spower_batch_round(round: &Hash) {
+        <SpowerBatchAttestations<T>>::remove_prefix(round);
+        <SpowerBatchVotes<T>>::remove_prefix(round);
+        SpowerBatchRounds::remove(round);
+    }
+
+    /// Remove the unapproved rounds which expire at this block
+    fn expire_spower_batch_rounds(now: BlockNumber) -> Weight {
+        let rounds = SpowerBatchRoundsToExpire::take(now);
+        for round in rounds.iter() {
+            // The round may be approved already
+            if Self::spower_batch_rounds(round) == Some(now) {
+                Self::clear_spower_batch_round(round);
+            }
+        }
+        T::DbWeight::get().reads_writes(1, 1)
+            .saturating_add(T::DbWeight::get().reads_writes(1, 3).saturating_mul(rounds.len() as Weight))
+    }
+
+    fn slash_spower_operator(who: &T::AccountId, block: BlockNumber) {
+        if let Some(bond) = <SpowerOperators<T>>::take(who) {
+            let (_, unslashed) = T::Currency::slash_reserved(who, bond);
+            Self::deposit_event(RawEvent::SpowerOperatorSlashed(who.clone(), bond.saturating_sub(unslashed), block));
+            Self::decrease_spower_operators_count();
+        }
+    }
+
+    /// Keep the attest threshold reachable by the remaining spower operators
+    fn decrease_spower_operators_count() {
+        let count = SpowerOperatorsCount::mutate(|count| {
+            *count = count.saturating_sub(1);
+            *count
+        });
+        let threshold = Self::spower_attest_threshold();
+        if threshold > count && threshold > 1 {
+            let new_threshold = count.max(1);
+            SpowerAttestThreshold::put(new_threshold);
+            Self::deposit_event(RawEvent::SpowerAttestThresholdDecreased(new_threshold));
+        }
+    }
+
     fn get_current_block_number() -> BlockNumber {
         let current_block_number = <system::Module<T>>::block_number();
         TryInto::<u32>::try_into(current_block_number).ok().unwrap()
@@ -1503,6 +1855,7 @@ decl_event!(
     where
         AccountId = <T as system::Config>::AccountId,
         BlockNumber = <T as system::Config>::BlockNumber,
+        Balance = BalanceOf<T>,
     {
         /// sWorker registration success.
         /// The first item is the account who try to register.
@@ -1550,13 +1903,33 @@ decl_event!(
         /// The second item is the account who send the work report
         /// The third item is the owner account
         QueueWorkReportSuccess(SworkerAnchor, AccountId, AccountId),
-        /// Set the crust-spower service superior account.
-        SetSpowerSuperiorSuccess(AccountId),
+        /// Register the crust-spower service operator success.
+        RegisterSpowerOperatorSuccess(AccountId),
+        /// Remove the crust-spower service operator success.
+        RemoveSpowerOperatorSuccess(AccountId),
+        /// Set the spower attest threshold success.
+        SetSpowerAttestThresholdSuccess(u32),
+        /// The spower attest threshold is decreased to the remaining spower operators count.
+        SpowerAttestThresholdDecreased(u32),
+        /// Attest the spower batch success
+        /// The first item is the spower operator
+        /// The second item is the batch hash
+        /// The third item is the votes count of this batch
+        AttestSpowerBatchSuccess(AccountId, Hash, u32),
+        /// The spower operator is slashed for attesting different batches in the same round
+        /// The first item is the spower operator
+        /// The second item is the slashed amount
+        /// The third item is the batch block of the round
+        SpowerOperatorSlashed(AccountId, Balance, u32),
         /// Update spower success
         /// The first item is the account who update the spower.
         /// The second item is the current block number
         /// The third item is the updated sworkers count for sworker::WorkReports
         /// The fourth item is the updated files count for market::FilesV2
         UpdateSpowerSuccess(AccountId, BlockNumber, u32, u32),
+        /// Answer spot checks success
+        /// The first item is the account who answers the spot checks
+        /// The second item is the passed spot checks count
+        SpotCheckProofsSuccess(AccountId, u32),
     }
 );
@@ -183,6 +183,7 @@ impl market::Config for Test {
     type MaximumFileSize = MaximumFileSize;
     type WeightInfo = market::weight::WeightInfo<Test>;
     type RenewRewardRatio = RenewRewardRatio;
+    type Randomness = TestRandomness;
 }
 
 pub struct TestWorksInterface;
@@ -214,6 +215,10 @@ impl<AID> BenefitInterface<AID, BalanceOf<Test>, NegativeImbalanceOf<Test>> for
         return true;
     }
 
+    fn maybe_compound_reward(_: &AID, _: BalanceOf<Test>) -> BalanceOf<Test> {
+        Zero::zero()
+    }
+
     fn get_collateral_and_reward(_: &AID) -> (BalanceOf<Test>, BalanceOf<Test>) {
         (Zero::zero(), Zero::zero())
     }
@@ -226,6 +231,7 @@ impl<AID> BenefitInterface<AID, BalanceOf<Test>, NegativeImbalanceOf<Test>> for
 parameter_types! {
     pub const PunishmentSlots: u32 = 4;
     pub const MaxGroupSize: u32 = 4;
+    pub const SpowerOperatorBond: Balance = 0;
 }
 
 impl Config for Test {
@@ -235,6 +241,7 @@ impl Config for Test {
     type Works = TestWorksInterface;
     type MarketInterface = Market;
     type MaxGroupSize = MaxGroupSize;
+    type SpowerOperatorBond = SpowerOperatorBond;
     type BenefitInterface = TestBenefitInterface;
     type WeightInfo = weight::WeightInfo<Test>;
 }
@@ -814,13 +821,17 @@ pub fn add_who_into_replica(
     report_block: BlockNumber,
     valid_at: BlockNumber) {
     
+    // Fake the replicas queued by report works
+    let bn = Swork::last_processed_block_work_reports() + 1;
+    <LatestReplicasCommitment>::put(Swork::processed_replicas_commitment());
+    Swork::queue_replica_commitment(bn, cid, reported_size, &who, &owner, &anchor, report_slot, report_block, valid_at, true);
     assert_ok!(Market::update_replicas(
             Origin::signed(SPOWER.clone()), 
             vec![(cid.clone(), 
                   reported_size, 
                   vec![(who.clone(), owner.clone(), anchor.clone(), report_slot, report_block, valid_at, true)]
                 )], 
-            400));
+            bn));
 }
 
 pub fn delete_replica(
@@ -833,11 +844,15 @@ pub fn delete_replica(
     report_block: BlockNumber,
     valid_at: BlockNumber) {
     
+    // Fake the replicas queued by report works
+    let bn = Swork::last_processed_block_work_reports() + 1;
+    <LatestReplicasCommitment>::put(Swork::processed_replicas_commitment());
+    Swork::queue_replica_commitment(bn, cid, reported_size, &who, &owner, &anchor, report_slot, report_block, valid_at, false);
     assert_ok!(Market::update_replicas(
             Origin::signed(SPOWER.clone()), 
             vec![(cid.clone(), 
                   reported_size, 
                   vec![(who.clone(), owner.clone(), anchor.clone(), report_slot, report_block, valid_at, false)]
                 )], 
-            400));
+            bn));
 }
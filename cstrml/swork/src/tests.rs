@@ -409,7 +409,7 @@ fn report_works_should_work() {
             let file_f = "QmdwgqZy1MZBfWPi7GcxVsYgJEtmvHg6rsLzbCej3tf3oF".as_bytes().to_vec(); // F file
             let file_h = "QmdwgqZy1MZBfWPi7GcxVsYgJEtmvHg6rsLzbCej3tf3oH".as_bytes().to_vec(); // H file
 
-            assert_ok!(Market::set_spower_superior(Origin::root(), spower.clone()));
+            assert_ok!(Swork::register_spower_operator(Origin::root(), spower.clone()));
 
             register(&legal_pk, LegalCode::get());
             add_not_live_files();
@@ -430,7 +430,8 @@ fn report_works_should_work() {
                 legal_wr_info.deleted_files.clone(),
                 legal_wr_info.srd_root,
                 legal_wr_info.files_root,
-                legal_wr_info.sig
+                legal_wr_info.sig,
+                vec![]
             ));
             add_who_into_replica(&file_f, 134289408, reporter.clone(), reporter.clone(), legal_pk.clone(), legal_wr_info.block_number, 303, 303);
             add_who_into_replica(&file_h, 268578816, reporter.clone(), reporter.clone(), legal_pk.clone(), legal_wr_info.block_number, 303, 303);
@@ -522,7 +523,7 @@ fn report_works_for_invalid_cids_should_work() {
             let file_f = "QmdwgqZy1MZBfWPi7GcxVsYgJEtmvHg6rsLzbCej3tf3oF".as_bytes().to_vec(); // F file
             let file_h = "QmdwgqZy1MZBfWPi7GcxVsYgJEtmvHg6rsLzbCej3tf3oH".as_bytes().to_vec(); // H file
 
-            assert_ok!(Market::set_spower_superior(Origin::root(), spower.clone()));
+            assert_ok!(Swork::register_spower_operator(Origin::root(), spower.clone()));
             
             register(&legal_pk, LegalCode::get());
 
@@ -542,7 +543,8 @@ fn report_works_for_invalid_cids_should_work() {
                 legal_wr_info.deleted_files.clone(),
                 legal_wr_info.srd_root,
                 legal_wr_info.files_root,
-                legal_wr_info.sig
+                legal_wr_info.sig,
+                vec![]
             ));
             // Since file cid is not in FilesV2, the following call should trigger the T::SworkerInterface::update_illegal_file_replicas_count
             // which should make the Swork::Added_Files_Count to become 0 again
@@ -600,7 +602,8 @@ fn report_works_should_work_without_files() {
                 legal_wr_info.deleted_files,
                 legal_wr_info.srd_root,
                 legal_wr_info.files_root,
-                legal_wr_info.sig
+                legal_wr_info.sig,
+                vec![]
             ));
 
             // Check workloads after work report
@@ -636,7 +639,8 @@ fn report_works_should_work_with_added_and_deleted_files() {
                 legal_wr_info.deleted_files,
                 legal_wr_info.srd_root,
                 legal_wr_info.files_root,
-                legal_wr_info.sig
+                legal_wr_info.sig,
+                vec![]
             ));
 
             // Generate 606 blocks
@@ -658,7 +662,8 @@ fn report_works_should_work_with_added_and_deleted_files() {
                     legal_wr_info_with_added_and_deleted_files.deleted_files,
                     legal_wr_info_with_added_and_deleted_files.srd_root,
                     legal_wr_info_with_added_and_deleted_files.files_root,
-                    legal_wr_info_with_added_and_deleted_files.sig
+                    legal_wr_info_with_added_and_deleted_files.sig,
+                    vec![]
                 )
             );
         });
@@ -688,7 +693,8 @@ fn report_works_should_failed_with_not_registered() {
                     legal_wr_info.deleted_files,
                     legal_wr_info.srd_root,
                     legal_wr_info.files_root,
-                    legal_wr_info.sig
+                    legal_wr_info.sig,
+                    vec![]
                 ),
                 DispatchError::Module {
                     index: 2,
@@ -728,7 +734,8 @@ fn report_works_should_failed_with_illegal_code() {
                     legal_wr_info.deleted_files,
                     legal_wr_info.srd_root,
                     legal_wr_info.files_root,
-                    legal_wr_info.sig
+                    legal_wr_info.sig,
+                    vec![]
                 ),
                 DispatchError::Module {
                     index: 2,
@@ -766,7 +773,8 @@ fn report_works_should_failed_with_wrong_timing() {
                     illegal_wr_info.deleted_files,
                     illegal_wr_info.srd_root,
                     illegal_wr_info.files_root,
-                    illegal_wr_info.sig
+                    illegal_wr_info.sig,
+                    vec![]
                 ),
                 DispatchError::Module {
                     index: 2,
@@ -805,7 +813,8 @@ fn report_works_should_failed_with_illegal_sig() {
                     illegal_wr_info.deleted_files,
                     illegal_wr_info.srd_root,
                     illegal_wr_info.files_root,
-                    illegal_wr_info.sig
+                    illegal_wr_info.sig,
+                    vec![]
                 ),
                 DispatchError::Module {
                     index: 2,
@@ -854,7 +863,8 @@ fn report_works_should_failed_with_illegal_file_transition() {
                     illegal_wr_info.deleted_files,
                     illegal_wr_info.srd_root,
                     illegal_wr_info.files_root,
-                    illegal_wr_info.sig
+                    illegal_wr_info.sig,
+                    vec![]
                 ),
                 DispatchError::Module {
                     index: 2,
@@ -901,7 +911,8 @@ fn incremental_report_should_work_without_change() {
                 legal_wr_info.deleted_files,
                 legal_wr_info.srd_root,
                 legal_wr_info.files_root,
-                legal_wr_info.sig
+                legal_wr_info.sig,
+                vec![]
             ));
         });
 }
@@ -914,7 +925,7 @@ fn incremental_report_should_work_with_files_change() {
 
             let spower = SPOWER;
 
-            assert_ok!(Market::set_spower_superior(Origin::root(), spower.clone()));
+            assert_ok!(Swork::register_spower_operator(Origin::root(), spower.clone()));
 
             // Generate 303 blocks first
             run_to_block(303);
@@ -955,7 +966,8 @@ fn incremental_report_should_work_with_files_change() {
                 legal_wr_info.deleted_files.clone(),
                 legal_wr_info.srd_root,
                 legal_wr_info.files_root,
-                legal_wr_info.sig
+                legal_wr_info.sig,
+                vec![]
             ));
 
             for deleted_file in legal_wr_info.deleted_files {
@@ -1008,7 +1020,8 @@ fn incremental_report_should_failed_with_root_change() {
                     illegal_wr_info.deleted_files,
                     illegal_wr_info.srd_root,
                     illegal_wr_info.files_root,
-                    illegal_wr_info.sig
+                    illegal_wr_info.sig,
+                    vec![]
                 ),
                 DispatchError::Module {
                     index: 2,
@@ -1056,7 +1069,8 @@ fn incremental_report_should_failed_with_wrong_file_size_change() {
                     illegal_wr_info.deleted_files,
                     illegal_wr_info.srd_root,
                     illegal_wr_info.files_root,
-                    illegal_wr_info.sig
+                    illegal_wr_info.sig,
+                    vec![]
                 ),
                 DispatchError::Module {
                     index: 2,
@@ -1112,7 +1126,8 @@ fn update_identities_should_work() {
                 legal_wr_info.deleted_files,
                 legal_wr_info.srd_root,
                 legal_wr_info.files_root,
-                legal_wr_info.sig
+                legal_wr_info.sig,
+                vec![]
             ));
 
             // 3. Free and spower should already been updated
@@ -1200,7 +1215,7 @@ fn ab_upgrade_should_work() {
             let a_pk = a_wr_info.curr_pk.clone();
             let b_pk = b_wr_info_1.curr_pk.clone();
 
-            assert_ok!(Market::set_spower_superior(Origin::root(), spower.clone()));
+            assert_ok!(Swork::register_spower_operator(Origin::root(), spower.clone()));
 
             // 0. Initial setup
             register(&a_pk, LegalCode::get());
@@ -1232,7 +1247,8 @@ fn ab_upgrade_should_work() {
                 a_wr_info.deleted_files,
                 a_wr_info.srd_root,
                 a_wr_info.files_root,
-                a_wr_info.sig
+                a_wr_info.sig,
+                vec![]
             ));
 
             // 3. Check A's work report and free & spower
@@ -1269,7 +1285,8 @@ fn ab_upgrade_should_work() {
                 b_wr_info_1.deleted_files,
                 b_wr_info_1.srd_root,
                 b_wr_info_1.files_root,
-                b_wr_info_1.sig
+                b_wr_info_1.sig,
+                vec![]
             ));
 
             // 7. Check B's work report and free & spower
@@ -1306,7 +1323,8 @@ fn ab_upgrade_should_work() {
                 b_wr_info_2.deleted_files,
                 b_wr_info_2.srd_root,
                 b_wr_info_2.files_root,
-                b_wr_info_2.sig
+                b_wr_info_2.sig,
+                vec![]
             ));
             // b_wr_info_2 contains 1 added_file (file_c) and 1 deleted_file (file_b)
             let file_b = "QmdwgqZy1MZBfWPi7GcxVsYgJEtmvHg6rsLzbCej3tf3oB".as_bytes().to_vec(); // B file
@@ -1369,7 +1387,8 @@ fn ab_upgrade_expire_should_work() {
                 wr_info_300.deleted_files,
                 wr_info_300.srd_root,
                 wr_info_300.files_root,
-                wr_info_300.sig
+                wr_info_300.sig,
+                vec![]
             ));
 
             // 3. Runs to 606
@@ -1389,7 +1408,8 @@ fn ab_upgrade_expire_should_work() {
                     wr_info_600.deleted_files,
                     wr_info_600.srd_root,
                     wr_info_600.files_root,
-                    wr_info_600.sig
+                    wr_info_600.sig,
+                    vec![]
                 ),
                 DispatchError::Module {
                     index: 2,
@@ -1436,7 +1456,8 @@ fn ab_upgrade_should_failed_with_files_size_unmatch() {
                 a_wr_info.deleted_files,
                 a_wr_info.srd_root,
                 a_wr_info.files_root,
-                a_wr_info.sig
+                a_wr_info.sig,
+                vec![]
             ));
 
             // 2. Runs to 606, and do sWorker upgrade
@@ -1460,7 +1481,8 @@ fn ab_upgrade_should_failed_with_files_size_unmatch() {
                     b_wr_info.deleted_files,
                     b_wr_info.srd_root,
                     b_wr_info.files_root,
-                    b_wr_info.sig
+                    b_wr_info.sig,
+                    vec![]
                 ),
                 DispatchError::Module {
                     index: 2,
@@ -1758,7 +1780,8 @@ fn report_works_should_fail_due_to_reporter_is_group_owner() {
                     legal_wr_info.deleted_files,
                     legal_wr_info.srd_root,
                     legal_wr_info.files_root,
-                    legal_wr_info.sig
+                    legal_wr_info.sig,
+                    vec![]
                 ),
                 DispatchError::Module {
                     index: 2,
@@ -1932,7 +1955,7 @@ fn join_group_should_work_for_spower_in_work_report() {
             let ferdie = Sr25519Keyring::Ferdie.to_account_id();
             let spower = SPOWER;
 
-            assert_ok!(Market::set_spower_superior(Origin::root(), spower.clone()));
+            assert_ok!(Swork::register_spower_operator(Origin::root(), spower.clone()));
 
             // Get work report in 300 slot fo alice, bob and eve
             let alice_wr_info = group_work_report_alice_300();
@@ -2006,7 +2029,8 @@ fn join_group_should_work_for_spower_in_work_report() {
                 alice_wr_info.deleted_files,
                 alice_wr_info.srd_root,
                 alice_wr_info.files_root,
-                alice_wr_info.sig
+                alice_wr_info.sig,
+                vec![]
             ));
 
             add_who_into_replica(&file_a, 13, alice.clone(), ferdie.clone(), a_pk.clone(), alice_wr_info.block_number, 303, 303);
@@ -2095,7 +2119,8 @@ fn join_group_should_work_for_spower_in_work_report() {
                 bob_wr_info.deleted_files,
                 bob_wr_info.srd_root,
                 bob_wr_info.files_root,
-                bob_wr_info.sig
+                bob_wr_info.sig,
+                vec![]
             ));
 
             add_who_into_replica(&file_b, 7, bob.clone(), ferdie.clone(), b_pk.clone(), bob_wr_info.block_number, 303, 303);
@@ -2212,7 +2237,8 @@ fn join_group_should_work_for_spower_in_work_report() {
                 eve_wr_info.deleted_files,
                 eve_wr_info.srd_root,
                 eve_wr_info.files_root,
-                eve_wr_info.sig
+                eve_wr_info.sig,
+                vec![]
             ));
 
             add_who_into_replica(&file_c, 37, eve.clone(), ferdie.clone(), c_pk.clone(), eve_wr_info.block_number, 303, 303);
@@ -2321,7 +2347,8 @@ fn join_group_should_work_for_spower_in_work_report() {
                 bob_wr_info.deleted_files,
                 bob_wr_info.srd_root,
                 bob_wr_info.files_root,
-                bob_wr_info.sig
+                bob_wr_info.sig,
+                vec![]
             ));
             delete_replica(&file_b, 7, bob.clone(), ferdie.clone(), b_pk.clone(), bob_wr_info.block_number, 603, 603);
             delete_replica(&file_c, 37, bob.clone(), ferdie.clone(), b_pk.clone(), bob_wr_info.block_number, 603, 603);
@@ -2414,7 +2441,8 @@ fn join_group_should_work_for_spower_in_work_report() {
                 eve_wr_info.deleted_files,
                 eve_wr_info.srd_root,
                 eve_wr_info.files_root,
-                eve_wr_info.sig
+                eve_wr_info.sig,
+                vec![]
             ));
             delete_replica(&file_c, 37, eve.clone(), ferdie.clone(), c_pk.clone(), eve_wr_info.block_number, 603, 603);
             delete_replica(&file_d, 55, eve.clone(), ferdie.clone(), c_pk.clone(), eve_wr_info.block_number, 603, 603);
@@ -2547,7 +2575,8 @@ fn join_group_should_work_for_spower_in_work_report() {
                 alice_wr_info.deleted_files,
                 alice_wr_info.srd_root,
                 alice_wr_info.files_root,
-                alice_wr_info.sig
+                alice_wr_info.sig,
+                vec![]
             ));
             delete_replica(&file_a, 13, alice.clone(), ferdie.clone(), a_pk.clone(), alice_wr_info.block_number, 1503, 1503);
             delete_replica(&file_b, 7, alice.clone(), ferdie.clone(), a_pk.clone(), alice_wr_info.block_number, 1503, 1503);
@@ -2576,12 +2605,11 @@ fn join_group_should_work_for_spower_in_work_report() {
 
             // calculate_reward doesn't update spower now, the offchain crust-spower service will listen to the FileClosed event 
             // and update the spower for file_c and file_d
-            assert_ok!(Swork::set_spower_superior(Origin::root(), spower.clone()));
             assert_ok!(Swork::update_spower(
             Origin::signed(spower.clone()), 
             vec![(b_pk.clone(), (0 as i64 - 55 as i64)),
                  (a_pk.clone(), (0 as i64 - 37 as i64))],
-            vec![]));
+            vec![], 1));
 
             // d has gone!
             assert_eq!(Swork::work_reports(&b_pk).unwrap(), WorkReport {
@@ -2629,7 +2657,7 @@ fn join_group_should_work_for_stake_limit() {
             let file_d = "QmdwgqZy1MZBfWPi7GcxVsYgJEtmvHg6rsLzbCej3tf3oD".as_bytes().to_vec(); // D file
             let file_e = "QmdwgqZy1MZBfWPi7GcxVsYgJEtmvHg6rsLzbCej3tf3oE".as_bytes().to_vec(); // E file
 
-            assert_ok!(Market::set_spower_superior(Origin::root(), spower.clone()));
+            assert_ok!(Swork::register_spower_operator(Origin::root(), spower.clone()));
 
             register(&a_pk, LegalCode::get());
             register(&b_pk, LegalCode::get());
@@ -2689,7 +2717,8 @@ fn join_group_should_work_for_stake_limit() {
                 alice_wr_info.deleted_files,
                 alice_wr_info.srd_root,
                 alice_wr_info.files_root,
-                alice_wr_info.sig
+                alice_wr_info.sig,
+                vec![]
             ));
             add_who_into_replica(&file_a, 13, alice.clone(), ferdie.clone(), a_pk.clone(), alice_wr_info.block_number, 303, 303);
             add_who_into_replica(&file_b, 7, alice.clone(), ferdie.clone(), a_pk.clone(), alice_wr_info.block_number, 303, 303);
@@ -2706,7 +2735,8 @@ fn join_group_should_work_for_stake_limit() {
                 bob_wr_info.deleted_files,
                 bob_wr_info.srd_root,
                 bob_wr_info.files_root,
-                bob_wr_info.sig
+                bob_wr_info.sig,
+                vec![]
             ));
             add_who_into_replica(&file_b, 7, bob.clone(), ferdie.clone(), b_pk.clone(), bob_wr_info.block_number, 303, 303);
             add_who_into_replica(&file_c, 37, bob.clone(), ferdie.clone(), b_pk.clone(), bob_wr_info.block_number, 303, 303);
@@ -2723,7 +2753,8 @@ fn join_group_should_work_for_stake_limit() {
                 eve_wr_info.deleted_files,
                 eve_wr_info.srd_root,
                 eve_wr_info.files_root,
-                eve_wr_info.sig
+                eve_wr_info.sig,
+                vec![]
             ));
             add_who_into_replica(&file_c, 37, eve.clone(), ferdie.clone(), c_pk.clone(), eve_wr_info.block_number, 303, 303);
             add_who_into_replica(&file_d, 55, eve.clone(), ferdie.clone(), c_pk.clone(), eve_wr_info.block_number, 303, 303);
@@ -2811,7 +2842,8 @@ fn quit_group_should_work_for_stake_limit() {
                 alice_wr_info.deleted_files,
                 alice_wr_info.srd_root,
                 alice_wr_info.files_root,
-                alice_wr_info.sig
+                alice_wr_info.sig,
+                vec![]
             ));
 
             run_to_block(603);
@@ -2848,7 +2880,7 @@ fn kick_out_should_work_for_stake_limit() {
             let file_b = "QmdwgqZy1MZBfWPi7GcxVsYgJEtmvHg6rsLzbCej3tf3oB".as_bytes().to_vec(); // B file
             let file_c = "QmdwgqZy1MZBfWPi7GcxVsYgJEtmvHg6rsLzbCej3tf3oC".as_bytes().to_vec(); // C file
 
-            assert_ok!(Market::set_spower_superior(Origin::root(), spower.clone()));
+            assert_ok!(Swork::register_spower_operator(Origin::root(), spower.clone()));
 
             assert_noop!(
                 Swork::kick_out(
@@ -2915,7 +2947,8 @@ fn kick_out_should_work_for_stake_limit() {
                 alice_wr_info.deleted_files,
                 alice_wr_info.srd_root,
                 alice_wr_info.files_root,
-                alice_wr_info.sig
+                alice_wr_info.sig,
+                vec![]
             ));
             add_who_into_replica(&file_a, 13, alice.clone(), ferdie.clone(), a_pk.clone(), alice_wr_info.block_number, 303, 303);
             add_who_into_replica(&file_b, 7, alice.clone(), ferdie.clone(), a_pk.clone(), alice_wr_info.block_number, 303, 303);
@@ -2970,7 +3003,7 @@ fn punishment_by_offline_should_work_for_stake_limit() {
             let alice_wr_info = group_work_report_alice_300();
             let a_pk = alice_wr_info.curr_pk.clone();
 
-            assert_ok!(Market::set_spower_superior(Origin::root(), spower.clone()));
+            assert_ok!(Swork::register_spower_operator(Origin::root(), spower.clone()));
 
             register(&a_pk, LegalCode::get());
             register_identity(&alice, &a_pk, &a_pk);
@@ -3006,7 +3039,8 @@ fn punishment_by_offline_should_work_for_stake_limit() {
                 alice_wr_info.deleted_files,
                 alice_wr_info.srd_root,
                 alice_wr_info.files_root,
-                alice_wr_info.sig
+                alice_wr_info.sig,
+                vec![]
             ));
             add_who_into_replica(&file_a, 13, alice.clone(), ferdie.clone(), a_pk.clone(), alice_wr_info.block_number, 303, 303);
             add_who_into_replica(&file_b, 7, alice.clone(), ferdie.clone(), a_pk.clone(), alice_wr_info.block_number, 303, 303);
@@ -3161,7 +3195,8 @@ fn basic_check_should_work() {
                     legal_wr_info.deleted_files,
                     legal_wr_info.srd_root,
                     legal_wr_info.files_root,
-                    legal_wr_info.sig
+                    legal_wr_info.sig,
+                    vec![]
                 ),
                 DispatchError::Module {
                     index: 2,
@@ -3186,7 +3221,8 @@ fn basic_check_should_work() {
                     legal_wr_info.deleted_files,
                     legal_wr_info.srd_root,
                     legal_wr_info.files_root,
-                    legal_wr_info.sig
+                    legal_wr_info.sig,
+                    vec![]
                 ),
                 DispatchError::Module {
                     index: 2,
@@ -3213,7 +3249,8 @@ fn basic_check_should_work() {
                     legal_wr_info.deleted_files,
                     legal_wr_info.srd_root,
                     legal_wr_info.files_root,
-                    legal_wr_info.sig
+                    legal_wr_info.sig,
+                    vec![]
                 ),
                 DispatchError::Module {
                     index: 2,
@@ -3579,8 +3616,7 @@ fn spower_delay_should_work() {
             let file_f = "QmdwgqZy1MZBfWPi7GcxVsYgJEtmvHg6rsLzbCej3tf3oF".as_bytes().to_vec(); // F file
             let file_h = "QmdwgqZy1MZBfWPi7GcxVsYgJEtmvHg6rsLzbCej3tf3oH".as_bytes().to_vec(); // H file
 
-            assert_ok!(Market::set_spower_superior(Origin::root(), spower.clone()));
-            assert_ok!(Swork::set_spower_superior(Origin::root(), spower.clone()));
+            assert_ok!(Swork::register_spower_operator(Origin::root(), spower.clone()));
 
             register(&legal_pk, LegalCode::get());
             add_not_live_files();
@@ -3601,7 +3637,8 @@ fn spower_delay_should_work() {
                 legal_wr_info.deleted_files.clone(),
                 legal_wr_info.srd_root,
                 legal_wr_info.files_root,
-                legal_wr_info.sig
+                legal_wr_info.sig,
+                vec![]
             ));
             add_who_into_replica(&file_f, 134289408, reporter.clone(), reporter.clone(), legal_pk.clone(), legal_wr_info.block_number, 303, 303);
             add_who_into_replica(&file_h, 268578816, reporter.clone(), reporter.clone(), legal_pk.clone(), legal_wr_info.block_number, 303, 303);
@@ -3671,7 +3708,7 @@ fn spower_delay_should_work() {
             assert_ok!(Swork::update_spower(
                 Origin::signed(spower.clone()), 
                 vec![(legal_pk.clone(), (file_spower_0 as i64 - file_size_0 as i64))],
-                vec![(cid_0.clone(), file_spower_0, vec![(reporter.clone(), reporter.clone(), legal_pk.clone(), None)])]));
+                vec![(cid_0.clone(), file_spower_0, vec![(reporter.clone(), reporter.clone(), legal_pk.clone(), None)])], 1));
 
             assert_eq!(Swork::work_reports(&legal_pk).unwrap(), WorkReport {
                 report_slot: 300,
@@ -3689,7 +3726,7 @@ fn spower_delay_should_work() {
             assert_ok!(Swork::update_spower(
                 Origin::signed(spower.clone()), 
                 vec![(legal_pk.clone(), (file_spower_1 as i64 - file_size_1 as i64))],
-                vec![(cid_1.clone(), file_spower_1, vec![(reporter.clone(), reporter.clone(), legal_pk.clone(), None)])]));
+                vec![(cid_1.clone(), file_spower_1, vec![(reporter.clone(), reporter.clone(), legal_pk.clone(), None)])], 2));
 
             assert_eq!(Swork::work_reports(&legal_pk).unwrap(), WorkReport {
                 report_slot: 300,
@@ -3700,4 +3737,421 @@ fn spower_delay_should_work() {
                 reported_files_root: hex::decode("11").unwrap()
             });
         });
-}
\ No newline at end of file
+}
+
+#[test]
+fn spower_operators_should_attest_the_same_batch() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            let alice: AccountId = Sr25519Keyring::Alice.to_account_id();
+            let bob: AccountId = Sr25519Keyring::Bob.to_account_id();
+            let charlie: AccountId = Sr25519Keyring::Charlie.to_account_id();
+            let dave: AccountId = Sr25519Keyring::Dave.to_account_id();
+            let cid = "QmdwgqZy1MZBfWPi7GcxVsYgJEtmvHg6rsLzbCej3tf3oF".as_bytes().to_vec();
+
+            assert_ok!(Swork::register_spower_operator(Origin::root(), alice.clone()));
+            assert_ok!(Swork::register_spower_operator(Origin::root(), bob.clone()));
+            assert_ok!(Swork::register_spower_operator(Origin::root(), charlie.clone()));
+            assert_noop!(
+                Swork::register_spower_operator(Origin::root(), alice.clone()),
+                DispatchError::Module {
+                    index: 2,
+                    error: 23,
+                    message: Some("SpowerOperatorAlreadyExist"),
+                }
+            );
+            assert_eq!(Swork::spower_operators_count(), 3);
+            assert_noop!(
+                Swork::set_spower_attest_threshold(Origin::root(), 0),
+                DispatchError::Module {
+                    index: 2,
+                    error: 26,
+                    message: Some("InvalidSpowerAttestThreshold"),
+                }
+            );
+            // The threshold cannot be more than the operators count
+            assert_noop!(
+                Swork::set_spower_attest_threshold(Origin::root(), 4),
+                DispatchError::Module {
+                    index: 2,
+                    error: 26,
+                    message: Some("InvalidSpowerAttestThreshold"),
+                }
+            );
+            assert_ok!(Swork::set_spower_attest_threshold(Origin::root(), 2));
+
+            // 1. Only spower operators can submit
+            run_to_block(303);
+            assert_noop!(
+                Swork::update_spower(Origin::signed(dave.clone()), vec![], vec![], 300),
+                DispatchError::Module {
+                    index: 2,
+                    error: 22,
+                    message: Some("IllegalSpowerOperator"),
+                }
+            );
+
+            // 2. The batch is applied after 2 operators attested it
+            assert_ok!(Swork::update_spower(Origin::signed(alice.clone()), vec![], vec![], 300));
+            assert_eq!(Swork::last_spower_update_block(), 0);
+            assert_noop!(
+                Swork::update_spower(Origin::signed(alice.clone()), vec![], vec![], 300),
+                DispatchError::Module {
+                    index: 2,
+                    error: 25,
+                    message: Some("SpowerBatchAlreadyAttested"),
+                }
+            );
+            assert_ok!(Swork::update_spower(Origin::signed(bob.clone()), vec![], vec![], 300));
+            assert_eq!(Swork::last_spower_update_block(), 303);
+            assert_eq!(Swork::applied_spower_batches(b"update_spower".to_vec()).map(|(block, _)| block), Some(300));
+
+            // 3. Attest different batches for the same last processed block would be slashed
+            assert_ok!(Market::update_replicas(Origin::signed(alice.clone()), vec![], 400));
+            let round: Hash = (b"update_replicas", 400u32).using_encoded(blake2_256).into();
+            assert_eq!(Swork::spower_batch_attestations(&round, &alice).is_some(), true);
+            assert_ok!(Market::update_replicas(Origin::signed(alice.clone()), vec![(cid.clone(), 1, vec![])], 400));
+            assert_eq!(Swork::spower_operators(&alice), None);
+            assert_eq!(Swork::spower_operators_count(), 2);
+
+            // 4. The vote from the slashed operator is not counted
+            assert_ok!(Market::update_replicas(Origin::signed(bob.clone()), vec![], 400));
+            assert_eq!(Swork::last_processed_block_work_reports(), 0);
+            assert_ok!(Market::update_replicas(Origin::signed(charlie.clone()), vec![], 400));
+            assert_eq!(Swork::last_processed_block_work_reports(), 400);
+            assert_eq!(Swork::spower_batch_attestations(&round, &bob), None);
+            assert_eq!(Swork::spower_batch_rounds(&round), None);
+        });
+}
+
+#[test]
+fn applied_spower_batch_should_not_be_replayed() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            let alice: AccountId = Sr25519Keyring::Alice.to_account_id();
+            let bob: AccountId = Sr25519Keyring::Bob.to_account_id();
+            let charlie: AccountId = Sr25519Keyring::Charlie.to_account_id();
+            let legal_wr_info = legal_work_report();
+            let legal_pk = legal_wr_info.curr_pk.clone();
+
+            assert_ok!(Swork::register_spower_operator(Origin::root(), alice.clone()));
+            assert_ok!(Swork::register_spower_operator(Origin::root(), bob.clone()));
+            assert_ok!(Swork::register_spower_operator(Origin::root(), charlie.clone()));
+            assert_ok!(Swork::set_spower_attest_threshold(Origin::root(), 2));
+
+            run_to_block(303);
+            add_wr(&legal_pk, &WorkReport {
+                report_slot: 300,
+                spower: 100,
+                free: 0,
+                reported_files_size: 0,
+                reported_srd_root: hex::decode("00").unwrap(),
+                reported_files_root: hex::decode("11").unwrap()
+            });
+
+            // 1. Apply the batch with alice and bob
+            assert_ok!(Swork::update_spower(Origin::signed(alice.clone()), vec![(legal_pk.clone(), 10)], vec![], 300));
+            assert_ok!(Swork::update_spower(Origin::signed(bob.clone()), vec![(legal_pk.clone(), 10)], vec![], 300));
+            assert_eq!(Swork::work_reports(&legal_pk).unwrap().spower, 110);
+
+            // 2. The late vote of charlie cannot bring it back
+            assert_noop!(
+                Swork::update_spower(Origin::signed(charlie.clone()), vec![(legal_pk.clone(), 10)], vec![], 300),
+                DispatchError::Module {
+                    index: 2,
+                    error: 30,
+                    message: Some("SpowerBatchAlreadyApplied"),
+                }
+            );
+            assert_noop!(
+                Swork::update_spower(Origin::signed(charlie.clone()), vec![(legal_pk.clone(), 10)], vec![], 299),
+                DispatchError::Module {
+                    index: 2,
+                    error: 30,
+                    message: Some("SpowerBatchAlreadyApplied"),
+                }
+            );
+            assert_eq!(Swork::work_reports(&legal_pk).unwrap().spower, 110);
+
+            // 3. The same changes can still be applied for a new batch block
+            assert_ok!(Swork::update_spower(Origin::signed(charlie.clone()), vec![(legal_pk.clone(), 10)], vec![], 301));
+            assert_ok!(Swork::update_spower(Origin::signed(alice.clone()), vec![(legal_pk.clone(), 10)], vec![], 301));
+            assert_eq!(Swork::work_reports(&legal_pk).unwrap().spower, 120);
+
+            // 4. The unapproved round expires
+            assert_ok!(Swork::update_spower(Origin::signed(bob.clone()), vec![(legal_pk.clone(), 10)], vec![], 302));
+            let round: Hash = (b"update_spower", 302u32).using_encoded(blake2_256).into();
+            let expire_at = 303 + SPOWER_BATCH_ROUND_LIFETIME;
+            assert_eq!(Swork::spower_batch_rounds(&round), Some(expire_at));
+            assert_eq!(Swork::spower_batch_rounds_to_expire(expire_at), vec![round]);
+            Swork::on_initialize(expire_at as u64);
+            assert_eq!(Swork::spower_batch_rounds(&round), None);
+            assert_eq!(Swork::spower_batch_attestations(&round, &bob), None);
+            assert_eq!(Swork::spower_batch_rounds_to_expire(expire_at), Vec::<Hash>::new());
+        });
+}
+
+#[test]
+fn spower_attest_threshold_should_follow_the_operators_count() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            let alice: AccountId = Sr25519Keyring::Alice.to_account_id();
+            let bob: AccountId = Sr25519Keyring::Bob.to_account_id();
+            let charlie: AccountId = Sr25519Keyring::Charlie.to_account_id();
+            let cid = "QmdwgqZy1MZBfWPi7GcxVsYgJEtmvHg6rsLzbCej3tf3oF".as_bytes().to_vec();
+
+            assert_ok!(Swork::register_spower_operator(Origin::root(), alice.clone()));
+            assert_ok!(Swork::register_spower_operator(Origin::root(), bob.clone()));
+            assert_ok!(Swork::register_spower_operator(Origin::root(), charlie.clone()));
+            assert_ok!(Swork::set_spower_attest_threshold(Origin::root(), 3));
+
+            // 1. Removing an operator decreases the threshold to the operators count
+            assert_ok!(Swork::remove_spower_operator(Origin::root(), charlie.clone()));
+            assert_eq!(Swork::spower_operators_count(), 2);
+            assert_eq!(Swork::spower_attest_threshold(), 2);
+
+            // 2. Slashing an operator decreases the threshold as well
+            run_to_block(303);
+            assert_ok!(Market::update_replicas(Origin::signed(alice.clone()), vec![], 300));
+            assert_ok!(Market::update_replicas(Origin::signed(alice.clone()), vec![(cid.clone(), 1, vec![])], 300));
+            assert_eq!(Swork::spower_operators_count(), 1);
+            assert_eq!(Swork::spower_attest_threshold(), 1);
+
+            // 3. The remaining operator can still apply the batch
+            assert_ok!(Swork::update_spower(Origin::signed(bob.clone()), vec![], vec![], 300));
+            assert_eq!(Swork::last_spower_update_block(), 303);
+
+            // 4. The threshold never goes to 0
+            assert_ok!(Swork::remove_spower_operator(Origin::root(), bob.clone()));
+            assert_eq!(Swork::spower_operators_count(), 0);
+            assert_eq!(Swork::spower_attest_threshold(), 1);
+        });
+}
+
+#[test]
+fn spower_batch_in_different_orders_should_be_the_same() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            let alice: AccountId = Sr25519Keyring::Alice.to_account_id();
+            let bob: AccountId = Sr25519Keyring::Bob.to_account_id();
+            let legal_pk = legal_work_report().curr_pk;
+            let another_pk = hex::decode("11").unwrap();
+
+            assert_ok!(Swork::register_spower_operator(Origin::root(), alice.clone()));
+            assert_ok!(Swork::register_spower_operator(Origin::root(), bob.clone()));
+            assert_ok!(Swork::set_spower_attest_threshold(Origin::root(), 2));
+
+            run_to_block(303);
+            for pk in [legal_pk.clone(), another_pk.clone()].iter() {
+                add_wr(pk, &WorkReport {
+                    report_slot: 300,
+                    spower: 100,
+                    free: 0,
+                    reported_files_size: 0,
+                    reported_srd_root: hex::decode("00").unwrap(),
+                    reported_files_root: hex::decode("11").unwrap()
+                });
+            }
+
+            // Bob attests the same changes as alice in a different order, he should not be slashed
+            assert_ok!(Swork::update_spower(Origin::signed(alice.clone()), vec![(legal_pk.clone(), 10), (another_pk.clone(), -10)], vec![], 300));
+            assert_ok!(Swork::update_spower(Origin::signed(bob.clone()), vec![(another_pk.clone(), -10), (legal_pk.clone(), 10)], vec![], 300));
+            assert_eq!(Swork::spower_operators_count(), 2);
+            assert_eq!(Swork::work_reports(&legal_pk).unwrap().spower, 110);
+            assert_eq!(Swork::work_reports(&another_pk).unwrap().spower, 90);
+        });
+}
+
+#[test]
+fn report_works_with_spot_check_proofs_should_work() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            let reporter: AccountId = Sr25519Keyring::Alice.to_account_id();
+            let pk = hex::decode("d080e3680a046545e66a160823f184def3530c870b46249cc8bf90db5ada6726787b18ba7bca0f1f5594ada3f8384e9ff5f6aa67297dee4af77034ae9b988c5c").unwrap();
+            let block_hash = hex::decode("0000000000000000000000000000000000000000000000000000000000000000").unwrap();
+            let srd_root = hex::decode("00").unwrap();
+            let files_root = hex::decode("11").unwrap();
+            // Signed over the work report of slot 600 without any files
+            let wr_sig = hex::decode("500982ecdd14cd8a7bbf5b39dc4dfc60437f1cbe98345edb4e76e88ff53636bb7908393177b180d089a89c2de0acde3b5924df5353feeec5329ae666b439dd42").unwrap();
+            let cid = "QmdwgqZy1MZBfWPi7GcxVsYgJEtmvHg6rsLzbCej3tf3oF".as_bytes().to_vec();
+            let nonce = [7u8; 32];
+            let chunk_hash = hex::decode("5ca7dc9d5c4b87f0b7bfbab3c4f7d3f24e0e2b2b6e1c8a9dd6f6c02ff3a1b2c4").unwrap();
+            // Signed over pk ++ "300" ++ cid ++ nonce ++ chunk_hash
+            let sig = hex::decode("177d59c67aeb6490d8eb5815df9707bfbcaf69cde36acdc2facd1a5b1bb1455ce051bc5bdd9359b922a140cb04c962a6baeadd0b2088271c30709fdf3a98e543").unwrap();
+            let report_works_with_proofs = |proofs: Vec<(ReportSlot, MerkleRoot, MerkleRoot, SworkerSignature)>| {
+                Swork::report_works(
+                    Origin::signed(reporter.clone()),
+                    pk.clone(),
+                    vec![],
+                    600,
+                    block_hash.clone(),
+                    0,
+                    0,
+                    vec![],
+                    vec![],
+                    srd_root.clone(),
+                    files_root.clone(),
+                    wr_sig.clone(),
+                    proofs
+                )
+            };
+
+            run_to_block(603);
+            register(&pk, LegalCode::get());
+            <market::SpotChecks<Test>>::insert(300, (cid.clone(), pk.clone()), (reporter.clone(), nonce));
+
+            // 1. Exceed the proofs limit
+            assert_noop!(
+                report_works_with_proofs(vec![(300, cid.clone(), chunk_hash.clone(), sig.clone()); 101]),
+                DispatchError::Module {
+                    index: 2,
+                    error: 27,
+                    message: Some("ExceedSpotCheckProofsLimit"),
+                }
+            );
+
+            // 2. Proofs can only answer the past slots
+            assert_noop!(
+                report_works_with_proofs(vec![(600, cid.clone(), chunk_hash.clone(), sig.clone())]),
+                DispatchError::Module {
+                    index: 2,
+                    error: 28,
+                    message: Some("InvalidSpotCheckSlot"),
+                }
+            );
+
+            // 3. The sig should cover the nonce and the chunk hash
+            assert_noop!(
+                report_works_with_proofs(vec![(300, cid.clone(), vec![0u8; 32], sig.clone())]),
+                DispatchError::Module {
+                    index: 2,
+                    error: 29,
+                    message: Some("IllegalSpotCheckProofSig"),
+                }
+            );
+            <market::SpotChecks<Test>>::insert(300, (cid.clone(), pk.clone()), (reporter.clone(), [8u8; 32]));
+            assert_noop!(
+                report_works_with_proofs(vec![(300, cid.clone(), chunk_hash.clone(), sig.clone())]),
+                DispatchError::Module {
+                    index: 2,
+                    error: 29,
+                    message: Some("IllegalSpotCheckProofSig"),
+                }
+            );
+            <market::SpotChecks<Test>>::insert(300, (cid.clone(), pk.clone()), (reporter.clone(), nonce));
+
+            // 4. Valid proof passes the spot check along with the work report, stale proof is ignored
+            assert_ok!(report_works_with_proofs(vec![
+                (300, cid.clone(), chunk_hash.clone(), sig.clone()),
+                (0, cid.clone(), chunk_hash.clone(), sig.clone())
+            ]));
+            assert_eq!(Market::spot_checks(300, (cid.clone(), pk.clone())), None);
+            assert!(Swork::work_reports(&pk).is_some());
+            assert_eq!(Swork::identities(&reporter).unwrap_or_default().anchor, pk);
+        });
+}
+
+#[test]
+fn replicas_commitment_should_be_chained_in_queued_order() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            let alice: AccountId = Sr25519Keyring::Alice.to_account_id();
+            let bob: AccountId = Sr25519Keyring::Bob.to_account_id();
+            let anchor = hex::decode("11").unwrap();
+            let cid1 = "QmdwgqZy1MZBfWPi7GcxVsYgJEtmvHg6rsLzbCej3tf3oF".as_bytes().to_vec();
+            let cid2 = "QmdwgqZy1MZBfWPi7GcxVsYgJEtmvHg6rsLzbCej3tf3oH".as_bytes().to_vec();
+
+            // 1. Queue 2 replicas in block 301 and 1 replica in block 302
+            Swork::queue_replica_commitment(301, &cid1, 100, &alice, &alice, &anchor, 300, 301, 303, true);
+            Swork::queue_replica_commitment(301, &cid2, 200, &bob, &bob, &anchor, 300, 301, 303, true);
+            Swork::queue_replica_commitment(302, &cid1, 100, &alice, &alice, &anchor, 300, 302, 303, false);
+            assert_eq!(Swork::replicas_commitment_blocks_range(), (0, 2));
+            assert_eq!(Swork::latest_replicas_commitment(), Swork::replicas_commitments(302).unwrap());
+
+            let replica1 = (cid1.clone(), 100, vec![(alice.clone(), alice.clone(), anchor.clone(), 300, 301, 303, true)]);
+            let replica2 = (cid2.clone(), 200, vec![(bob.clone(), bob.clone(), anchor.clone(), 300, 301, 303, true)]);
+            let replica3 = (cid1.clone(), 100, vec![(alice.clone(), alice.clone(), anchor.clone(), 300, 302, 303, false)]);
+
+            // 2. The replicas should be replayed in the queued order until a queued block
+            assert!(Swork::check_replicas_commitment(302, &vec![replica1.clone(), replica2.clone(), replica3.clone()]));
+            assert!(Swork::check_replicas_commitment(301, &vec![replica1.clone(), replica2.clone()]));
+            assert!(!Swork::check_replicas_commitment(302, &vec![replica2.clone(), replica1.clone(), replica3.clone()]));
+            assert!(!Swork::check_replicas_commitment(302, &vec![replica1.clone(), replica3.clone()]));
+            assert!(!Swork::check_replicas_commitment(301, &vec![replica1.clone()]));
+            assert!(!Swork::check_replicas_commitment(301, &vec![replica1.clone(), replica2.clone(), replica3.clone()]));
+            // A cid can appear more than once, but its replicas cannot be merged out of order
+            let merged = (cid1.clone(), 100, vec![
+                (alice.clone(), alice.clone(), anchor.clone(), 300, 301, 303, true),
+                (alice.clone(), alice.clone(), anchor.clone(), 300, 302, 303, false)
+            ]);
+            assert!(!Swork::check_replicas_commitment(302, &vec![merged, replica2.clone()]));
+            // Block without queued replicas only accepts an empty batch
+            assert!(Swork::check_replicas_commitment(300, &vec![]));
+            assert!(!Swork::check_replicas_commitment(300, &vec![replica1.clone()]));
+
+            // 3. Process until block 301
+            let commitment_301 = Swork::replicas_commitments(301).unwrap();
+            Swork::update_last_processed_block_of_work_reports(301);
+            assert_eq!(Swork::processed_replicas_commitment(), commitment_301);
+            assert_eq!(Swork::replicas_commitments(301), None);
+            assert_eq!(Swork::replicas_commitment_blocks(0), None);
+            assert_eq!(Swork::replicas_commitment_blocks_range(), (1, 2));
+
+            // 4. The processed replicas cannot be replayed again
+            assert!(!Swork::check_replicas_commitment(302, &vec![replica1.clone(), replica2.clone(), replica3.clone()]));
+            assert!(Swork::check_replicas_commitment(302, &vec![replica3.clone()]));
+
+            // 5. Process until block 302
+            Swork::update_last_processed_block_of_work_reports(302);
+            assert_eq!(Swork::processed_replicas_commitment(), Swork::latest_replicas_commitment());
+            assert_eq!(Swork::replicas_commitments(302), None);
+            assert_eq!(Swork::replicas_commitment_blocks_range(), (2, 2));
+            assert!(Swork::check_replicas_commitment(303, &vec![]));
+        });
+}
+
+#[test]
+fn report_works_should_queue_replicas_commitment() {
+    ExtBuilder::default()
+        .build()
+        .execute_with(|| {
+            let reporter: AccountId = Sr25519Keyring::Alice.to_account_id();
+            let legal_wr_info = legal_work_report_with_added_files();
+            let legal_pk = legal_wr_info.curr_pk.clone();
+            let added_files = legal_wr_info.added_files.clone();
+
+            register(&legal_pk, LegalCode::get());
+            add_not_live_files();
+
+            run_to_block(303);
+            assert_ok!(Swork::report_works(
+                Origin::signed(reporter.clone()),
+                legal_wr_info.curr_pk,
+                legal_wr_info.prev_pk,
+                legal_wr_info.block_number,
+                legal_wr_info.block_hash,
+                legal_wr_info.free,
+                legal_wr_info.spower,
+                legal_wr_info.added_files,
+                legal_wr_info.deleted_files,
+                legal_wr_info.srd_root,
+                legal_wr_info.files_root,
+                legal_wr_info.sig,
+                vec![]
+            ));
+
+            let replicas: Vec<(MerkleRoot, u64, Vec<(AccountId, AccountId, SworkerAnchor, ReportSlot, BlockNumber, BlockNumber, bool)>)> = added_files.iter()
+                .map(|(cid, file_size, valid_at)| (cid.clone(), *file_size, vec![(reporter.clone(), reporter.clone(), legal_pk.clone(), 300, 303, *valid_at as BlockNumber, true)]))
+                .collect();
+            let mut reversed = replicas.clone();
+            reversed.reverse();
+            assert!(Swork::check_replicas_commitment(303, &replicas));
+            assert!(!Swork::check_replicas_commitment(303, &reversed));
+        });
+}
@@ -41,14 +41,19 @@ impl<T: frame_system::Config> crate::WeightInfo for WeightInfo<T> {
 			.saturating_add(T::DbWeight::get().reads(6 as Weight))
 			.saturating_add(T::DbWeight::get().writes(3 as Weight))
 	}
-	fn report_works(added: u32, deleted: u32) -> Weight {
+	fn report_works(added: u32, deleted: u32, spot_check_proofs: u32) -> Weight {
 		(3_505_000_000 as Weight)
-			.saturating_add((700_000_000 as Weight).saturating_mul(added as Weight))
-			.saturating_add((350_000_000 as Weight).saturating_mul(deleted as Weight))
-			.saturating_add(T::DbWeight::get().reads(2 as Weight).saturating_mul(added as Weight))
-			.saturating_add(T::DbWeight::get().writes(2 as Weight).saturating_mul(added as Weight))
-			.saturating_add(T::DbWeight::get().reads(2 as Weight).saturating_mul(deleted as Weight))
-			.saturating_add(T::DbWeight::get().writes(2 as Weight).saturating_mul(deleted as Weight))
+			.saturating_add((705_000_000 as Weight).saturating_mul(added as Weight))
+			.saturating_add((355_000_000 as Weight).saturating_mul(deleted as Weight))
+			.saturating_add((1_000_000_000 as Weight).saturating_mul(spot_check_proofs as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight).saturating_mul(added as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight).saturating_mul(added as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight).saturating_mul(deleted as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight).saturating_mul(deleted as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight).saturating_mul(spot_check_proofs as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight).saturating_mul(spot_check_proofs as Weight))
 	}
 	fn update_spower(changed_sworkers_count: u32, changed_files_count: u32) -> Weight {
 		(1_000_000_000 as Weight)
@@ -4,7 +4,7 @@
 use hex_literal::hex;
 use sp_core::{Pair, Public, sr25519, crypto::UncheckedInto};
 use crust_runtime::{
-    AuthorityDiscoveryId, BalancesConfig, GenesisConfig, ImOnlineId,
+    AuthorityDiscoveryId, BalancesConfig, CSMConfig, GenesisConfig, ImOnlineId,
     AuthorityDiscoveryConfig, SessionConfig, SessionKeys, StakerStatus,
     StakingConfig, IndicesConfig, SystemConfig, SworkConfig,
     WASM_BINARY, LocksConfig
@@ -204,6 +204,7 @@ fn testnet_genesis(
     _enable_println: bool,
 ) -> GenesisConfig {
     const ENDOWMENT: u128 = 1_000_000 * CRUS;
+    const CSM_ENDOWMENT: u128 = 1_000_000 * CRUS;
     const STASH: u128 = 20_000 * CRUS;
     GenesisConfig {
         // pallet_sudo: Some(SudoConfig {
@@ -220,6 +221,13 @@ fn testnet_genesis(
                 .map(|k| (k, ENDOWMENT))
                 .collect(),
         }),
+        balances_Instance2: Some(CSMConfig {
+            balances: endowed_accounts
+                .iter()
+                .cloned()
+                .map(|k| (k, CSM_ENDOWMENT))
+                .collect(),
+        }),
         pallet_indices: Some(IndicesConfig {
             indices: vec![],
         }),
@@ -328,6 +336,9 @@ fn rocky_staging_testnet_config_genesis(wasm_binary: &[u8]) -> GenesisConfig {
                 .map(|k| (k, ENDOWMENT))
                 .collect(),
         }),
+        balances_Instance2: Some(CSMConfig {
+            balances: vec![],
+        }),
         pallet_indices: Some(IndicesConfig {
             indices: vec![],
         }),
@@ -625,6 +636,9 @@ fn mainnet_staging_testnet_config_genesis(wasm_binary: &[u8]) -> GenesisConfig {
                 .map(|k| (k, ENDOWMENT))
                 .collect(),
         }),
+        balances_Instance2: Some(CSMConfig {
+            balances: vec![],
+        }),
         pallet_indices: Some(IndicesConfig {
             indices: vec![],
         }),
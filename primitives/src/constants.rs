@@ -10,8 +10,8 @@ pub mod currency {
     pub const CENTS: Balance = DOLLARS / 100;
     pub const MILLICENTS: Balance = CENTS / 1_000;
 
-    // GPoS rewards in the first year
-    pub const FIRST_YEAR_REWARDS: Balance = 5_000_000 * CRUS;
+    /// CSM has the same decimals with CRU.
+    pub const CSMS: Balance = 1_000_000_000_000;
 
     pub const fn deposit(items: u32, bytes: u32) -> Balance {
 		items as Balance * 1_000 * CENTS + (bytes as Balance) * 100 * MILLICENTS
@@ -51,16 +51,6 @@ pub mod fee {
     pub const TARGET_BLOCK_FULLNESS: Perbill = Perbill::from_percent(25);
 }
 
-pub mod staking {
-    use crate::Balance;
-    // The reward decrease ratio per year
-    pub const REWARD_DECREASE_RATIO: (Balance, Balance) = (88, 100);
-    // The minimal reward ratio
-    pub const MIN_REWARD_RATIO: (Balance, Balance) = (28, 1000);
-    // The start year for extra reward
-    pub const EXTRA_REWARD_START_YEAR: u64 = 4;
-}
-
 pub mod swork {
     use super::time::*;
 
@@ -86,6 +76,8 @@ pub mod market {
     pub const PRICE_UPDATE_SLOT: u32 = 600;
     pub const PRICE_UPDATE_OFFSET: u32 = 3;
 
+    pub const SPOT_CHECK_OFFSET: u32 = 13;
+
     pub const SPOWER_UPDATE_SLOT: u32 = 100;
     pub const SPOWER_UPDATE_OFFSET: u32 = 7;
     pub const MAX_PENDING_FILES: usize = 20;
@@ -63,6 +63,9 @@ pub type SworkerAnchor = SworkerPubKey;
 /// sworker signature, little-endian-format, 64 bytes vec
 pub type SworkerSignature = Vec<u8>;
 
+/// Random nonce of a replica spot check, which should be signed in the proof
+pub type SpotCheckNonce = [u8; 32];
+
 /// sworker enclave code
 pub type SworkerCode = Vec<u8>;
 
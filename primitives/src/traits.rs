@@ -2,7 +2,7 @@
 // This file is part of Crust.
 
 use frame_support::traits::{LockableCurrency, WithdrawReasons};
-use crate::{BlockNumber, EraIndex, MerkleRoot, ReportSlot, SworkerAnchor};
+use crate::{BlockNumber, EraIndex, Hash, MerkleRoot, ReportSlot, SworkerAnchor, SpotCheckNonce};
 use sp_runtime::{DispatchError, Perbill};
 use sp_std::collections::btree_map::BTreeMap;
 use sp_std::vec::Vec;
@@ -32,16 +32,29 @@ pub trait SworkerInterface<AccountId> {
 	fn update_last_processed_block_of_work_reports(last_processed_block: BlockNumber);
 	// Update changed spower of sworkers
 	fn update_sworkers_changed_spower(sworker_spower_changed_map: &BTreeMap<SworkerAnchor, i64>);
+	// Check whether the replicas to update match the replicas queued by report works until the last processed block
+	fn check_replicas_commitment(
+		last_processed_block: BlockNumber,
+		file_infos_map: &Vec<(MerkleRoot, u64, Vec<(AccountId, AccountId, SworkerAnchor, ReportSlot, BlockNumber, BlockNumber, bool)>)>
+	) -> bool;
 	// Update illegal file replicas count
 	fn update_illegal_file_replicas_count(illegal_file_replicas_map: &BTreeMap<ReportSlot, u32>);
+	// Attest a spower batch of the kind for the batch block by the spower operator, return true if enough operators attested the same batch
+	fn attest_spower_batch(who: &AccountId, kind: &[u8], batch_block: BlockNumber, batch_hash: Hash) -> Result<bool, DispatchError>;
 }
 
 /// Means for interacting with a specialized version of the `market` trait.
 pub trait MarketInterface<AccountId, Balance> {
 	// used for distribute market staking payout
 	fn withdraw_staking_pot() -> Balance;
+	// used for projecting market staking payout
+	fn staking_pot_balance() -> Balance;
 	// Update files spower in market::FilesV2
 	fn update_files_spower(changed_files: &Vec<(MerkleRoot, u64, Vec<(AccountId, AccountId, SworkerAnchor, Option<BlockNumber>)>)>);
+	// Get the nonce of the spot check drawn in the report slot, return None if there is no such spot check
+	fn spot_check_nonce(anchor: &SworkerAnchor, cid: &MerkleRoot, report_slot: ReportSlot) -> Option<SpotCheckNonce>;
+	// Pass the spot check of the replica drawn in the report slot, return false if there is no such spot check
+	fn pass_spot_check(anchor: &SworkerAnchor, cid: &MerkleRoot, report_slot: ReportSlot) -> bool;
 }
 
 pub trait BenefitInterface<AccountId, Balance, NegativeImbalance> {
@@ -53,11 +66,25 @@ pub trait BenefitInterface<AccountId, Balance, NegativeImbalance> {
 
 	fn maybe_free_count(who: &AccountId) -> bool;
 
+	fn maybe_compound_reward(who: &AccountId, reward: Balance) -> Balance;
+
 	fn get_collateral_and_reward(who: &AccountId) -> (Balance, Balance);
 
 	fn get_market_funds_ratio(who: &AccountId) -> Perbill;
 }
 
+/// Means for interacting with a specialized version of the `csm-locking` trait.
+pub trait CsmInterface<AccountId, Balance> {
+	// Get the active locking CSM of the account
+	fn locked_csm(who: &AccountId) -> Balance;
+	// Get the total CSM guaranteed to the target
+	fn guaranteed_csm(target: &AccountId) -> Balance;
+	// Get the guarantee target and the guaranteed CSM of the account
+	fn guarantee_of(who: &AccountId) -> Option<(AccountId, Balance)>;
+	// Get the guarantee pref of the target
+	fn guarantee_pref(target: &AccountId) -> Option<u32>;
+}
+
 pub trait LocksInterface<AccountId, Balance> {
 	fn create_cru18_lock(who: &AccountId, value: Balance);
 }
\ No newline at end of file
@@ -5,7 +5,7 @@ use frame_support::traits::{OnUnbalanced, Currency, Imbalance, WithdrawReasons,
 use frame_support::unsigned::TransactionValidityError;
 use frame_support::weights::{WeightToFeePolynomial, WeightToFeeCoefficients, WeightToFeeCoefficient};
 use frame_support::dispatch::{GetCallMetadata, CallMetadata};
-use crate::{Balances, Authorship, NegativeImbalance};
+use crate::{Balances, Authorship, NegativeImbalance, FeeReductionQuotaPerCsm};
 use sp_arithmetic::{Perbill, traits::{BaseArithmetic, Unsigned}};
 use smallvec::smallvec;
 use sp_std::marker::PhantomData;
@@ -14,7 +14,7 @@ use sp_runtime::{
     transaction_validity::InvalidTransaction,
 };
 use pallet_transaction_payment::{OnChargeTransaction};
-use primitives::traits::BenefitInterface;
+use primitives::{Balance, constants::currency::CSMS, traits::BenefitInterface};
 
 /// Logic for the author to get a portion of fees.
 pub struct Author;
@@ -49,6 +49,16 @@ impl Convert<u64, u128> for CurrencyToVoteHandler {
     }
 }
 
+/// Convert the locking and guaranteed CSM into the extra fee reduction quota in CRU,
+/// each CSM gives `FeeReductionQuotaPerCsm`.
+pub struct CsmToFeeReductionQuota;
+
+impl Convert<Balance, Balance> for CsmToFeeReductionQuota {
+    fn convert(csm: Balance) -> Balance {
+        csm.saturating_mul(FeeReductionQuotaPerCsm::get()) / CSMS
+    }
+}
+
 /// Implementor of `WeightToFeePolynomial` that maps one unit of weight to one unit of fee.
 pub struct OneTenthFee<T>(sp_std::marker::PhantomData<T>);
 
@@ -61,7 +61,7 @@ use pallet_session::{historical as session_historical};
 pub use pallet_timestamp::Call as TimestampCall;
 
 /// Implementations of some helper traits passed into runtime modules as associated types.
-use impls::{CurrencyToVoteHandler, Author, OneTenthFee, CurrencyAdapter};
+use impls::{CurrencyToVoteHandler, Author, OneTenthFee, CurrencyAdapter, CsmToFeeReductionQuota};
 
 /// Crust primitives
 use primitives::{
@@ -103,10 +103,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
     spec_name: create_runtime_str!("crust"),
     impl_name: create_runtime_str!("crustio-crust"),
     authoring_version: 1,
-    spec_version: 25,
+    spec_version: 26,
     impl_version: 1,
     apis: RUNTIME_API_VERSIONS,
-    transaction_version: 1
+    transaction_version: 9
 };
 
 /// The version information used to identify this runtime when compiled natively.
@@ -393,14 +393,22 @@ parameter_types! {
     pub const BondingDuration: EraIndex = 28 * 4;
     // 108 eras in which slashes can be cancelled (slightly less than 28 days).
     pub const SlashDeferDuration: EraIndex = 27 * 4;
+    // Keep counting the guarantees submitted before the most recent slash of the validators.
+    pub const FilterSlashedGuarantees: bool = false;
     // 1 * CRUs / TB, since we treat 1 TB = 1_000_000_000_000, so the ratio = `1`
     pub const SPowerRatio: u128 = 1;
     // 64 guarantors for one validator.
     pub const MaxGuarantorRewardedPerValidator: u32 = 64;
+    // 64 guarantors at most for one validator, so every guarantor would be rewarded.
+    pub const MaxGuarantorsPerValidator: u32 = 64;
     // 60 eras means 15 days if era = 6 hours
     pub const MarketStakingPotDuration: u32 = 60;
     // free transfer amount for other locks
     pub const UncheckedFrozenBondFund: Balance = 1 * DOLLARS;
+    // 10% commission increase at most for each change
+    pub const MaxCommissionIncreasePerChange: Perbill = Perbill::from_percent(10);
+    // 4 eras means 1 day if era = 6 hours
+    pub const CommissionIncreaseDelay: EraIndex = 4;
 }
 
 impl staking::Config for Runtime {
@@ -413,20 +421,25 @@ impl staking::Config for Runtime {
     type Event = Event;
     type Slash = Treasury;
     type Reward = ();
-    type Randomness = RandomnessCollectiveFlip;
+    type Randomness = Babe;
     type SessionsPerEra = SessionsPerEra;
     type BondingDuration = BondingDuration;
     type MaxGuarantorRewardedPerValidator = MaxGuarantorRewardedPerValidator;
+    type MaxGuarantorsPerValidator = MaxGuarantorsPerValidator;
     type SlashDeferDuration = SlashDeferDuration;
 
     // A majority of the council can cancel the slash.
     type SlashCancelOrigin = frame_system::EnsureRoot<Self::AccountId>;
+    type TokenomicsOrigin = EnsureRootOrHalfCouncil;
+    type FilterSlashedGuarantees = FilterSlashedGuarantees;
     type SessionInterface = Self;
     type SPowerRatio = SPowerRatio;
     type MarketStakingPot = Market;
     type MarketStakingPotDuration = MarketStakingPotDuration;
     type BenefitInterface = Benefits;
     type UncheckedFrozenBondFund = UncheckedFrozenBondFund;
+    type MaxCommissionIncreasePerChange = MaxCommissionIncreasePerChange;
+    type CommissionIncreaseDelay = CommissionIncreaseDelay;
     type WeightInfo = staking::weight::WeightInfo;
 }
 
@@ -697,9 +710,26 @@ impl balances::Config<balances::Instance1> for Runtime {
     type MaxLocks = MaxLocks;
 }
 
+impl balances::Config<balances::Instance2> for Runtime {
+    type Balance = Balance;
+    type DustRemoval = ();
+    type Event = Event;
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = StorageMapShim<
+        balances::Account<Runtime, balances::Instance2>,
+        frame_system::Provider<Runtime>,
+        AccountId,
+        balances::AccountData<Balance>,
+    >;
+    type WeightInfo = weights::pallet_balances::WeightInfo<Runtime>;
+    type MaxLocks = MaxLocks;
+}
+
 parameter_types! {
     pub const ClaimsModuleId: ModuleId = ModuleId(*b"crclaims");
     pub Prefix: &'static [u8] = b"Pay CRUs to the Crust account:";
+    // The claims are signed by the ethereum mainnet accounts
+    pub const ClaimsChainId: u64 = 1;
 }
 
 impl claims::Config for Runtime {
@@ -713,6 +743,7 @@ impl claims::Config for Runtime {
         EnsureRoot<AccountId>,
         pallet_collective::EnsureProportionMoreThan<_4, _5, AccountId, CouncilCollective>
     >;
+    type ChainId = ClaimsChainId;
 }
 
 // TODO: better way to deal with fee(s)
@@ -740,6 +771,7 @@ impl pallet_transaction_payment::Config for Runtime {
 parameter_types! {
     pub const PunishmentSlots: u32 = 8; // 8 report slot == 8 hours
     pub const MaxGroupSize: u32 = 1000;
+    pub const SpowerOperatorBond: Balance = 10_000 * DOLLARS;
 }
 
 impl swork::Config for Runtime {
@@ -749,6 +781,7 @@ impl swork::Config for Runtime {
     type Works = Staking;
     type MarketInterface = Market;
     type MaxGroupSize = MaxGroupSize;
+    type SpowerOperatorBond = SpowerOperatorBond;
     type BenefitInterface = Benefits;
     type WeightInfo = swork::weight::WeightInfo<Runtime>;
 }
@@ -797,12 +830,15 @@ impl market::Config for Runtime {
     type WeightInfo = market::weight::WeightInfo<Runtime>;
     type MaximumFileSize = MaximumFileSize;
     type RenewRewardRatio = RenewRewardRatio;
+    type Randomness = Babe;
 }
 
 parameter_types! {
     pub const BenefitReportWorkCost: Balance = 3 * DOLLARS;
     pub BenefitsLimitRatio: Perbill = Perbill::from_rational_approximation(2u64, 1000);
     pub const BenefitMarketCostRatio: Perbill = Perbill::one();
+    // 1 locking or guaranteed CSM gives 0.01 CRU extra fee reduction quota per era
+    pub const FeeReductionQuotaPerCsm: Balance = 1 * CENTS;
 }
 
 impl benefits::Config for Runtime {
@@ -812,21 +848,39 @@ impl benefits::Config for Runtime {
     type BenefitsLimitRatio = BenefitsLimitRatio;
     type BenefitMarketCostRatio = BenefitMarketCostRatio;
     type BondingDuration = BondingDuration;
+    type CsmInterface = CSMLocking;
+    type CsmToFeeReductionQuota = CsmToFeeReductionQuota;
     type WeightInfo = benefits::weight::WeightInfo<Runtime>;
 }
 
 
 parameter_types! {
     pub const UnlockPeriod: BlockNumber = 432_000; // 30 days
+    pub const MinVestedTransfer: Balance = 100 * DOLLARS;
+    pub const MaxVestingSchedules: u32 = 28;
 }
 
 impl locks::Config for Runtime {
     type Event = Event;
     type Currency = Balances;
     type UnlockPeriod = UnlockPeriod;
+    type MinVestedTransfer = MinVestedTransfer;
+    type MaxVestingSchedules = MaxVestingSchedules;
+    type TransferLockOrigin = EnsureRootOrHalfCouncil;
     type WeightInfo = locks::weight::WeightInfo<Runtime>;
 }
 
+parameter_types! {
+    pub const CSMBondingDuration: BlockNumber = 201_600; // 14 days
+}
+
+impl csm_locking::Config for Runtime {
+    type Currency = CSM;
+    type Event = Event;
+    type BondingDuration = CSMBondingDuration;
+    type WeightInfo = csm_locking::weight::WeightInfo;
+}
+
 parameter_types! {
     pub const BridgeChainId: u8 = 1;
     pub const ProposalLifetime: BlockNumber = 50400; // ~7 days
@@ -843,6 +897,8 @@ impl bridge::Config for Runtime {
 parameter_types! {
     // bridge::derive_resource_id(1, &bridge::hashing::blake2_128(b"CRU"));
     pub const BridgeTokenId: [u8; 32] = hex_literal::hex!("000000000000000000000000000000608d1bc9a2d146ebc94667c336721b2801");
+    // bridge::derive_resource_id(1, &bridge::hashing::blake2_128(b"CSM"));
+    pub const BridgeCsmTokenId: [u8; 32] = hex_literal::hex!("00000000000000000000000000000098aef84ac01d96413445cf3dc4d5c44c01");
 }
 
 impl bridge_transfer::Config for Runtime {
@@ -850,6 +906,8 @@ impl bridge_transfer::Config for Runtime {
     type BridgeOrigin = bridge::EnsureBridge<Runtime>;
     type Currency = Balances;
     type BridgeTokenId = BridgeTokenId;
+    type CsmCurrency = CSM;
+    type BridgeCsmTokenId = BridgeCsmTokenId;
 }
 
 construct_runtime! {
@@ -914,6 +972,10 @@ construct_runtime! {
         // ChainBridge
         ChainBridge: bridge::{Module, Call, Storage, Event<T>},
         BridgeTransfer: bridge_transfer::{Module, Call, Event<T>, Storage},
+
+        // CSM
+        CSM: balances::<Instance2>::{Module, Call, Storage, Config<T>, Event<T>},
+        CSMLocking: csm_locking::{Module, Call, Storage, Event<T>},
     }
 }
 
@@ -1134,6 +1196,28 @@ impl_runtime_apis! {
 		}
     }
 
+    impl market_runtime_api::MarketApi<Block> for Runtime {
+        fn claimable_files(from: BlockNumber, to: BlockNumber) -> Vec<(MerkleRoot, BlockNumber)> {
+            Market::claimable_files(from, to)
+        }
+    }
+
+    impl benefits_runtime_api::BenefitsApi<Block, AccountId, Balance> for Runtime {
+        fn benefit_quota(who: AccountId) -> benefits_runtime_api::BenefitQuota<Balance> {
+            Benefits::benefit_quota(&who)
+        }
+    }
+
+    impl staking_runtime_api::StakingApi<Block, AccountId, Balance> for Runtime {
+        fn slashing_report(stash: AccountId) -> staking_runtime_api::SlashingReport<AccountId, Balance> {
+            Staking::slashing_report(&stash)
+        }
+
+        fn reward_projection(eras: u32) -> staking_runtime_api::RewardProjection<Balance> {
+            Staking::reward_projection(eras)
+        }
+    }
+
     #[cfg(feature = "runtime-benchmarks")]
     impl frame_benchmarking::Benchmark<Block> for Runtime {
         fn dispatch_benchmark(
@@ -1157,7 +1241,7 @@ impl_runtime_apis! {
             add_benchmark!(params, batches, market, Market);
             add_benchmark!(params, batches, swork, SworkBench::<Runtime>);
             add_benchmark!(params, batches, benefits, Benefits);
-            // add_benchmark!(params, batches, csmlocking, CSMLocking);
+            add_benchmark!(params, batches, csm_locking, CSMLocking);
             add_benchmark!(params, batches, locks, Locks);
 
             if batches.is_empty() { return Err("Benchmark not found for this pallet.".into()) }